@@ -0,0 +1,303 @@
+//! Album art of the song being played, drawn with the
+//! [kitty graphics protocol](https://sw.kovidgoyal.net/kitty/graphics-protocol/) or with
+//! [sixel](https://en.wikipedia.org/wiki/Sixel), depending on what the terminal supports.
+//!
+//! tui can't draw images, so the browse screen leaves an area blank and the art is written
+//! straight to stdout after each frame. It's only written again when the song or the area change.
+
+use std::{
+    collections::BTreeMap,
+    io::{self, Cursor, Write},
+    path::Path,
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use crossterm::{cursor::MoveTo, queue};
+use image::{imageops::FilterType, DynamicImage, ImageOutputFormat, RgbImage};
+use tui::layout::Rect;
+
+use crate::player::PlayerState;
+
+/// Files looked for in the directory of a song that has no embedded cover
+const COVER_FILES: &[&str] = &[
+    "cover.jpg",
+    "cover.png",
+    "folder.jpg",
+    "folder.png",
+    "front.jpg",
+    "front.png",
+];
+
+/// Assumed size of a terminal cell, in pixels, used to size sixel images. Kitty fits images to
+/// the cells by itself.
+const CELL_WIDTH: u32 = 10;
+const CELL_HEIGHT: u32 = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Protocol {
+    Kitty,
+    Sixel,
+}
+
+impl Protocol {
+    /// Guesses which protocol the terminal supports from environment variables. Returns `None`
+    /// for terminals that support neither, or that we don't know about.
+    pub fn detect() -> Option<Self> {
+        let var = |name| std::env::var(name).unwrap_or_default();
+        detect_from(
+            &var("TERM"),
+            &var("TERM_PROGRAM"),
+            std::env::var_os("KITTY_WINDOW_ID").is_some(),
+        )
+    }
+}
+
+fn detect_from(term: &str, term_program: &str, kitty_window: bool) -> Option<Protocol> {
+    let kitty = kitty_window
+        || term == "xterm-kitty"
+        || term == "xterm-ghostty"
+        || term_program == "WezTerm"
+        || term_program == "ghostty";
+    let sixel = term.contains("sixel")
+        || term.starts_with("foot")
+        || term.starts_with("mlterm")
+        || term_program == "iTerm.app";
+
+    if kitty {
+        Some(Protocol::Kitty)
+    } else if sixel {
+        Some(Protocol::Sixel)
+    } else {
+        None
+    }
+}
+
+#[derive(Debug)]
+pub struct AlbumArt {
+    protocol: Protocol,
+    /// Path of the song whose cover is loaded
+    song: String,
+    image: Option<DynamicImage>,
+    /// Where the image should be drawn, as laid out by the last render
+    pub area: Option<Rect>,
+    /// Where the image was last drawn
+    drawn: Option<Rect>,
+}
+
+impl AlbumArt {
+    pub fn new(protocol: Protocol) -> Self {
+        Self {
+            protocol,
+            song: String::new(),
+            image: None,
+            area: None,
+            drawn: None,
+        }
+    }
+
+    /// Loads the cover of the song being played, if the song changed
+    pub fn update(&mut self, state: &PlayerState) {
+        if state.path == self.song {
+            return;
+        }
+
+        self.song = state.path.clone();
+        self.image = find_cover(&self.song).and_then(|data| image::load_from_memory(&data).ok());
+        // Draw the new image, even if the area is the same
+        self.drawn = None;
+    }
+
+    pub fn has_image(&self) -> bool {
+        self.image.is_some()
+    }
+
+    /// Draws the image in [Self::area], or erases it if there's no area anymore
+    pub fn draw(&mut self, out: &mut impl Write) -> io::Result<()> {
+        let area = self.area.filter(|_| self.image.is_some());
+        if area == self.drawn {
+            return Ok(());
+        }
+
+        self.erase(out)?;
+        if let (Some(area), Some(image)) = (area, &self.image) {
+            queue!(out, MoveTo(area.x, area.y))?;
+            match self.protocol {
+                Protocol::Kitty => write_kitty(out, image, area)?,
+                Protocol::Sixel => write_sixel(out, image, area)?,
+            }
+        }
+
+        self.drawn = area;
+        out.flush()
+    }
+
+    fn erase(&self, out: &mut impl Write) -> io::Result<()> {
+        match (self.protocol, self.drawn) {
+            (Protocol::Kitty, Some(_)) => write!(out, "\x1b_Ga=d,q=2\x1b\\")?,
+            // Sixels are part of the cells, so they're erased by writing over them
+            (Protocol::Sixel, Some(area)) => {
+                for y in area.top()..area.bottom() {
+                    queue!(out, MoveTo(area.x, y))?;
+                    write!(out, "{:width$}", "", width = area.width as usize)?;
+                }
+            }
+            (_, None) => {}
+        }
+        Ok(())
+    }
+}
+
+/// Reads the cover of a local song from its tags, or from an image file in the same directory
+fn find_cover(path: &str) -> Option<Vec<u8>> {
+    if path.is_empty() || path.contains("://") {
+        return None;
+    }
+
+    embedded_cover(path).or_else(|| {
+        let dir = Path::new(path).parent()?;
+        COVER_FILES
+            .iter()
+            .find_map(|name| std::fs::read(dir.join(name)).ok())
+    })
+}
+
+fn embedded_cover(path: &str) -> Option<Vec<u8>> {
+    use lofty::{PictureType, TaggedFileExt};
+
+    let file = lofty::read_from_path(path).ok()?;
+    let pictures = file.primary_tag().or(file.first_tag())?.pictures();
+    pictures
+        .iter()
+        .find(|p| p.pic_type() == PictureType::CoverFront)
+        .or(pictures.first())
+        .map(|p| p.data().to_vec())
+}
+
+/// Sends the image as a PNG, scaled by the terminal to fill `area`
+fn write_kitty(out: &mut impl Write, image: &DynamicImage, area: Rect) -> io::Result<()> {
+    let mut png = Vec::new();
+    image
+        .thumbnail(512, 512)
+        .write_to(&mut Cursor::new(&mut png), ImageOutputFormat::Png)
+        .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+
+    // The data is sent in chunks of at most 4096 bytes, and `m=1` means there's more to come
+    let data = STANDARD.encode(png);
+    let chunks: Vec<_> = data.as_bytes().chunks(4096).collect();
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more = u8::from(i + 1 < chunks.len());
+        if i == 0 {
+            write!(
+                out,
+                "\x1b_Gf=100,a=T,q=2,c={},r={},m={};",
+                area.width, area.height, more
+            )?;
+        } else {
+            write!(out, "\x1b_Gm={};", more)?;
+        }
+        out.write_all(chunk)?;
+        write!(out, "\x1b\\")?;
+    }
+    Ok(())
+}
+
+fn write_sixel(out: &mut impl Write, image: &DynamicImage, area: Rect) -> io::Result<()> {
+    let width = area.width as u32 * CELL_WIDTH;
+    let height = area.height as u32 * CELL_HEIGHT;
+    let image = image
+        .resize_exact(width, height, FilterType::Triangle)
+        .to_rgb8();
+    out.write_all(sixel(&image).as_bytes())
+}
+
+/// Encodes an image as sixel, with its colors reduced to a 6x6x6 palette
+fn sixel(image: &RgbImage) -> String {
+    let (width, height) = image.dimensions();
+    let level = |c: u8| (c as usize * 5 + 127) / 255;
+    let color_index = |x, y| {
+        let [r, g, b] = image.get_pixel(x, y).0;
+        level(r) * 36 + level(g) * 6 + level(b)
+    };
+
+    let mut s = format!("\x1bPq\"1;1;{};{}", width, height);
+    for i in 0..216 {
+        let percent = |level: usize| level * 100 / 5;
+        s.push_str(&format!(
+            "#{};2;{};{};{}",
+            i,
+            percent(i / 36),
+            percent(i / 6 % 6),
+            percent(i % 6)
+        ));
+    }
+
+    // Each band is 6 pixels tall, and is drawn once for every color in it
+    for top in (0..height).step_by(6) {
+        let mut sixels_by_color: BTreeMap<usize, Vec<u8>> = BTreeMap::new();
+        for x in 0..width {
+            for dy in 0..6.min(height - top) {
+                let sixels = sixels_by_color
+                    .entry(color_index(x, top + dy))
+                    .or_insert_with(|| vec![0; width as usize]);
+                sixels[x as usize] |= 1 << dy;
+            }
+        }
+
+        for (n, (color, sixels)) in sixels_by_color.iter().enumerate() {
+            if n > 0 {
+                // Go back to the start of the band
+                s.push('$');
+            }
+            s.push_str(&format!("#{}", color));
+            push_run_length_encoded(&mut s, sixels);
+        }
+        s.push('-');
+    }
+
+    s.push_str("\x1b\\");
+    s
+}
+
+fn push_run_length_encoded(s: &mut String, sixels: &[u8]) {
+    let mut i = 0;
+    while i < sixels.len() {
+        let run = sixels[i..].iter().take_while(|&&b| b == sixels[i]).count();
+        let c = (63 + sixels[i]) as char;
+        if run > 3 {
+            s.push_str(&format!("!{}{}", run, c));
+        } else {
+            s.extend(std::iter::repeat(c).take(run));
+        }
+        i += run;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_protocol() {
+        assert_eq!(detect_from("xterm-kitty", "", false), Some(Protocol::Kitty));
+        assert_eq!(
+            detect_from("xterm-256color", "WezTerm", false),
+            Some(Protocol::Kitty)
+        );
+        assert_eq!(detect_from("foot", "", false), Some(Protocol::Sixel));
+        assert_eq!(detect_from("xterm-256color", "", false), None);
+    }
+
+    #[test]
+    fn test_sixel() {
+        // 5 white pixels on top of 5 red ones, in a single band
+        let mut image = RgbImage::from_pixel(5, 2, image::Rgb([255, 255, 255]));
+        for x in 0..5 {
+            image.put_pixel(x, 1, image::Rgb([255, 0, 0]));
+        }
+
+        let s = sixel(&image);
+        assert!(s.starts_with("\x1bPq\"1;1;5;2#0;2;0;0;0"));
+        // white is color 215, with the top pixel set. Red is color 180, with the second one set.
+        assert!(s.ends_with("#180!5A$#215!5@-\x1b\\"));
+    }
+}
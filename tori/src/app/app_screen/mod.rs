@@ -1,9 +1,19 @@
-use crate::{command, error::Result, events, player::Player, rect_ops::RectOps};
+use crate::{
+    command, config::Config, error::Result, events, player::Player, recent::RecentSongs,
+    rect_ops::RectOps, session::Session,
+};
+use std::{cell::RefCell, rc::Rc};
 
 mod now_playing;
-use now_playing::NowPlaying;
+use now_playing::{format_time, NowPlaying};
 use tui::layout::Rect;
 
+const MIN_SPEED: f64 = 0.25;
+const MAX_SPEED: f64 = 4.0;
+/// How much [SpeedUp](command::Command::SpeedUp) and [SpeedDown](command::Command::SpeedDown)
+/// change the playback speed
+const SPEED_STEP: f64 = 0.1;
+
 use super::{
     browse_screen::BrowseScreen,
     component::{Component, MouseHandler},
@@ -27,15 +37,36 @@ pub struct AppScreen<'a> {
 }
 
 impl<'a> AppScreen<'a> {
-    pub fn new() -> Result<Self> {
+    pub fn new(recent_songs: Rc<RefCell<RecentSongs>>) -> Result<Self> {
         Ok(Self {
-            browse: BrowseScreen::new()?,
+            browse: BrowseScreen::new(recent_songs)?,
             playlist: PlaylistScreen::default(),
             now_playing: NowPlaying::default(),
             selected: Selected::default(),
         })
     }
 
+    /// See [BrowseScreen::select_playlist]
+    pub fn select_playlist(&mut self, name: &str) -> Result<bool> {
+        self.browse.select_playlist(name)
+    }
+
+    /// See [BrowseScreen::session]
+    pub fn session(&self) -> Session {
+        self.browse.session()
+    }
+
+    /// See [BrowseScreen::restore_session]
+    pub fn restore_session(&mut self, session: &Session) -> Result<()> {
+        self.browse.restore_session(session)
+    }
+
+    /// See [BrowseScreen::draw_images]
+    pub fn draw_images(&mut self) -> Result<()> {
+        self.browse
+            .draw_images(matches!(self.selected, Selected::Browse))
+    }
+
     pub fn select(&mut self, selection: Selected) {
         self.selected = selection;
     }
@@ -51,53 +82,196 @@ impl<'a> AppScreen<'a> {
         use command::Command::*;
         match cmd {
             Quit => {
-                app.quit();
+                // Quitting again while asked to confirm goes through
+                let queued = app.player.playlist_count().unwrap_or(0);
+                if Config::global().confirm_quit && queued > 0 && !self.browse.confirming_quit() {
+                    self.select(Selected::Browse);
+                    self.browse.confirm_quit(queued);
+                } else {
+                    app.quit();
+                }
             }
             SeekForward => {
-                app.player.seek(10.)?;
-                self.now_playing.update(&app.player);
+                self.seek(app, Config::global().seek_seconds);
             }
             SeekBackward => {
-                app.player.seek(-10.)?;
-                self.now_playing.update(&app.player);
+                self.seek(app, -Config::global().seek_seconds);
+            }
+            SwitchScreen => match self.selected {
+                Selected::Browse => {
+                    self.playlist.update(&app.player)?;
+                    self.select(Selected::Playlist);
+                }
+                Selected::Playlist => self.select(Selected::Browse),
+            },
+            GoToPlaying => {
+                self.select(Selected::Browse);
+                self.browse.go_to_playing(app)?;
+            }
+            SeekTo => {
+                if app.player.playlist_count().unwrap_or(0) == 0 {
+                    app.notify_info("Nothing is playing");
+                    return Ok(());
+                }
+                self.select(Selected::Browse);
+                self.browse.open_seek_to();
             }
             NextSong => {
                 app.player
                     .playlist_next()
-                    .unwrap_or_else(|_| app.notify_err("No next song"));
-                self.now_playing.update(&app.player);
+                    .unwrap_or_else(|_| app.notify_info("No next song"));
+                self.now_playing.update(app.refresh_player_state());
             }
             PrevSong => {
                 app.player
                     .playlist_previous()
-                    .unwrap_or_else(|_| app.notify_err("No previous song"));
-                self.now_playing.update(&app.player);
+                    .unwrap_or_else(|_| app.notify_info("No previous song"));
+                self.now_playing.update(app.refresh_player_state());
             }
             TogglePause => {
+                // Nothing is loaded, so there's nothing to pause. Toggling anyway would make the
+                // next song start paused.
+                if app.player.playlist_count().unwrap_or(0) == 0 {
+                    return Ok(());
+                }
+
                 app.player.toggle_pause()?;
-                self.now_playing.update(&app.player);
+                self.now_playing.update(app.refresh_player_state());
+                app.notify_info(if self.now_playing.paused {
+                    "Paused"
+                } else {
+                    "Playing"
+                });
             }
             ToggleLoop => {
                 app.player.toggle_loop_file()?;
-                self.now_playing.update(&app.player);
+                self.now_playing.update(app.refresh_player_state());
+                app.notify_info(if self.now_playing.loop_file {
+                    "Looping the current song"
+                } else {
+                    "Stopped looping the current song"
+                });
+            }
+            ToggleLoopPlaylist => {
+                app.player.toggle_loop_playlist()?;
+                self.now_playing.update(app.refresh_player_state());
+                app.notify_info(if self.now_playing.loop_playlist {
+                    "Looping the queue"
+                } else {
+                    "Stopped looping the queue"
+                });
             }
             VolumeUp => {
                 app.player.add_volume(5)?;
-                self.now_playing.update(&app.player);
+                self.now_playing.update(app.refresh_player_state());
+                self.show_volume_osd(app);
             }
             VolumeDown => {
                 app.player.add_volume(-5)?;
-                self.now_playing.update(&app.player);
+                self.now_playing.update(app.refresh_player_state());
+                self.show_volume_osd(app);
             }
             Mute => {
                 app.player.toggle_mute()?;
-                self.now_playing.update(&app.player);
+                self.now_playing.update(app.refresh_player_state());
+                self.show_volume_osd(app);
+                app.notify_info(if self.now_playing.muted {
+                    "Muted"
+                } else {
+                    "Unmuted"
+                });
+            }
+            SpeedUp => {
+                self.set_speed(app, step_speed(app.player.speed()?, SPEED_STEP))?;
+            }
+            SpeedDown => {
+                self.set_speed(app, step_speed(app.player.speed()?, -SPEED_STEP))?;
+            }
+            SpeedReset => {
+                self.set_speed(app, 1.0)?;
+            }
+            SetLoopPoint => {
+                let now = app.player.time_pos_f64()?;
+                let (a, b) = next_ab_loop(app.player.ab_loop()?, now);
+                app.player.set_ab_loop(a, b)?;
+                self.now_playing.update(app.refresh_player_state());
+                match (a, b) {
+                    (Some(a), Some(b)) => app.notify_info(format!(
+                        "Looping from {} to {}",
+                        format_time(a),
+                        format_time(b)
+                    )),
+                    _ => app.notify_info(format!("Loop start set at {}", format_time(now))),
+                }
+            }
+            ClearLoopPoints => {
+                app.player.set_ab_loop(None, None)?;
+                self.now_playing.update(app.refresh_player_state());
+                app.notify_info("Cleared the loop");
+            }
+            ClearQueue => {
+                if app.player.playlist_count().unwrap_or(0) == 0 {
+                    return Ok(());
+                }
+
+                app.player.playlist_clear()?;
+                self.playlist.update_after_delay(app);
+                app.notify_info("Cleared the queue");
             }
             _ => self.pass_event_down(app, events::Event::Command(cmd))?,
         }
         Ok(())
     }
 
+    /// Seeks the current song by some amount of seconds. Seeking past the end of the song skips
+    /// to the next one in the queue, if there is one.
+    fn seek(&mut self, app: &mut App, seconds: f64) {
+        let past_end = seconds > 0.0
+            && matches!(app.player.time_remaining(), Ok(rem) if (rem as f64) < seconds);
+
+        let res = if past_end {
+            app.player
+                .playlist_next()
+                .or_else(|_| app.player.seek(seconds))
+        } else {
+            app.player.seek(seconds)
+        };
+
+        if let Err(e) = res {
+            app.notify_err(format!("Failed to seek: {}", e));
+        }
+        self.now_playing.update(app.refresh_player_state());
+
+        let (pos, rem) = (self.now_playing.time_pos, self.now_playing.time_rem);
+        let duration = pos + rem;
+        if duration > 0 {
+            app.show_osd(
+                format!(
+                    "{} / {}",
+                    format_time(pos as f64),
+                    format_time(duration as f64)
+                ),
+                pos as f64 / duration as f64,
+            );
+        }
+    }
+
+    fn show_volume_osd(&self, app: &mut App) {
+        let volume = self.now_playing.volume;
+        if self.now_playing.muted {
+            app.show_osd(format!("Volume {}% (muted)", volume), 0.0);
+        } else {
+            app.show_osd(format!("Volume {}%", volume), volume as f64 / 100.0);
+        }
+    }
+
+    fn set_speed(&mut self, app: &mut App, speed: f64) -> Result<()> {
+        app.player.set_speed(speed)?;
+        self.now_playing.update(app.refresh_player_state());
+        app.notify_info(format!("Speed: {:.2}x", self.now_playing.speed));
+        Ok(())
+    }
+
     /// Returns (app chunk, now_playing chunk)
     fn subcomponent_chunks(frame: Rect) -> (Rect, Rect) {
         frame.split_bottom(2)
@@ -131,6 +305,10 @@ impl<'a> Component for AppScreen<'a> {
         match &event {
             Command(cmd) => self.handle_command(app, *cmd)?,
             Terminal(crossterm::event::Event::Key(key_event)) => match key_event.code {
+                // While a count is being typed, these are part of it
+                KeyCode::Char('1' | '2') if self.browse.has_count() => {
+                    self.pass_event_down(app, event)?
+                }
                 KeyCode::Char('1') if self.mode() == Mode::Normal => {
                     self.select(Selected::Browse);
                 }
@@ -141,11 +319,18 @@ impl<'a> Component for AppScreen<'a> {
                 _ => self.pass_event_down(app, event)?,
             },
             SecondTick => {
-                self.now_playing.update(&app.player);
+                self.now_playing.update(app.player_state());
                 self.pass_event_down(app, event)?;
             }
+            // The browse screen keeps loading playlists while the queue is shown
+            PlaylistLoading { .. } | PlaylistLoaded { .. } | RecentSongsChanged => {
+                self.browse.handle_event(app, event)?
+            }
             _ => self.pass_event_down(app, event)?,
         }
+        if self.now_playing.eq_preset.as_deref() != app.eq_preset() {
+            self.now_playing.eq_preset = app.eq_preset().map(str::to_string);
+        }
         Ok(())
     }
 }
@@ -172,6 +357,21 @@ impl<'a> MouseHandler for AppScreen<'a> {
     }
 }
 
+/// Changes the speed by `delta`, rounded to a multiple of 0.1x so that float errors don't add up,
+/// and kept between [MIN_SPEED] and [MAX_SPEED]
+fn step_speed(speed: f64, delta: f64) -> f64 {
+    (((speed + delta) * 10.0).round() / 10.0).clamp(MIN_SPEED, MAX_SPEED)
+}
+
+/// The A-B loop after setting a point at `now`. The first point is the start, and the second
+/// one is the end, unless it's before the start. Then it replaces the start.
+fn next_ab_loop((a, b): (Option<f64>, Option<f64>), now: f64) -> (Option<f64>, Option<f64>) {
+    match (a, b) {
+        (Some(a), None) if now > a => (Some(a), Some(now)),
+        _ => (Some(now), None),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -244,4 +444,28 @@ mod tests {
         };
         assert_eq!(AppScreen::subcomponent_chunks(frame), (app, now_playing));
     }
+
+    #[test]
+    fn test_step_speed() {
+        assert_eq!(step_speed(1.0, SPEED_STEP), 1.1);
+        assert_eq!(step_speed(0.7, SPEED_STEP), 0.8);
+        assert_eq!(step_speed(3.95, SPEED_STEP), MAX_SPEED);
+        assert_eq!(step_speed(0.3, -SPEED_STEP), MIN_SPEED);
+    }
+
+    #[test]
+    fn test_next_ab_loop() {
+        assert_eq!(next_ab_loop((None, None), 10.0), (Some(10.0), None));
+        assert_eq!(
+            next_ab_loop((Some(10.0), None), 25.0),
+            (Some(10.0), Some(25.0))
+        );
+        // Going back before the start moves it instead
+        assert_eq!(next_ab_loop((Some(10.0), None), 5.0), (Some(5.0), None));
+        // A full loop is replaced by a new one
+        assert_eq!(
+            next_ab_loop((Some(10.0), Some(25.0)), 40.0),
+            (Some(40.0), None)
+        );
+    }
 }
@@ -2,7 +2,7 @@ use tui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Style},
     text::{Line, Span},
-    widgets::Paragraph,
+    widgets::{Gauge, Paragraph},
     Frame,
 };
 
@@ -13,10 +13,16 @@ use crate::{
     },
     error::Result,
     events,
-    player::Player,
+    player::{Player, PlayerState},
     rect_ops::RectOps,
 };
 
+/// Formats a position in a song as `mm:ss`
+pub fn format_time(seconds: f64) -> String {
+    let seconds = seconds as i64;
+    format!("{:02}:{:02}", seconds / 60, seconds % 60)
+}
+
 #[derive(Debug)]
 struct SubcomponentChunks {
     top_line: Rect,
@@ -34,23 +40,31 @@ pub struct NowPlaying {
     pub time_rem: i64,
     pub paused: bool,
     pub loop_file: bool,
+    pub loop_playlist: bool,
+    pub ab_loop: (Option<f64>, Option<f64>),
     pub volume: i64,
+    pub muted: bool,
+    pub speed: f64,
+    /// Name of the equalizer preset in use
+    pub eq_preset: Option<String>,
+    /// Whether the player has nothing loaded
+    pub idle: bool,
 }
 
 impl NowPlaying {
-    pub fn update(&mut self, player: &impl Player) {
-        self.media_title = player.media_title().unwrap_or_default();
-        self.percentage = player.percent_pos().unwrap_or_default();
-        self.time_pos = player.time_pos().unwrap_or_default();
-        self.time_rem = player.time_remaining().unwrap_or_default();
-        self.paused = player.paused().unwrap_or_default();
-        self.loop_file = player.looping_file().unwrap_or_default();
-
-        self.volume = if player.muted().unwrap_or(false) {
-            0
-        } else {
-            player.volume().unwrap_or_default()
-        };
+    pub fn update(&mut self, state: &PlayerState) {
+        self.media_title = state.media_title.clone();
+        self.percentage = state.percentage;
+        self.time_pos = state.time_pos;
+        self.time_rem = state.time_remaining;
+        self.paused = state.paused;
+        self.loop_file = state.loop_file;
+        self.loop_playlist = state.loop_playlist;
+        self.ab_loop = state.ab_loop;
+        self.idle = state.idle;
+        self.speed = state.speed;
+        self.volume = state.volume;
+        self.muted = state.muted;
     }
 
     fn playback_strs(&self) -> (String, String) {
@@ -59,9 +73,10 @@ impl NowPlaying {
         (playback_left_str, playback_right_str)
     }
 
-    pub fn click(&mut self, app: &mut App, x: u16, y: u16) -> Result<()> {
-        let frame = app.frame_size();
-        let chunks = self.subcomponent_chunks(frame);
+    /// Handles a click at (x, y), where `chunk` is the area of the now playing bar. Clicks outside
+    /// of the volume and playback bars do nothing.
+    pub fn click(&mut self, app: &mut App, chunk: Rect, (x, y): (u16, u16)) -> Result<()> {
+        let chunks = self.subcomponent_chunks(chunk);
 
         if chunks.volume.contains(x, y) {
             let dx = (x - chunks.volume.left()) as f64;
@@ -70,16 +85,13 @@ impl NowPlaying {
             app.player.set_volume((130.0 * percentage).round() as i64)?;
         }
 
-        if chunks.playback_bar.contains(x, y) {
+        if chunks.playback_bar.contains(x, y) && !self.idle {
             let dx = (x - chunks.playback_bar.left()) as f64;
             let percentage = dx / chunks.playback_bar.width as f64;
-            let percentage = (percentage * 100.0).round() as usize;
-            // this is bugged currently :/
-            // app.mpv.seek_percent_absolute(percentage)?;
-            app.player.seek_absolute(percentage)?;
+            app.player.seek_absolute(percentage * 100.0)?;
         }
 
-        self.update(&app.player);
+        self.update(app.refresh_player_state());
         Ok(())
     }
 
@@ -120,6 +132,13 @@ impl Component for NowPlaying {
         let chunks = self.subcomponent_chunks(chunk);
         let (playback_left_str, playback_right_str) = self.playback_strs();
 
+        // Dim the whole bar when nothing is playing
+        let fg = if self.idle {
+            Color::DarkGray
+        } else {
+            Color::White
+        };
+
         ///////////////////////////////
         //        Media title        //
         ///////////////////////////////
@@ -140,6 +159,37 @@ impl Component for NowPlaying {
                 ));
             }
 
+            if self.loop_playlist {
+                parts.push(Span::styled(
+                    "[looping queue] ",
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+
+            let ab_loop = match self.ab_loop {
+                (Some(a), Some(b)) => Some(format!("[A-B {}-{}] ", format_time(a), format_time(b))),
+                (Some(a), None) => Some(format!("[A {}-] ", format_time(a))),
+                _ => None,
+            };
+            if let Some(ab_loop) = ab_loop {
+                parts.push(Span::styled(ab_loop, Style::default().fg(Color::DarkGray)));
+            }
+
+            // The speed is 0 before the first update
+            if !self.idle && self.speed > 0.0 && self.speed != 1.0 {
+                parts.push(Span::styled(
+                    format!("[{:.2}x] ", self.speed),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+
+            if let Some(preset) = &self.eq_preset {
+                parts.push(Span::styled(
+                    format!("[eq: {}] ", preset),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+
             parts.push(Span::styled(
                 &self.media_title,
                 Style::default().fg(Color::Yellow),
@@ -154,7 +204,11 @@ impl Component for NowPlaying {
         let volume_title = Paragraph::new(Line::from(vec![
             Span::raw("volume "),
             Span::styled(
-                format!("{}%", self.volume),
+                if self.muted {
+                    format!("{}% (muted)", self.volume)
+                } else {
+                    format!("{}%", self.volume)
+                },
                 Style::default().fg(Color::DarkGray),
             ),
         ]))
@@ -169,9 +223,11 @@ impl Component for NowPlaying {
             let indicator = "■";
             let right = "─"
                 .repeat((chunks.volume.width as usize * 100 / 130).saturating_sub(left_width + 1));
+            // The bar keeps showing the volume that unmuting goes back to, but dimmed
+            let fg = if self.muted { Color::DarkGray } else { fg };
             Paragraph::new(Line::from(vec![
-                Span::styled(left, Style::default().fg(Color::White)),
-                Span::styled(indicator, Style::default().fg(Color::White)),
+                Span::styled(left, Style::default().fg(fg)),
+                Span::styled(indicator, Style::default().fg(fg)),
                 Span::styled(right, Style::default().fg(Color::DarkGray)),
             ]))
         };
@@ -179,24 +235,14 @@ impl Component for NowPlaying {
         ///////////////////////////////////////
         //        Playback percentage        //
         ///////////////////////////////////////
-        let playback_bar_str: String = {
-            let mut s: Vec<_> = "─"
-                .repeat(chunks.playback_bar.width as usize)
-                .chars()
-                .collect();
-            let i = (self.percentage as usize * s.len() / 100)
-                .min(s.len() - 1)
-                .max(0);
-            s[i] = '■';
-            s.into_iter().collect()
-        };
+        let playback_bar = Gauge::default()
+            .ratio((self.percentage as f64 / 100.0).clamp(0.0, 1.0))
+            .label("")
+            .use_unicode(true)
+            .gauge_style(Style::default().fg(fg).bg(Color::DarkGray));
 
-        let playback_left =
-            Paragraph::new(playback_left_str).style(Style::default().fg(Color::White));
-        let playback_bar =
-            Paragraph::new(playback_bar_str).style(Style::default().fg(Color::White));
-        let playback_right =
-            Paragraph::new(playback_right_str).style(Style::default().fg(Color::White));
+        let playback_left = Paragraph::new(playback_left_str).style(Style::default().fg(fg));
+        let playback_right = Paragraph::new(playback_right_str).style(Style::default().fg(fg));
 
         /////////////////////////////////////
         //        Render everything        //
@@ -222,7 +268,7 @@ impl MouseHandler for NowPlaying {
     fn handle_mouse(
         &mut self,
         app: &mut App,
-        _chunk: Rect,
+        chunk: Rect,
         event: crossterm::event::MouseEvent,
     ) -> Result<()> {
         use crossterm::event::{MouseButton, MouseEventKind};
@@ -230,7 +276,7 @@ impl MouseHandler for NowPlaying {
             event.kind,
             MouseEventKind::Down(MouseButton::Left) | MouseEventKind::Drag(MouseButton::Left)
         ) {
-            self.click(app, event.column, event.row)?;
+            self.click(app, chunk, (event.column, event.row))?;
         }
         Ok(())
     }
@@ -0,0 +1,169 @@
+use crate::{
+    app::{component::Component, filtered_list::FilteredList, App, Mode, MyBackend},
+    config::Config,
+    error::Result,
+    events::Event,
+    m3u,
+};
+
+use crossterm::event::KeyCode;
+use tui::{
+    layout::{Constraint, Rect},
+    style::{Color, Style},
+    widgets::{Block, BorderType, Borders, Row, Table, TableState},
+    Frame,
+};
+
+/// A song found by the global search, along with where it was found
+#[derive(Debug)]
+pub struct SearchResult {
+    pub playlist: String,
+    /// Index of the song in its playlist
+    pub index: usize,
+    pub song: m3u::Song,
+}
+
+/// Shows the songs of every playlist that match some query
+#[derive(Debug, Default)]
+pub struct GlobalSearchPane {
+    query: String,
+    songs: Vec<SearchResult>,
+    shown: FilteredList<TableState>,
+    /// Playlists that couldn't be read, and so weren't searched
+    skipped: Vec<String>,
+}
+
+impl GlobalSearchPane {
+    /// Searches song titles, artists and paths in all playlists of `Config::playlists_dir`.
+    /// Playlists that can't be read are skipped (see [Self::skipped]).
+    pub fn new(query: &str, playlists: &[String]) -> Self {
+        let mut songs = Vec::new();
+        let mut skipped = Vec::new();
+        for playlist in playlists {
+            let path = Config::playlist_path(playlist);
+            let parsed = std::fs::File::open(&path)
+                .map_err(Into::into)
+                .and_then(|file| m3u::Parser::from_reader(file).all_songs());
+            let Ok(mut playlist_songs) = parsed else {
+                skipped.push(playlist.clone());
+                continue;
+            };
+            if let Some(dir) = path.parent() {
+                for song in &mut playlist_songs {
+                    song.resolve_relative_to(dir);
+                }
+            }
+            songs.extend(playlist_songs.into_iter().enumerate().map(|(index, song)| {
+                SearchResult {
+                    playlist: playlist.clone(),
+                    index,
+                    song,
+                }
+            }));
+        }
+
+        let mut me = Self {
+            query: query.to_string(),
+            songs,
+            shown: FilteredList::default(),
+            skipped,
+        };
+        me.refresh_shown();
+        me
+    }
+
+    fn refresh_shown(&mut self) {
+        let query = self.query.to_lowercase();
+        let pred = |r: &SearchResult| {
+            r.song.title.to_lowercase().contains(&query)
+                || r.song
+                    .artist
+                    .as_ref()
+                    .map_or(false, |artist| artist.to_lowercase().contains(&query))
+                || r.song.path.to_lowercase().contains(&query)
+        };
+        self.shown.filter(&self.songs, pred, |i, j| i.cmp(&j));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.shown.items.is_empty()
+    }
+
+    /// Playlists that couldn't be read, and so weren't searched
+    pub fn skipped(&self) -> &[String] {
+        &self.skipped
+    }
+
+    pub fn selected_item(&self) -> Option<&SearchResult> {
+        self.shown.selected_item().and_then(|i| self.songs.get(i))
+    }
+}
+
+impl Component for GlobalSearchPane {
+    type RenderState = bool;
+
+    fn mode(&self) -> Mode {
+        Mode::Normal
+    }
+
+    fn render(&mut self, frame: &mut Frame<'_, MyBackend>, chunk: Rect, is_focused: bool) {
+        let border_style = if is_focused {
+            Style::default().fg(Color::LightBlue)
+        } else {
+            Style::default()
+        };
+
+        let block = Block::default()
+            .title(format!(
+                " search: {} ({}) ",
+                self.query,
+                self.shown.items.len()
+            ))
+            .borders(Borders::ALL)
+            .border_type(BorderType::Plain)
+            .border_style(border_style);
+
+        let rows: Vec<_> = self
+            .shown
+            .items
+            .iter()
+            .map(|&i| &self.songs[i])
+            .map(|r| Row::new(vec![format!(" {}", r.song.title), r.playlist.clone()]))
+            .collect();
+
+        let free_width = chunk.width.saturating_sub(2);
+        let widths = &[
+            Constraint::Length(free_width - free_width / 4),
+            Constraint::Length(free_width / 4),
+        ];
+        let widget = Table::new(rows)
+            .block(block)
+            .widths(widths)
+            .highlight_style(Style::default().bg(Color::Yellow).fg(Color::Black))
+            .highlight_symbol(" ◇");
+        frame.render_stateful_widget(widget, chunk, &mut self.shown.state);
+    }
+
+    #[allow(clippy::single_match)]
+    fn handle_event(&mut self, _app: &mut App, event: Event) -> Result<()> {
+        use crate::command::Command::*;
+        use KeyCode::*;
+
+        match event {
+            Event::Command(cmd) => match cmd {
+                SelectNext => self.shown.select_next(Config::global().wrap_navigation),
+                SelectPrev => self.shown.select_prev(Config::global().wrap_navigation),
+                SelectFirst => self.shown.select_first(),
+                SelectLast => self.shown.select_last(),
+                _ => {}
+            },
+            Event::Terminal(crossterm::event::Event::Key(event)) => match event.code {
+                Up => self.shown.select_prev(Config::global().wrap_navigation),
+                Down => self.shown.select_next(Config::global().wrap_navigation),
+                _ => {}
+            },
+            _ => {}
+        }
+        Ok(())
+    }
+}
@@ -1,22 +1,31 @@
+#[cfg(feature = "album-art")]
+use crate::album_art::{AlbumArt, Protocol};
 use crate::{
     app::{component::Component, App, MyBackend},
     command,
+    config::Config,
     error::Result,
     events::Event,
-    m3u::playlist_management,
+    m3u::{self, playlist_management},
     player::Player,
+    recent::RecentSongs,
     rect_ops::RectOps,
+    session::{FocusedPane, Session},
+    util,
 };
 
-use crossterm::event::{KeyCode, MouseEvent, MouseEventKind};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
 
 use std::borrow::Cow;
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use tui::layout::Rect;
 use tui::style::Color;
 use tui::style::Style;
 use tui::{
     layout::{Constraint, Direction, Layout},
+    widgets::Paragraph,
     Frame,
 };
 
@@ -26,20 +35,84 @@ use playlists::PlaylistsPane;
 mod songs;
 use songs::SongsPane;
 
+mod global_search;
+use global_search::GlobalSearchPane;
+
 use super::Mode;
 use super::{component::MouseHandler, modal::HotkeyModal};
-use crate::app::modal::{self, ConfirmationModal, HelpModal, InputModal, Modal};
+use crate::app::modal::{self, ConfirmationModal, HelpModal, InputModal, ListModal, Modal};
+
+/// Whether a command changes the songs of the selected playlist
+fn edits_songs(cmd: command::Command) -> bool {
+    use command::Command::*;
+    matches!(
+        cmd,
+        Rename | Delete | ToggleMark | MoveSong | YankSong | PasteSong | SwapSongUp | SwapSongDown
+    )
+}
+
+/// Longer counts are cut down to this, so that `99999999j` doesn't hang tori
+const MAX_COUNT: usize = 9999;
+/// Choices of the [confirm_quit](crate::config::Config::confirm_quit) modal
+const QUIT_SAVE: &str = "Save the queue as a playlist, then quit";
+const QUIT_ANYWAY: &str = "Quit anyway";
+const QUIT_CANCEL: &str = "Don't quit";
+
+/// Item of the [SetPreset](crate::command::Command::SetPreset) list that turns the equalizer off
+const EQ_OFF: &str = "(off)";
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 enum ModalType {
     Help,
     Hotkey,
+    Log,
     Play,
-    AddSong { playlist: String },
+    AddSong {
+        playlist: String,
+    },
     AddPlaylist,
-    DeletePlaylist { playlist: String },
-    RenameSong { playlist: String, index: usize },
-    DeleteSong { playlist: String, index: usize },
+    RenamePlaylist {
+        playlist: String,
+    },
+    DeletePlaylist {
+        playlist: String,
+    },
+    DuplicatePlaylist {
+        playlist: String,
+    },
+    RenameSong {
+        playlist: String,
+        index: usize,
+    },
+    DeleteSong {
+        playlist: String,
+        index: usize,
+    },
+    DeleteSongs {
+        playlist: String,
+        indices: Vec<usize>,
+    },
+    MoveSong {
+        playlist: String,
+        index: usize,
+    },
+    MoveSongs {
+        playlist: String,
+        indices: Vec<usize>,
+    },
+    AddCurrentSong {
+        path: String,
+        title: String,
+    },
+    EqPreset,
+    SeekTo,
+    ConfirmQuit,
+    SaveQueue {
+        /// Quit once the queue is saved
+        then_quit: bool,
+    },
+    GlobalSearch,
+    ImportDirectory,
 }
 
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
@@ -48,6 +121,7 @@ enum BrowsePane {
     #[default]
     Playlists,
     Songs,
+    GlobalSearch,
     Modal(ModalType),
 }
 
@@ -55,8 +129,18 @@ enum BrowsePane {
 pub struct BrowseScreen<'a> {
     playlists: PlaylistsPane,
     songs: SongsPane<'a>,
+    global_search: GlobalSearchPane,
     modal: Box<dyn Modal>,
     selected_pane: BrowsePane,
+    /// Songs added or played from a modal during this session, oldest first
+    input_history: Vec<String>,
+    /// Count typed before a command, like the 5 in `5j`
+    count: Option<usize>,
+    /// Songs played last, kept up to date by [App]
+    recent_songs: Rc<RefCell<RecentSongs>>,
+    /// Only set if album art is enabled and the terminal can draw it
+    #[cfg(feature = "album-art")]
+    album_art: Option<AlbumArt>,
 }
 
 impl<'a> std::fmt::Debug for BrowseScreen<'a> {
@@ -70,19 +154,119 @@ impl<'a> std::fmt::Debug for BrowseScreen<'a> {
 }
 
 impl<'a> BrowseScreen<'a> {
-    pub fn new() -> Result<Self> {
+    pub fn new(recent_songs: Rc<RefCell<RecentSongs>>) -> Result<Self> {
         let playlists = PlaylistsPane::new()?;
         let mut songs = SongsPane::default();
-        songs.update_from_playlist_pane(&playlists)?;
+        songs.update_from_playlist_pane(&playlists, &recent_songs.borrow())?;
         Ok(Self {
             playlists,
             songs,
+            recent_songs,
+            #[cfg(feature = "album-art")]
+            album_art: Protocol::detect()
+                .filter(|_| Config::global().album_art)
+                .map(AlbumArt::new),
             ..Default::default()
         })
     }
 
+    /// Draws the album art in the area left for it by the last render. The art isn't part of the
+    /// tui frame, so this has to be called after every frame is drawn.
+    #[cfg(feature = "album-art")]
+    pub fn draw_images(&mut self, visible: bool) -> Result<()> {
+        if let Some(art) = &mut self.album_art {
+            if !visible {
+                art.area = None;
+            }
+            art.draw(&mut std::io::stdout())?;
+        }
+        Ok(())
+    }
+
+    #[cfg(not(feature = "album-art"))]
+    pub fn draw_images(&mut self, _visible: bool) -> Result<()> {
+        Ok(())
+    }
+
+    /// Splits the playlists chunk to leave room for the album art, if there's any to show.
+    /// Returns the chunk left for the playlists.
+    #[cfg(feature = "album-art")]
+    fn render_album_art(&mut self, frame: &mut Frame<'_, MyBackend>, chunk: Rect) -> Rect {
+        use tui::widgets::{Block, BorderType, Borders};
+
+        let Some(art) = &mut self.album_art else {
+            return chunk;
+        };
+        art.area = None;
+        if !art.has_image() || matches!(self.selected_pane, BrowsePane::Modal(_)) {
+            return chunk;
+        }
+
+        // Cells are about twice as tall as they are wide, so this is roughly a square
+        let height = (chunk.width / 2 + 2).min(chunk.height / 2);
+        let vchunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(height)])
+            .split(chunk);
+
+        let block = Block::default()
+            .title(" Album art ")
+            .borders(Borders::LEFT | Borders::BOTTOM | Borders::TOP)
+            .border_type(BorderType::Plain);
+        art.area = Some(block.inner(vchunks[1])).filter(|area| area.area() > 0);
+        frame.render_widget(block, vchunks[1]);
+        vchunks[0]
+    }
+
+    #[cfg(not(feature = "album-art"))]
+    fn render_album_art(&mut self, _frame: &mut Frame<'_, MyBackend>, chunk: Rect) -> Rect {
+        chunk
+    }
+
+    /// Selects a playlist by name and shows its songs. Returns whether the playlist exists.
+    pub fn select_playlist(&mut self, name: &str) -> Result<bool> {
+        if !self.playlists.select_name(name) {
+            return Ok(false);
+        }
+        self.reload_songs()?;
+        Ok(true)
+    }
+
+    /// Where the user is, to be restored with [Self::restore_session] on the next launch
+    pub fn session(&self) -> Session {
+        let pane = match self.selected_pane {
+            BrowsePane::Songs => FocusedPane::Songs,
+            _ => FocusedPane::Playlists,
+        };
+        Session {
+            playlist: self.playlists.selected_item().map(str::to_string),
+            song: self.songs.selected_index(),
+            pane,
+        }
+    }
+
+    /// Selects the saved playlist, song and pane. Does nothing if the playlist doesn't exist
+    /// anymore.
+    pub fn restore_session(&mut self, session: &Session) -> Result<()> {
+        let Some(playlist) = &session.playlist else {
+            return Ok(());
+        };
+        if !self.select_playlist(playlist)? {
+            return Ok(());
+        }
+
+        if let Some(song) = session.song {
+            self.songs.select_song(song);
+        }
+        if session.pane == FocusedPane::Songs {
+            self.selected_pane = BrowsePane::Songs;
+        }
+        Ok(())
+    }
+
     pub fn reload_songs(&mut self) -> Result<()> {
-        self.songs.update_from_playlist_pane(&self.playlists)
+        self.songs
+            .update_from_playlist_pane(&self.playlists, &self.recent_songs.borrow())
     }
 
     /// Passes the event down to the currently selected pane.
@@ -91,6 +275,7 @@ impl<'a> BrowseScreen<'a> {
         match self.selected_pane {
             Playlists => self.playlists.handle_event(app, event),
             Songs => self.songs.handle_event(app, event),
+            GlobalSearch => self.global_search.handle_event(app, event),
             Modal(_) => {
                 let msg = self.modal.handle_event(event)?;
                 self.handle_modal_message(app, msg)
@@ -113,13 +298,68 @@ impl<'a> BrowseScreen<'a> {
                 (Hotkey, _) => {
                     self.selected_pane = BrowsePane::Songs;
                 }
+                (Log, _) => {
+                    self.selected_pane = BrowsePane::Songs;
+                }
+
+                // ConfirmQuit
+                (ConfirmQuit, Quit) => {
+                    self.selected_pane = BrowsePane::Songs;
+                }
+                (ConfirmQuit, Commit(choice)) => match choice.as_str() {
+                    QUIT_SAVE => {
+                        let modal_type = ModalType::SaveQueue { then_quit: true };
+                        self.open_modal(" Save the queue as ", modal_type);
+                    }
+                    QUIT_ANYWAY => app.quit(),
+                    _ => self.selected_pane = BrowsePane::Songs,
+                },
+
+                // SaveQueue
+                (SaveQueue { then_quit: _ }, Quit) => {
+                    self.selected_pane = BrowsePane::Songs;
+                }
+                (SaveQueue { then_quit }, Commit(playlist)) => {
+                    let then_quit = *then_quit;
+                    self.selected_pane = BrowsePane::Songs;
+                    if self.save_queue(app, &playlist)? && then_quit {
+                        app.quit();
+                    }
+                }
+
+                // EqPreset
+                (EqPreset, Quit) => {
+                    self.selected_pane = BrowsePane::Songs;
+                }
+                (EqPreset, Commit(name)) => {
+                    app.set_eq_preset((name != EQ_OFF).then_some(name.as_str()));
+                    self.selected_pane = BrowsePane::Songs;
+                }
+
+                // SeekTo
+                (SeekTo, Quit) => {
+                    self.selected_pane = BrowsePane::Songs;
+                }
+                (SeekTo, Commit(time)) => {
+                    self.selected_pane = BrowsePane::Songs;
+                    match util::parse_time(&time) {
+                        Some(seconds) => {
+                            if let Err(e) = app.player.seek_to(seconds) {
+                                app.notify_err(format!("Failed to seek: {}", e));
+                            }
+                        }
+                        None => app
+                            .notify_err(format!("'{}' isn't a time like 1:30 or 90", time.trim())),
+                    }
+                }
 
                 // AddSong
                 (AddSong { playlist: _ }, Quit) => {
                     self.selected_pane = BrowsePane::Songs;
                 }
                 (AddSong { playlist }, Commit(song)) => {
-                    playlist_management::add_song(app, playlist, song);
+                    playlist_management::add_song(app, playlist, song.clone());
+                    push_history(&mut self.input_history, song);
                     self.selected_pane = BrowsePane::Songs;
                 }
 
@@ -145,13 +385,43 @@ impl<'a> BrowseScreen<'a> {
                     self.selected_pane = BrowsePane::Playlists;
                 }
 
+                // ImportDirectory
+                (ImportDirectory, Quit) => {
+                    self.selected_pane = BrowsePane::Playlists;
+                }
+                (ImportDirectory, Commit(dir)) => {
+                    self.import_directory(app, &dir)?;
+                    self.selected_pane = BrowsePane::Playlists;
+                }
+
+                // RenamePlaylist
+                (RenamePlaylist { playlist: _ }, Quit) => {
+                    self.selected_pane = BrowsePane::Playlists;
+                }
+                (RenamePlaylist { playlist }, Commit(new_name)) => {
+                    let playlist = playlist.clone();
+                    self.rename_playlist(app, &playlist, &new_name)?;
+                    self.selected_pane = BrowsePane::Playlists;
+                }
+
+                // DuplicatePlaylist
+                (DuplicatePlaylist { playlist: _ }, Quit) => {
+                    self.selected_pane = BrowsePane::Playlists;
+                }
+                (DuplicatePlaylist { playlist }, Commit(new_name)) => {
+                    let playlist = playlist.clone();
+                    self.duplicate_playlist(app, &playlist, &new_name)?;
+                    self.selected_pane = BrowsePane::Playlists;
+                }
+
                 // DeletePlaylist
                 (DeletePlaylist { playlist: _ }, Quit) => {
                     self.selected_pane = BrowsePane::Playlists;
                 }
                 (DeletePlaylist { playlist }, Commit(_)) => {
                     playlist_management::delete_playlist(playlist)?;
-                    self.playlists = PlaylistsPane::new()?;
+                    self.playlists.reload_from_dir()?;
+                    // Shows the songs of the playlist that took its place, or clears the pane
                     self.reload_songs()?;
                     self.selected_pane = BrowsePane::Playlists;
                 }
@@ -162,6 +432,7 @@ impl<'a> BrowseScreen<'a> {
                 }
                 (Play, Commit(path)) => {
                     app.player.play(&path)?;
+                    push_history(&mut self.input_history, path);
                     self.selected_pane = BrowsePane::Songs;
                 }
 
@@ -176,7 +447,9 @@ impl<'a> BrowseScreen<'a> {
                     self.selected_pane = BrowsePane::Songs;
                 }
                 (RenameSong { playlist, index }, Commit(new_name)) => {
-                    playlist_management::rename_song(playlist, *index, &new_name)?;
+                    app.push_undo(playlist_management::rename_song(
+                        playlist, *index, &new_name,
+                    )?);
                     self.reload_songs()?;
                     self.selected_pane = BrowsePane::Songs;
                 }
@@ -192,10 +465,120 @@ impl<'a> BrowseScreen<'a> {
                     self.selected_pane = BrowsePane::Songs;
                 }
                 (DeleteSong { playlist, index }, Commit(_)) => {
-                    playlist_management::delete_song(playlist, *index)?;
+                    app.push_undo(playlist_management::delete_song(playlist, *index)?);
+                    self.reload_songs()?;
+                    self.selected_pane = BrowsePane::Songs;
+                }
+
+                // DeleteSongs
+                (
+                    DeleteSongs {
+                        playlist: _,
+                        indices: _,
+                    },
+                    Quit,
+                ) => {
+                    self.selected_pane = BrowsePane::Songs;
+                }
+                (DeleteSongs { playlist, indices }, Commit(_)) => {
+                    app.push_undo(playlist_management::delete_songs(playlist, indices)?);
+                    self.reload_songs()?;
+                    self.selected_pane = BrowsePane::Songs;
+                }
+
+                // MoveSong
+                (
+                    MoveSong {
+                        playlist: _,
+                        index: _,
+                    },
+                    Quit,
+                ) => {
+                    self.selected_pane = BrowsePane::Songs;
+                }
+                (MoveSong { playlist, index }, Commit(to_playlist)) => {
+                    if *playlist == to_playlist {
+                        app.notify_info(format!("The song is already in {}", to_playlist));
+                    } else {
+                        app.push_undo(playlist_management::move_song(
+                            playlist,
+                            *index,
+                            &to_playlist,
+                        )?);
+                        self.playlists.reload_from_dir()?;
+                        self.reload_songs()?;
+                        app.notify_ok(format!("Moved the song to {}", to_playlist));
+                    }
+                    self.selected_pane = BrowsePane::Songs;
+                }
+
+                // MoveSongs
+                (
+                    MoveSongs {
+                        playlist: _,
+                        indices: _,
+                    },
+                    Quit,
+                ) => {
+                    self.selected_pane = BrowsePane::Songs;
+                }
+                (MoveSongs { playlist, indices }, Commit(to_playlist)) => {
+                    let count = indices.len();
+                    app.push_undo(playlist_management::move_songs(
+                        playlist,
+                        indices,
+                        &to_playlist,
+                    )?);
+                    self.playlists.reload_from_dir()?;
                     self.reload_songs()?;
+                    app.notify_ok(format!("Moved {} songs to {}", count, to_playlist));
+                    self.selected_pane = BrowsePane::Songs;
+                }
+
+                // AddCurrentSong
+                (AddCurrentSong { path: _, title: _ }, Quit) => {
+                    self.selected_pane = BrowsePane::Playlists;
+                }
+                (AddCurrentSong { path, title }, Commit(playlist)) => {
+                    if path.contains("://") {
+                        // Streams can't be probed like files, so mpv's title is used instead
+                        let song = m3u::Song {
+                            title: title.clone(),
+                            path: path.clone(),
+                            ..Default::default()
+                        };
+                        song.add_to_playlist(&playlist)?;
+                        let song = title.clone();
+                        app.channel
+                            .send(Event::SongAdded { playlist, song })
+                            .unwrap();
+                    } else {
+                        playlist_management::add_song(app, &playlist, path.clone());
+                    }
+                    self.selected_pane = BrowsePane::Playlists;
+                }
+
+                // GlobalSearch
+                (GlobalSearch, Quit) => {
                     self.selected_pane = BrowsePane::Songs;
                 }
+                (GlobalSearch, Commit(query)) => {
+                    self.global_search = GlobalSearchPane::new(&query, self.playlists.playlists());
+                    if self.global_search.is_empty() {
+                        app.notify_info(format!("No songs found for '{}'", query));
+                        self.selected_pane = BrowsePane::Songs;
+                    } else {
+                        self.selected_pane = BrowsePane::GlobalSearch;
+                    }
+                    // After the above, so that it isn't replaced by "No songs found"
+                    let skipped = self.global_search.skipped();
+                    if !skipped.is_empty() {
+                        app.notify_err(format!(
+                            "Couldn't read some playlists, so they weren't searched: {}",
+                            skipped.join(", ")
+                        ));
+                    }
+                }
             }
         } else {
             panic!("Please don't call BrowseScreen::handle_modal_message without a selected modal");
@@ -206,9 +589,30 @@ impl<'a> BrowseScreen<'a> {
     /// Handles an Event::Command(cmd)
     fn handle_command(&mut self, app: &mut App, cmd: command::Command) -> Result<()> {
         use command::Command::*;
+        let count = self.count.take();
+        if self.selected_pane == BrowsePane::Songs && self.songs.is_recent() && edits_songs(cmd) {
+            app.notify_info("Recently played songs can't be edited");
+            return Ok(());
+        }
+        if self.selected_pane == BrowsePane::Songs && self.songs.has_nested() && edits_songs(cmd) {
+            app.notify_info(
+                "This playlist includes other playlists, so its songs can't be edited. Turn off expand_nested_playlists to edit it",
+            );
+            return Ok(());
+        }
         match cmd {
+            SelectNext | SelectPrev => {
+                for _ in 0..count.unwrap_or(1) {
+                    self.pass_event_down(app, Event::Command(cmd))?;
+                }
+            }
+            SelectFirst | SelectLast => match count {
+                // Like vim's `5G`, goes to the fifth item
+                Some(n) => self.select_nth(app, n - 1),
+                None => self.pass_event_down(app, Event::Command(cmd))?,
+            },
             PlayFromModal => {
-                self.open_modal(" Play ", ModalType::Play);
+                self.open_modal_with_history(" Play ", ModalType::Play);
             }
             OpenHelpModal => {
                 self.open_help_modal();
@@ -216,6 +620,49 @@ impl<'a> BrowseScreen<'a> {
             OpenHotkeyModal => {
                 self.open_hotkey_modal();
             }
+            ShowLog => {
+                let now = std::time::Instant::now();
+                let entries = app
+                    .notification_log()
+                    .iter()
+                    .rev()
+                    .map(|entry| entry.format(now))
+                    .collect();
+                self.selected_pane = BrowsePane::Modal(ModalType::Log);
+                self.modal = Box::new(ListModal::new(" Notifications, newest first ", entries));
+            }
+            SaveQueue => {
+                if app.player.playlist_count().unwrap_or(0) == 0 {
+                    app.notify_info("The queue is empty");
+                } else {
+                    let modal_type = ModalType::SaveQueue { then_quit: false };
+                    self.open_modal(" Save the queue as ", modal_type);
+                }
+            }
+            SetPreset => {
+                let presets = &Config::global().eq_presets;
+                if presets.is_empty() {
+                    app.notify_info("There are no eq_presets in the config");
+                } else {
+                    let items = std::iter::once(EQ_OFF.to_string())
+                        .chain(presets.keys().cloned())
+                        .collect();
+                    self.selected_pane = BrowsePane::Modal(ModalType::EqPreset);
+                    self.modal = Box::new(ListModal::new(" Equalizer preset ", items));
+                }
+            }
+            GlobalSearch => {
+                self.open_modal(" Search all playlists ", ModalType::GlobalSearch);
+            }
+            ImportDirectory => {
+                self.open_modal(" Import directory ", ModalType::ImportDirectory);
+            }
+            ExportPlaylist => {
+                if let Some(playlist) = self.playlists.selected_item() {
+                    let path = m3u::export::export_pls(playlist)?;
+                    app.notify_ok(format!("Exported to {}", path.display()));
+                }
+            }
             SelectRight | SelectLeft => self.select_next_panel(),
             // TODO: this should probably be in each pane's handle_event, somehow
             Add => match self.selected_pane {
@@ -224,20 +671,20 @@ impl<'a> BrowseScreen<'a> {
                 }
                 BrowsePane::Songs => {
                     if let Some(playlist) = self.playlists.selected_item() {
-                        self.open_modal(
-                            " Add song ",
-                            ModalType::AddSong {
-                                playlist: playlist.to_owned(),
-                            },
-                        );
+                        let modal_type = ModalType::AddSong {
+                            playlist: playlist.to_owned(),
+                        };
+                        self.open_modal_with_history(" Add song ", modal_type);
                     } else {
                         app.notify_err("Please select a playlist before adding a song");
                     }
                 }
-                BrowsePane::Modal(_) => {}
+                BrowsePane::GlobalSearch | BrowsePane::Modal(_) => {}
             },
+            RenamePlaylist => self.open_rename_playlist(),
+            DuplicatePlaylist => self.open_duplicate_playlist(),
             Rename => match self.selected_pane {
-                BrowsePane::Playlists => {}
+                BrowsePane::Playlists => self.open_rename_playlist(),
                 BrowsePane::Songs => {
                     if let (Some(playlist), Some(index)) =
                         (self.playlists.selected_item(), self.songs.selected_index())
@@ -252,7 +699,7 @@ impl<'a> BrowseScreen<'a> {
                             },
                         );
 
-                        let song_title = self.songs.selected_item().unwrap().title.clone();
+                        let song_title = self.songs.selected_item().unwrap().full_title();
                         self.modal = Box::new(
                             InputModal::new(" Rename song (esc cancels) ").set_input(song_title),
                         );
@@ -261,23 +708,32 @@ impl<'a> BrowseScreen<'a> {
                 _ => {}
             },
             Delete => match self.selected_pane {
-                BrowsePane::Playlists => {
-                    if let Some(playlist) = self.playlists.selected_item() {
-                        let title = format!("Do you really want to delete '{}'?", playlist);
-                        let modal_type = ModalType::DeletePlaylist {
+                BrowsePane::Playlists => self.open_delete_playlist(),
+                BrowsePane::Songs => {
+                    let marked = self.songs.marked_indices();
+                    if let (Some(playlist), false) =
+                        (self.playlists.selected_item(), marked.is_empty())
+                    {
+                        let title = format!(
+                            "Do you really want to delete {} songs from {}?",
+                            marked.len(),
+                            playlist
+                        );
+                        let modal_type = ModalType::DeleteSongs {
                             playlist: playlist.to_owned(),
+                            indices: marked,
                         };
                         self.open_confirmation(title.as_str(), modal_type)
                             .apply_style(Style::default().fg(Color::LightRed));
-                    }
-                }
-                BrowsePane::Songs => {
-                    if let (Some(playlist), Some(index)) =
+                    } else if let (Some(playlist), Some(index)) =
                         (self.playlists.selected_item(), self.songs.selected_index())
                     {
+                        // The index is the song's position in the playlist, not in the (maybe
+                        // filtered) list, so the title is looked up by it too
                         let title = format!(
-                            "Do you really want to delete '{}'?",
-                            self.songs.selected_item().unwrap().title
+                            "Do you really want to delete '{}' from {}?",
+                            self.songs.song(index).unwrap().title,
+                            playlist
                         );
                         let modal_type = ModalType::DeleteSong {
                             playlist: playlist.to_owned(),
@@ -289,6 +745,96 @@ impl<'a> BrowseScreen<'a> {
                 }
                 _ => {}
             },
+            DeletePlaylist => self.open_delete_playlist(),
+            YankSong => {
+                if let (BrowsePane::Songs, Some(playlist), Some(index)) = (
+                    &self.selected_pane,
+                    self.playlists.selected_item(),
+                    self.songs.selected_index(),
+                ) {
+                    if let Some(song) = playlist_management::song_content(playlist, index)? {
+                        app.yank_song(song);
+                        let title = &self.songs.song(index).unwrap().title;
+                        app.notify_info(format!("Yanked '{}'", title));
+                    }
+                }
+            }
+            PasteSong => {
+                if let (BrowsePane::Songs, Some(playlist), Some(song)) = (
+                    &self.selected_pane,
+                    self.playlists.selected_item(),
+                    app.yanked_song(),
+                ) {
+                    let index = self.songs.selected_index().map_or(0, |i| i + 1);
+                    let edit = playlist_management::insert_song_at(playlist, index, song)?;
+                    app.push_undo(edit);
+                    self.reload_songs()?;
+                    self.songs.select_song(index);
+                }
+            }
+            MoveSong => {
+                if let (BrowsePane::Songs, Some(playlist), Some(index)) = (
+                    &self.selected_pane,
+                    self.playlists.selected_item(),
+                    self.songs.selected_index(),
+                ) {
+                    let marked = self.songs.marked_indices();
+                    let modal_type = if marked.is_empty() {
+                        ModalType::MoveSong {
+                            playlist: playlist.to_owned(),
+                            index,
+                        }
+                    } else {
+                        ModalType::MoveSongs {
+                            playlist: playlist.to_owned(),
+                            indices: marked,
+                        }
+                    };
+                    let others: Vec<_> = self
+                        .playlists
+                        .playlists()
+                        .iter()
+                        .filter(|&p| p != playlist)
+                        .cloned()
+                        .collect();
+
+                    if others.is_empty() {
+                        app.notify_err("There are no other playlists to move the song to");
+                    } else {
+                        self.selected_pane = BrowsePane::Modal(modal_type);
+                        self.modal = Box::new(ListModal::new(" Move song to ", others));
+                    }
+                }
+            }
+            AddCurrentToPlaylist => {
+                let state = app.refresh_player_state();
+                if state.idle || state.path.is_empty() {
+                    app.notify_info("Nothing is playing");
+                    return Ok(());
+                }
+                let modal_type = ModalType::AddCurrentSong {
+                    path: state.path.clone(),
+                    title: state.media_title.clone(),
+                };
+
+                let playlists = self.playlists.playlists().to_vec();
+                if playlists.is_empty() {
+                    app.notify_err("There are no playlists to add the song to");
+                } else {
+                    self.selected_pane = BrowsePane::Modal(modal_type);
+                    self.modal = Box::new(ListModal::new(" Add the playing song to ", playlists));
+                }
+            }
+            Undo => match app.pop_undo() {
+                Some(edit) => {
+                    let playlists = edit.playlists().join(", ");
+                    edit.apply()?;
+                    self.playlists.reload_from_dir()?;
+                    self.reload_songs()?;
+                    app.notify_info(format!("Undid the last change to {}", playlists));
+                }
+                None => app.notify_info("Nothing to undo"),
+            },
             OpenInEditor => self.playlists.open_editor_for_selected(app)?,
             _ => self.pass_event_down(app, Event::Command(cmd))?,
         }
@@ -308,16 +854,162 @@ impl<'a> BrowseScreen<'a> {
             return self.pass_event_down(app, Terminal(event));
         }
 
+        if self.selected_pane == BrowsePane::GlobalSearch {
+            if let crossterm::event::Event::Key(key_event) = event {
+                match key_event.code {
+                    Enter => return self.jump_to_search_result(app),
+                    Esc => {
+                        self.selected_pane = BrowsePane::Songs;
+                        return Ok(());
+                    }
+                    _ => {}
+                }
+            }
+            return self.pass_event_down(app, Terminal(event));
+        }
+
         match event {
-            crossterm::event::Event::Key(event) => match event.code {
-                Right | Left => self.select_next_panel(),
-                _ => self.pass_event_down(app, Terminal(crossterm::event::Event::Key(event)))?,
-            },
+            crossterm::event::Event::Key(event) => {
+                if self.mode() == Mode::Normal {
+                    if let Some(count) = count_with_key(self.count, event) {
+                        self.count = Some(count);
+                        return Ok(());
+                    }
+                }
+                self.count = None;
+
+                match event.code {
+                    Right | Left => self.select_next_panel(),
+                    _ => {
+                        self.pass_event_down(app, Terminal(crossterm::event::Event::Key(event)))?
+                    }
+                }
+            }
             _ => self.pass_event_down(app, Terminal(event))?,
         }
         Ok(())
     }
 
+    /// Asks for a time to jump to in the song being played
+    pub fn open_seek_to(&mut self) {
+        self.open_modal(" Seek to (mm:ss or seconds) ", ModalType::SeekTo);
+    }
+
+    /// Asks whether to quit even though `queued` songs are in the queue, or to save them first
+    pub fn confirm_quit(&mut self, queued: usize) {
+        let title = match queued {
+            1 => " 1 song in the queue will be lost ".to_string(),
+            n => format!(" {} songs in the queue will be lost ", n),
+        };
+        let choices = [QUIT_SAVE, QUIT_ANYWAY, QUIT_CANCEL].map(String::from);
+        self.selected_pane = BrowsePane::Modal(ModalType::ConfirmQuit);
+        self.modal = Box::new(ListModal::new(title, choices.to_vec()));
+    }
+
+    /// Saves the songs in the queue as a new playlist. Returns whether it was saved.
+    fn save_queue(&mut self, app: &mut App, playlist: &str) -> Result<bool> {
+        use playlist_management::CreatePlaylistError;
+
+        let count = app.player.playlist_count()?;
+        let mut queue = m3u::Playlist::default();
+        for i in 0..count {
+            let path = app.player.playlist_track_path(i)?;
+            // mpv falls back to the path when there's no title
+            let title = app.player.playlist_track_title(i).unwrap_or_default();
+            let (artist, title) = if title.is_empty() || title == path {
+                (None, path.clone())
+            } else {
+                m3u::parser::split_artist(&title)
+            };
+            queue.songs.push(m3u::Song {
+                title,
+                artist,
+                path,
+                ..Default::default()
+            });
+        }
+
+        match playlist_management::create_playlist(playlist) {
+            Ok(_) => {}
+            Err(CreatePlaylistError::PlaylistAlreadyExists) => {
+                app.notify_err(format!("Playlist '{}' already exists!", playlist));
+                return Ok(false);
+            }
+            Err(CreatePlaylistError::InvalidChar(c)) => {
+                app.notify_err(format!("Playlist names cannot contain '{}'", c));
+                return Ok(false);
+            }
+            Err(CreatePlaylistError::IOError(e)) => return Err(e.into()),
+        }
+        playlist_management::write_playlist(playlist, &queue)?;
+
+        self.playlists.reload_from_dir()?;
+        self.reload_songs()?;
+        app.notify_ok(format!("Saved {} songs to {}", queue.songs.len(), playlist));
+        Ok(true)
+    }
+
+    pub fn confirming_quit(&self) -> bool {
+        self.selected_pane == BrowsePane::Modal(ModalType::ConfirmQuit)
+    }
+
+    /// Whether a count is being typed, like `5` before `5j`
+    pub fn has_count(&self) -> bool {
+        self.count.is_some()
+    }
+
+    /// Selects the `n`-th shown item of the focused pane, counting from 0
+    fn select_nth(&mut self, app: &mut App, n: usize) {
+        match self.selected_pane {
+            BrowsePane::Playlists => self.playlists.select_nth(app, n),
+            BrowsePane::Songs => self.songs.select_nth(n),
+            BrowsePane::GlobalSearch | BrowsePane::Modal(_) => {}
+        }
+    }
+
+    /// Creates a playlist named after `dir`, with the audio files inside it
+    fn import_directory(&mut self, app: &mut App, dir: &str) -> Result<()> {
+        use playlist_management::CreatePlaylistError;
+
+        let path = Path::new(dir);
+        let Some(playlist) = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .filter(|_| path.is_dir())
+        else {
+            app.notify_err(format!("'{}' is not a directory", dir));
+            return Ok(());
+        };
+
+        match playlist_management::create_playlist(playlist) {
+            Ok(_) => {}
+            Err(CreatePlaylistError::PlaylistAlreadyExists) => {
+                app.notify_err(format!("Playlist '{}' already exists!", playlist));
+                return Ok(());
+            }
+            Err(CreatePlaylistError::InvalidChar(c)) => {
+                app.notify_err(format!("Playlist names cannot contain '{}'", c));
+                return Ok(());
+            }
+            Err(CreatePlaylistError::IOError(e)) => return Err(e.into()),
+        }
+
+        let recursive = Config::global().import_recursive;
+        let result = playlist_management::import_directory(path, playlist, recursive);
+        if result.is_err() {
+            // Don't leave an empty playlist behind
+            let _ = std::fs::remove_file(Config::playlist_path(playlist));
+        }
+
+        self.playlists.reload_from_dir()?;
+        self.playlists.select_by_name(app, playlist);
+        self.reload_songs()?;
+
+        let count = result?;
+        app.notify_ok(format!("Added {} songs to '{}'", count, playlist));
+        Ok(())
+    }
+
     // TODO: I don't know how to make this 'a instead of 'static :(
     fn open_modal<T>(&mut self, title: T, modal_type: ModalType) -> &mut Box<dyn Modal>
     where
@@ -328,12 +1020,106 @@ impl<'a> BrowseScreen<'a> {
         &mut self.modal
     }
 
+    /// Like [Self::open_modal], but the user can recall songs from [Self::input_history] and
+    /// complete paths with Tab
+    fn open_modal_with_history(&mut self, title: &'static str, modal_type: ModalType) {
+        self.selected_pane = BrowsePane::Modal(modal_type);
+        self.modal = Box::new(
+            InputModal::new(title)
+                .with_history(self.input_history.clone())
+                .with_completion(util::path_completions),
+        );
+    }
+
     fn open_confirmation(&mut self, title: &str, modal_type: ModalType) -> &mut Box<dyn Modal> {
         self.selected_pane = BrowsePane::Modal(modal_type);
         self.modal = Box::new(ConfirmationModal::new(title));
         &mut self.modal
     }
 
+    fn open_rename_playlist(&mut self) {
+        if let Some(playlist) = self.playlists.selected_item() {
+            let modal_type = ModalType::RenamePlaylist {
+                playlist: playlist.to_owned(),
+            };
+            let playlist = playlist.to_owned();
+            self.open_modal("<placeholder>", modal_type);
+            self.modal =
+                Box::new(InputModal::new(" Rename playlist (esc cancels) ").set_input(playlist));
+        }
+    }
+
+    fn rename_playlist(&mut self, app: &mut App, playlist: &str, new_name: &str) -> Result<()> {
+        use playlist_management::CreatePlaylistError;
+
+        if playlist == new_name {
+            return Ok(());
+        }
+
+        match playlist_management::rename_playlist(playlist, new_name) {
+            Ok(()) => {}
+            Err(CreatePlaylistError::PlaylistAlreadyExists) => {
+                app.notify_err(format!("Playlist '{}' already exists!", new_name));
+                return Ok(());
+            }
+            Err(CreatePlaylistError::InvalidChar(c)) => {
+                app.notify_err(format!("Playlist names cannot contain '{}'", c));
+                return Ok(());
+            }
+            Err(CreatePlaylistError::IOError(e)) => return Err(e.into()),
+        }
+
+        self.songs.rename_playlist(playlist, new_name);
+        self.playlists.reload_from_dir()?;
+        self.playlists.select_by_name(app, new_name);
+        self.reload_songs()
+    }
+
+    fn open_duplicate_playlist(&mut self) {
+        if let Some(playlist) = self.playlists.selected_item() {
+            let modal_type = ModalType::DuplicatePlaylist {
+                playlist: playlist.to_owned(),
+            };
+            let new_name = format!("{} copy", playlist);
+            self.open_modal("<placeholder>", modal_type);
+            self.modal =
+                Box::new(InputModal::new(" Name of the copy (esc cancels) ").set_input(new_name));
+        }
+    }
+
+    fn duplicate_playlist(&mut self, app: &mut App, playlist: &str, new_name: &str) -> Result<()> {
+        use playlist_management::CreatePlaylistError;
+
+        match playlist_management::duplicate_playlist(playlist, new_name) {
+            Ok(()) => {}
+            Err(CreatePlaylistError::PlaylistAlreadyExists) => {
+                app.notify_err(format!("Playlist '{}' already exists!", new_name));
+                return Ok(());
+            }
+            Err(CreatePlaylistError::InvalidChar(c)) => {
+                app.notify_err(format!("Playlist names cannot contain '{}'", c));
+                return Ok(());
+            }
+            Err(CreatePlaylistError::IOError(e)) => return Err(e.into()),
+        }
+
+        self.playlists.reload_from_dir()?;
+        self.playlists.select_by_name(app, new_name);
+        self.reload_songs()
+    }
+
+    /// Asks for confirmation before deleting the selected playlist
+    fn open_delete_playlist(&mut self) {
+        if let Some(playlist) = self.playlists.selected_item() {
+            let title = format!("Do you really want to delete '{}'?", playlist);
+            let modal_type = ModalType::DeletePlaylist {
+                playlist: playlist.to_owned(),
+            };
+            self.open_confirmation(title.as_str(), modal_type)
+                .apply_style(Style::default().fg(Color::LightRed));
+        }
+    }
+
     fn open_help_modal(&mut self) -> &mut Box<dyn Modal> {
         self.selected_pane = BrowsePane::Modal(ModalType::Help);
         self.modal = Box::new(HelpModal::new());
@@ -346,40 +1132,183 @@ impl<'a> BrowseScreen<'a> {
         &mut self.modal
     }
 
+    /// Selects the song being played, in the shown playlist if it's there, or else in the first
+    /// playlist that has it
+    pub fn go_to_playing(&mut self, app: &mut App) -> Result<()> {
+        let state = app.refresh_player_state();
+        if state.idle || state.path.is_empty() {
+            app.notify_info("Nothing is playing");
+            return Ok(());
+        }
+        let path = state.path.clone();
+
+        if !self.songs.select_path(&path) {
+            let playlists = self.playlists.playlists();
+            let Some(playlist) = playlist_with_song(playlists, Config::playlist_path, &path) else {
+                app.notify_info("The song being played isn't in any playlist");
+                return Ok(());
+            };
+            let playlist = playlist.to_string();
+            self.select_playlist(&playlist)?;
+            self.songs.select_path(&path);
+        }
+        self.selected_pane = BrowsePane::Songs;
+        Ok(())
+    }
+
+    /// Selects the playlist and the song of the selected global search result
+    fn jump_to_search_result(&mut self, app: &mut App) -> Result<()> {
+        if let Some(result) = self.global_search.selected_item() {
+            let (playlist, index) = (result.playlist.clone(), result.index);
+            self.playlists.select_by_name(app, &playlist);
+            self.reload_songs()?;
+            self.songs.select_song(index);
+            self.selected_pane = BrowsePane::Songs;
+        }
+        Ok(())
+    }
+
     fn select_next_panel(&mut self) {
         use BrowsePane::*;
         match self.selected_pane {
             Playlists => {
                 self.selected_pane = Songs;
             }
-            Songs => {
+            Songs | GlobalSearch => {
                 self.selected_pane = Playlists;
             }
             Modal(_) => {}
         }
     }
 
+    /// The focused pane's [status hint](Component::status_hint), if there's one
+    fn status_hint(&self) -> Option<String> {
+        match self.selected_pane {
+            BrowsePane::Playlists => self.playlists.status_hint(),
+            BrowsePane::Songs => self.songs.status_hint(),
+            BrowsePane::GlobalSearch => self.global_search.status_hint(),
+            BrowsePane::Modal(_) => None,
+        }
+    }
+
+    /// Splits off the bottom line for the status hint, if there's one. Returns (panes chunk,
+    /// hint chunk).
+    fn hint_chunks(&self, chunk: Rect) -> (Rect, Option<Rect>) {
+        match self.status_hint() {
+            Some(_) if chunk.height > 3 => {
+                let (panes, hint) = chunk.split_bottom(1);
+                (panes, Some(hint))
+            }
+            _ => (chunk, None),
+        }
+    }
+
     fn subcomponent_chunks(&self, chunk: Rect) -> Rc<[Rect]> {
+        // Compact mode is meant for small terminals, so the songs get more of the room
+        let playlists_width = if Config::global().compact { 12 } else { 15 };
         Layout::default()
             .direction(Direction::Horizontal)
-            .constraints([Constraint::Percentage(15), Constraint::Percentage(85)].as_ref())
+            .constraints(
+                [
+                    Constraint::Percentage(playlists_width),
+                    Constraint::Percentage(100 - playlists_width),
+                ]
+                .as_ref(),
+            )
             .split(chunk)
     }
 }
 
+/// Hint for a pane with a `/` filter, whose text without the `/` is `query`
+fn filter_hint(filter: &str, query: &str) -> Option<String> {
+    if filter.is_empty() {
+        None
+    } else if filter.ends_with('\n') {
+        Some(format!("filtered by '{}', esc clears", query))
+    } else {
+        Some(format!(
+            "filtering: {} (enter keeps the filter, esc clears it)",
+            query
+        ))
+    }
+}
+
+/// The first of `playlists` that has a song at `song_path`, where `path_of` gives the path of a
+/// playlist by name. Playlists that can't be read are skipped.
+fn playlist_with_song<'p>(
+    playlists: &'p [String],
+    path_of: impl Fn(&str) -> PathBuf,
+    song_path: &str,
+) -> Option<&'p str> {
+    playlists.iter().map(String::as_str).find(|playlist| {
+        let path = path_of(playlist);
+        let Ok(file) = std::fs::File::open(&path) else {
+            return false;
+        };
+        let Ok(mut songs) = m3u::Parser::from_reader(file).all_songs() else {
+            return false;
+        };
+        if let Some(dir) = path.parent() {
+            for song in &mut songs {
+                song.resolve_relative_to(dir);
+            }
+        }
+        songs.iter().any(|song| song.path == song_path)
+    })
+}
+
+/// Adds an entry to the end of an input history, unless it's the same as the last one
+fn push_history(history: &mut Vec<String>, entry: String) {
+    if !entry.is_empty() && history.last() != Some(&entry) {
+        history.push(entry);
+    }
+}
+
+/// The count typed so far, after `key` is typed. Returns `None` if `key` doesn't continue the count.
+fn count_with_key(count: Option<usize>, key: KeyEvent) -> Option<usize> {
+    if key
+        .modifiers
+        .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+    {
+        return None;
+    }
+    let KeyCode::Char(c) = key.code else {
+        return None;
+    };
+    let digit = c.to_digit(10)? as usize;
+    match count {
+        // Counts don't start with a 0
+        None if digit == 0 => None,
+        None => Some(digit),
+        Some(count) => Some((count * 10 + digit).min(MAX_COUNT)),
+    }
+}
+
 impl<'t> Component for BrowseScreen<'t> {
     type RenderState = ();
 
     fn render(&mut self, frame: &mut Frame<'_, MyBackend>, chunk: Rect, (): ()) {
+        let (chunk, hint_chunk) = self.hint_chunks(chunk);
+        if let (Some(hint_chunk), Some(hint)) = (hint_chunk, self.status_hint()) {
+            let hint =
+                Paragraph::new(format!(" {}", hint)).style(Style::default().fg(Color::DarkGray));
+            frame.render_widget(hint, hint_chunk);
+        }
+
         let hchunks = self.subcomponent_chunks(chunk);
+        let playlists_chunk = self.render_album_art(frame, hchunks[0]);
 
         self.playlists.render(
             frame,
-            hchunks[0],
+            playlists_chunk,
             self.selected_pane == BrowsePane::Playlists,
         );
-        self.songs
-            .render(frame, hchunks[1], self.selected_pane == BrowsePane::Songs);
+        if self.selected_pane == BrowsePane::GlobalSearch {
+            self.global_search.render(frame, hchunks[1], true);
+        } else {
+            self.songs
+                .render(frame, hchunks[1], self.selected_pane == BrowsePane::Songs);
+        }
 
         if let BrowsePane::Modal(_) = self.selected_pane {
             self.modal.render(frame);
@@ -392,14 +1321,52 @@ impl<'t> Component for BrowseScreen<'t> {
             Command(cmd) => self.handle_command(app, cmd)?,
             SongAdded { playlist, song } => {
                 if self.playlists.selected_item() == Some(playlist.as_str()) {
-                    self.reload_songs()?;
+                    if let Err(e) = self.reload_songs() {
+                        app.notify_err(e.to_string());
+                        return Ok(());
+                    }
                 }
                 app.notify_ok(format!("\"{}\" was added to {}", song, playlist));
             }
-            SecondTick => {}
+            SecondTick => {
+                self.songs.tick_marquee();
+                self.songs.update_stream_title(app.player_state());
+                self.songs.update_playing(app.player_state());
+                #[cfg(feature = "album-art")]
+                if let Some(art) = &mut self.album_art {
+                    art.update(app.player_state());
+                }
+            }
+            ConfigReloaded(_) | ScrobbleFailed(_) | Terminate => {}
+            RecentSongsChanged if self.playlists.recent_selected() => {
+                self.songs
+                    .refresh_recent(self.recent_songs.borrow().to_songs());
+            }
+            RecentSongsChanged => {}
             ChangedPlaylist => {
-                self.reload_songs()?;
+                let sender = app.channel.sender.clone();
+                let recent_songs = self.recent_songs.borrow();
+                self.songs
+                    .load_from_playlist_pane(&self.playlists, &recent_songs, sender);
             }
+            PlaylistLoading { id, count } => self.songs.loading_progress(id, count),
+            PlaylistLoaded { id, result } if self.songs.is_loading(id) => match result {
+                Ok(loaded) => {
+                    self.songs.finish_loading(loaded);
+                    if let Some(playlist) = self.songs.skipped_nested().first() {
+                        app.notify_err(format!(
+                            "Left out {}, which includes itself or is nested too deep",
+                            playlist
+                        ));
+                    }
+                }
+                Err(e) => {
+                    self.songs.finish_loading(m3u::LoadedSongs::default());
+                    app.notify_err(e);
+                }
+            },
+            // Another playlist was selected while this one was loading
+            PlaylistLoaded { .. } => {}
             Terminal(event) => self.handle_terminal_event(app, event)?,
         }
         Ok(())
@@ -410,6 +1377,7 @@ impl<'t> Component for BrowseScreen<'t> {
         match self.selected_pane {
             Playlists => self.playlists.mode(),
             Songs => self.songs.mode(),
+            GlobalSearch => self.global_search.mode(),
             Modal(_) => self.modal.mode(),
         }
     }
@@ -417,12 +1385,17 @@ impl<'t> Component for BrowseScreen<'t> {
 
 impl<'a> MouseHandler for BrowseScreen<'a> {
     fn handle_mouse(&mut self, app: &mut App, chunk: Rect, event: MouseEvent) -> Result<()> {
-        if let BrowsePane::Modal(_) = self.selected_pane {
-            // No modal clicks for now
+        if let BrowsePane::Modal(_) | BrowsePane::GlobalSearch = self.selected_pane {
+            // No modal or search result clicks for now
             return Ok(());
         }
 
+        let (chunk, _) = self.hint_chunks(chunk);
         let hchunks = self.subcomponent_chunks(chunk);
+        if let MouseEventKind::Up(MouseButton::Left) = event.kind {
+            // Ends a drag in the songs pane, wherever the mouse is released
+            return self.songs.handle_mouse(app, hchunks[1], event);
+        }
         if hchunks[0].contains(event.column, event.row) {
             if let MouseEventKind::Down(_) = event.kind {
                 self.selected_pane = BrowsePane::Playlists;
@@ -436,3 +1409,50 @@ impl<'a> MouseHandler for BrowseScreen<'a> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_playlist_with_song() {
+        let dir = std::env::temp_dir().join(format!("tori-playing-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("rock.m3u8"), "#EXTM3U\n/music/a.mp3\n").unwrap();
+        std::fs::write(dir.join("jazz.m3u8"), "#EXTM3U\nb.mp3\n/music/a.mp3\n").unwrap();
+
+        let playlists = ["missing", "jazz", "rock"].map(String::from);
+        let path_of = |name: &str| dir.join(format!("{}.m3u8", name));
+        let found = |song: &str| playlist_with_song(&playlists, path_of, song);
+        assert_eq!(found("/music/a.mp3"), Some("jazz"));
+        // Relative paths are resolved against the playlist's directory
+        assert_eq!(found(dir.join("b.mp3").to_str().unwrap()), Some("jazz"));
+        assert_eq!(found("/music/c.mp3"), None);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_count_with_key() {
+        let key = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+        assert_eq!(count_with_key(None, key('5')), Some(5));
+        assert_eq!(count_with_key(Some(1), key('0')), Some(10));
+        assert_eq!(count_with_key(None, key('0')), None);
+        assert_eq!(count_with_key(Some(5), key('j')), None);
+        assert_eq!(count_with_key(Some(9999), key('9')), Some(MAX_COUNT));
+
+        let ctrl_5 = KeyEvent::new(KeyCode::Char('5'), KeyModifiers::CONTROL);
+        assert_eq!(count_with_key(None, ctrl_5), None);
+    }
+
+    #[test]
+    fn test_count_containing_1_and_2() {
+        // `1` and `2` switch screens, unless a count was started, like in `312j`
+        let key = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+        let count = count_with_key(None, key('3'));
+        let count = count_with_key(count, key('1'));
+        let count = count_with_key(count, key('2'));
+        assert_eq!(count, Some(312));
+        assert_eq!(count_with_key(count, key('j')), None);
+    }
+}
@@ -8,24 +8,30 @@ use crate::{
     config::Config,
     error::Result,
     events::Event,
+    m3u::playlist_management,
+    util,
+    widgets::Scrollbar,
 };
 use crossterm::event::{KeyCode, KeyEvent, MouseButton, MouseEventKind};
-use crossterm::terminal::{EnterAlternateScreen, LeaveAlternateScreen};
-use crossterm::ExecutableCommand;
-use std::io;
-use std::result::Result as StdResult;
+use std::path::Path;
 use tui::{
     layout::{self, Rect},
-    style::{Color, Style},
+    style::{Color, Modifier, Style},
+    text::Span,
     widgets::{Block, BorderType, Borders, List, ListItem, ListState, Paragraph, Wrap},
     Frame,
 };
 
+/// Name of the entry that shows the [recently played](Config::recent_songs) songs
+pub const RECENT_PLAYLIST: &str = "Recently played";
+
 #[derive(Debug, Default)]
 pub struct PlaylistsPane {
     playlists: Vec<String>,
     shown: FilteredList<ListState>,
     filter: String,
+    /// Whether the first of `playlists` is [RECENT_PLAYLIST], which isn't a playlist file
+    recent: bool,
 }
 
 impl PlaylistsPane {
@@ -36,38 +42,30 @@ impl PlaylistsPane {
     }
 
     pub fn reload_from_dir(&mut self) -> Result<()> {
-        let dir = std::fs::read_dir(&Config::global().playlists_dir)
-            .map_err(|e| format!("Failed to read playlists directory: {}", e))?;
-
-        use std::fs::DirEntry;
-        let extract_playlist_name = |entry: StdResult<DirEntry, io::Error>| {
-            Ok(entry
-                .unwrap()
-                .file_name()
-                .into_string()
-                .map_err(|filename| format!("File '{:?}' has invalid UTF-8", filename))?
-                .trim_end_matches(".m3u8")
-                .to_string())
-        };
-
-        self.playlists = dir
-            .into_iter()
-            .map(extract_playlist_name)
-            .collect::<Result<_>>()?;
-
-        self.playlists.sort();
+        let config = Config::global();
+        self.playlists = playlist_management::list_playlists(Path::new(&config.playlists_dir))?;
+        self.recent = config.recent_songs > 0;
+        if self.recent {
+            self.playlists.insert(0, RECENT_PLAYLIST.to_string());
+        }
         self.refresh_shown();
         Ok(())
     }
 
+    /// The filter text, without the leading `/`
+    fn filter_query(&self) -> &str {
+        self.filter
+            .get(1..)
+            .unwrap_or_default()
+            .trim_end_matches('\n')
+    }
+
+    /// Shows the playlists whose names contain the filter text, ignoring case
     fn refresh_shown(&mut self) {
+        let query = self.filter_query().to_lowercase();
         self.shown.filter(
             &self.playlists,
-            |s| {
-                self.filter.is_empty()
-                    || s.to_lowercase()
-                        .contains(&self.filter[1..].trim_end_matches('\n').to_lowercase())
-            },
+            |s| s.to_lowercase().contains(&query),
             |i, j| i.cmp(&j),
         );
     }
@@ -95,12 +93,18 @@ impl PlaylistsPane {
     }
 
     pub fn select_next(&mut self, app: &mut App) {
-        self.shown.select_next();
+        self.shown.select_next(Config::global().wrap_navigation);
         app.channel.send(Event::ChangedPlaylist).unwrap();
     }
 
     pub fn select_prev(&mut self, app: &mut App) {
-        self.shown.select_prev();
+        self.shown.select_prev(Config::global().wrap_navigation);
+        app.channel.send(Event::ChangedPlaylist).unwrap();
+    }
+
+    /// See [FilteredList::select_nth]
+    pub fn select_nth(&mut self, app: &mut App, n: usize) {
+        self.shown.select_nth(n);
         app.channel.send(Event::ChangedPlaylist).unwrap();
     }
 
@@ -109,30 +113,57 @@ impl PlaylistsPane {
         app.channel.send(Event::ChangedPlaylist).unwrap();
     }
 
+    /// Clears the filter and selects the playlist with the given name, if it exists
+    pub fn select_by_name(&mut self, app: &mut App, name: &str) {
+        if self.select_name(name) {
+            app.channel.send(Event::ChangedPlaylist).unwrap();
+        }
+    }
+
+    /// Like [Self::select_by_name], but doesn't notify the app. Returns whether the playlist
+    /// exists.
+    pub fn select_name(&mut self, name: &str) -> bool {
+        self.filter.clear();
+        self.refresh_shown();
+        let position = self
+            .shown
+            .items
+            .iter()
+            .position(|&i| !self.is_recent(i) && self.playlists[i] == name);
+        if position.is_some() {
+            self.shown.state.select(position);
+        }
+        position.is_some()
+    }
+
+    /// Names of the playlist files, without [RECENT_PLAYLIST]
+    pub fn playlists(&self) -> &[String] {
+        &self.playlists[self.recent as usize..]
+    }
+
+    /// Name of the selected playlist. `None` if it's [RECENT_PLAYLIST], which isn't a file.
     pub fn selected_item(&self) -> Option<&str> {
         self.shown
             .selected_item()
+            .filter(|&i| !self.is_recent(i))
             .and_then(|i| self.playlists.get(i))
             .map(|s| s.as_str())
     }
 
+    /// Whether [RECENT_PLAYLIST] is selected
+    pub fn recent_selected(&self) -> bool {
+        matches!(self.shown.selected_item(), Some(i) if self.is_recent(i))
+    }
+
+    fn is_recent(&self, i: usize) -> bool {
+        self.recent && i == 0
+    }
+
     pub fn open_editor_for_selected(&mut self, app: &mut App) -> Result<()> {
         if let Some(selected) = self.selected_item() {
             let editor = std::env::var("EDITOR").unwrap_or_else(|_| "nano".to_string());
-
-            let _lock = app.channel.receiving_crossterm.lock().unwrap();
-            io::stdout().execute(LeaveAlternateScreen)?;
-
-            let res = std::process::Command::new(&editor)
-                .arg(Config::playlist_path(selected))
-                .status()
-                .map_err(|err| format!("Failed to execute editor '{}': {}", editor, err));
-
-            io::stdout().execute(EnterAlternateScreen)?;
-
-            res?;
+            app.open_in_editor(&editor, &Config::playlist_path(selected))?;
             self.reload_from_dir()?;
-            app.terminal.clear()?;
         }
         Ok(())
     }
@@ -155,6 +186,10 @@ impl PlaylistsPane {
 impl Component for PlaylistsPane {
     type RenderState = bool;
 
+    fn status_hint(&self) -> Option<String> {
+        super::filter_hint(&self.filter, self.filter_query())
+    }
+
     fn mode(&self) -> Mode {
         if self.filter.is_empty() || self.filter.as_bytes().last() == Some(&b'\n') {
             Mode::Normal
@@ -170,31 +205,70 @@ impl Component for PlaylistsPane {
             " playlists ".into()
         };
 
-        let mut block = Block::default()
-            .title(title)
-            .borders(Borders::LEFT | Borders::BOTTOM | Borders::TOP)
-            .border_type(BorderType::Plain);
+        let border_style = if is_focused {
+            Style::default().fg(Color::LightBlue)
+        } else {
+            Style::default()
+        };
 
-        if is_focused {
-            block = block.border_style(Style::default().fg(Color::LightBlue));
-        }
+        let compact = Config::global().compact;
+        let (title, borders) = if compact {
+            // The title is what shows the focus then
+            (Span::styled(title, border_style), Borders::NONE)
+        } else {
+            (
+                Span::raw(title),
+                Borders::LEFT | Borders::BOTTOM | Borders::TOP,
+            )
+        };
+
+        let block = Block::default()
+            .title(title)
+            .borders(borders)
+            .border_type(BorderType::Plain)
+            .border_style(border_style);
 
         if !self.playlists.is_empty() {
             // Render playlists list
+            let recent = self.recent;
             let playlists: Vec<_> = self
                 .shown
                 .items
                 .iter()
-                .map(|&i| ListItem::new(self.playlists[i].as_str()))
+                .map(|&i| {
+                    let item = ListItem::new(self.playlists[i].as_str());
+                    if recent && i == 0 {
+                        item.style(Style::default().add_modifier(Modifier::ITALIC))
+                    } else {
+                        item
+                    }
+                })
                 .collect();
 
             let widget = List::new(playlists)
                 .block(block)
                 .highlight_style(Style::default().bg(Color::LightBlue).fg(Color::Black));
             frame.render_stateful_widget(widget, chunk, &mut self.shown.state);
+
+            if self.shown.items.len() > chunk.height.saturating_sub(2) as usize {
+                // Render scrollbar. There's no right border, so it goes over the last column.
+                let scrollbar = Scrollbar::themed(
+                    self.shown.state.selected().unwrap_or(0) as u16,
+                    self.shown.items.len() as u16,
+                    border_style,
+                );
+                frame.render_widget(
+                    scrollbar,
+                    chunk.inner(&layout::Margin {
+                        vertical: 1,
+                        horizontal: 0,
+                    }),
+                );
+            }
         } else {
             // Help message
-            let key = Config::global()
+            let config = Config::global();
+            let key = config
                 .keybindings
                 .0
                 .iter()
@@ -203,8 +277,9 @@ impl Component for PlaylistsPane {
                 .unwrap_or("a");
 
             let widget = Paragraph::new(format!(
-                "You don't have any playlists yet! Press '{}' to add one.",
-                key
+                "You don't have any playlists yet! Press '{}' to add one, or put .m3u8 files in {}",
+                key,
+                Config::global().playlists_dir
             ))
             .wrap(Wrap { trim: true })
             .block(block)
@@ -224,7 +299,26 @@ impl Component for PlaylistsPane {
             Command(cmd) => match cmd {
                 SelectNext => self.select_next(app),
                 SelectPrev => self.select_prev(app),
+                SelectFirst => {
+                    self.shown.select_first();
+                    app.channel.send(Event::ChangedPlaylist).unwrap();
+                }
+                SelectLast => {
+                    self.shown.select_last();
+                    app.channel.send(Event::ChangedPlaylist).unwrap();
+                }
                 Search => self.filter = "/".into(),
+                CopyPlaylistPath => {
+                    if let Some(playlist) = self.selected_item() {
+                        let path = Config::playlist_path(playlist);
+                        let path = path.to_string_lossy().into_owned();
+                        util::copy_to_clipboard(path.clone())?;
+                        #[cfg(feature = "clip")]
+                        app.notify_info(format!("Copied {} to the clipboard", path));
+                        #[cfg(not(feature = "clip"))]
+                        app.notify_info("Clipboard support is disabled for this build. You can enable it by building with '--features clip'");
+                    }
+                }
                 _ => {}
             },
             Terminal(event) => match event {
@@ -247,6 +341,11 @@ impl Component for PlaylistsPane {
                         _ => {}
                     }
                 }
+                crossterm::event::Event::Paste(text) if self.mode() == Mode::Insert => {
+                    self.filter.push_str(&util::single_line(&text));
+                    self.refresh_shown();
+                    app.channel.send(Event::ChangedPlaylist).unwrap();
+                }
                 _ => {}
             },
             _ => {}
@@ -274,3 +373,59 @@ impl MouseHandler for PlaylistsPane {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+
+    #[test]
+    fn test_filter() {
+        let mut pane = PlaylistsPane {
+            playlists: vec!["Rock".into(), "jazz".into(), "Prog rock".into()],
+            ..Default::default()
+        };
+        pane.refresh_shown();
+        assert_eq!(pane.shown.items, vec![0, 1, 2]);
+
+        pane.filter = "/".into();
+        for c in "ROCK".chars() {
+            pane.handle_filter_key_event(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE))
+                .unwrap();
+        }
+        pane.refresh_shown();
+        assert_eq!(pane.shown.items, vec![0, 2]);
+        assert_eq!(pane.mode(), Mode::Insert);
+
+        // Esc clears the filter and shows every playlist again
+        pane.handle_filter_key_event(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE))
+            .unwrap();
+        pane.refresh_shown();
+        assert_eq!(pane.shown.items, vec![0, 1, 2]);
+        assert_eq!(pane.mode(), Mode::Normal);
+    }
+
+    #[test]
+    fn test_recent_playlist() {
+        let mut pane = PlaylistsPane {
+            playlists: vec![
+                RECENT_PLAYLIST.into(),
+                "rock".into(),
+                RECENT_PLAYLIST.into(),
+            ],
+            recent: true,
+            ..Default::default()
+        };
+        pane.refresh_shown();
+        assert_eq!(pane.playlists(), ["rock", RECENT_PLAYLIST]);
+
+        pane.shown.state.select(Some(0));
+        assert!(pane.recent_selected());
+        assert_eq!(pane.selected_item(), None);
+
+        // A playlist file can have the same name
+        assert!(pane.select_name(RECENT_PLAYLIST));
+        assert!(!pane.recent_selected());
+        assert_eq!(pane.selected_item(), Some(RECENT_PLAYLIST));
+    }
+}
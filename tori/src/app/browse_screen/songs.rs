@@ -1,54 +1,371 @@
 use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::mem;
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
 
 use crate::app::component::MouseHandler;
 use crate::command::Command;
-use crate::error::Result;
+use crate::error::{Error, Result};
 use crate::events::Event;
-use crate::player::Player;
+use crate::m3u::{self, LoadedSongs};
+use crate::player::{Player, PlayerState};
+use crate::recent::RecentSongs;
 use crate::util::ClickInfo;
 use crate::widgets::Scrollbar;
 use crate::{
     app::{component::Component, filtered_list::FilteredList, App, Mode, MyBackend},
-    config::Config,
+    config::{Config, FilterMode, SongColumn, SortingMethod},
 };
-use crate::{m3u, util};
+use crate::{fuzzy, util};
 
 use crossterm::event::{KeyCode, KeyEvent, MouseButton, MouseEventKind};
+use regex::{Regex, RegexBuilder};
 use tui::layout::Rect;
 use tui::widgets::{Paragraph, Wrap};
 use tui::{
     layout::{self, Constraint},
-    style::{Color, Style},
-    widgets::{Block, BorderType, Borders, Row, Table, TableState},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Cell, Row, Table, TableState},
     Frame,
 };
+use unicode_width::UnicodeWidthChar;
 
-/////////////////////////////////
-//        SortingMethod        //
-/////////////////////////////////
-#[derive(Debug, Default, Clone, Copy)]
-enum SortingMethod {
-    #[default]
-    /// identity permutation
-    Index,
-    Title,
-    Duration,
+use super::playlists::RECENT_PLAYLIST;
+
+/// Fills in the duration of local songs that don't have one (e.g. because they have no #EXTINF line)
+fn probe_missing_durations(songs: &mut [m3u::Song]) {
+    for song in songs.iter_mut().filter(|s| s.duration.is_zero()) {
+        if let Some(duration) = m3u::Song::probe_duration(&song.path) {
+            song.duration = duration;
+        }
+    }
 }
 
-impl SortingMethod {
-    pub fn next(&self) -> Self {
-        use SortingMethod::*;
-        match self {
-            Index => Title,
-            Title => Duration,
-            Duration => Index,
+/// How many songs a playlist loaded in the background reads between progress updates
+const LOAD_PROGRESS_STEP: usize = 500;
+
+/// Reads the songs of the playlist at `path`, expanding nested playlists and probing durations if
+/// the config asks for it. `progress` is called with the number of songs read so far, and can stop
+/// the loading by returning false, in which case this returns `Ok(None)`. It's also called again
+/// between the slower steps, so that they don't run for a playlist that isn't waited for anymore.
+fn load_songs(path: &Path, mut progress: impl FnMut(usize) -> bool) -> Result<Option<LoadedSongs>> {
+    let file = std::fs::File::open(path)
+        .map_err(|e| format!("Couldn't open playlist file {}: {}", path.display(), e))?;
+
+    let mut parser = m3u::Parser::from_reader(file);
+    let mut songs = Vec::new();
+    while let Some(song) = parser.next_song()? {
+        songs.push(song);
+        if songs.len() % LOAD_PROGRESS_STEP == 0 && !progress(songs.len()) {
+            return Ok(None);
         }
     }
+    if !progress(songs.len()) {
+        return Ok(None);
+    }
+
+    if let Some(dir) = path.parent() {
+        for song in &mut songs {
+            song.resolve_relative_to(dir);
+        }
+    }
+    let mut loaded = LoadedSongs::default();
+    if Config::global().expand_nested_playlists {
+        let count = songs.len();
+        let (expanded, skipped) = m3u::parser::expand_nested(songs, path);
+        loaded.has_nested = expanded.len() != count || !skipped.is_empty();
+        loaded.skipped_nested = skipped;
+        songs = expanded;
+        if !progress(songs.len()) {
+            return Ok(None);
+        }
+    }
+    if Config::global().probe_durations {
+        probe_missing_durations(&mut songs);
+    }
+    loaded.songs = songs;
+    Ok(Some(loaded))
 }
 
-fn compare_songs(
+/// The name of the playlist at `path`
+fn playlist_title(path: &Path) -> Cow<'static, str> {
+    Cow::Owned(path.file_stem().unwrap().to_string_lossy().to_string())
+}
+
+/// The message shown when a playlist can't be loaded
+fn load_error_message(error: Error) -> String {
+    match error {
+        Error::M3u(e) => format!(
+            "This playlist has an error: {}. Try fixing it with OpenInEditor",
+            e
+        ),
+        e => e.to_string(),
+    }
+}
+
+/// A playlist being loaded in the background by [SongsPane::load_playlist]
+#[derive(Debug)]
+struct Loading {
+    id: u64,
+    /// How many songs were read so far
+    count: usize,
+    /// Selection and scroll to restore once the songs are shown
+    state: TableState,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl Drop for Loading {
+    /// Stops the background thread once this load isn't waited for anymore
+    fn drop(&mut self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Widths of the songs table columns for a table `width` cells wide, borders included. The index
+/// and the duration have fixed widths, and the rest is split between the other columns, with
+/// artists getting half as much as titles and paths. A `compact` table has no borders and no
+/// padding after the duration, but keeps its last cell free for the scrollbar.
+fn column_widths(columns: &[SongColumn], width: u16, compact: bool) -> Vec<Constraint> {
+    let fixed_width = |column: &SongColumn| match column {
+        SongColumn::Index => Some(5),
+        SongColumn::Duration if compact => Some(6),
+        SongColumn::Duration => Some(10),
+        _ => None,
+    };
+    // The scrollbar is drawn over the right border, or in the last cell without borders
+    let borders = if compact { 1 } else { 2 };
+    let weight = |column: &SongColumn| match column {
+        SongColumn::Artist => 1,
+        _ => 2,
+    };
+
+    // The borders, two cells for the highlight symbol and one between every two columns
+    let fixed_total: u16 = columns.iter().filter_map(fixed_width).sum::<u16>()
+        + borders
+        + 2
+        + columns.len().saturating_sub(1) as u16;
+    let free_width = width.saturating_sub(fixed_total);
+    let total_weight: u16 = columns
+        .iter()
+        .filter(|c| fixed_width(c).is_none())
+        .map(weight)
+        .sum();
+
+    let mut widths: Vec<_> = columns
+        .iter()
+        .map(|c| fixed_width(c).unwrap_or(free_width * weight(c) / total_weight.max(1)))
+        .collect();
+
+    // Rounding leftovers go to the first flexible column
+    if let Some(first) = columns.iter().position(|c| fixed_width(c).is_none()) {
+        let others: u16 = columns
+            .iter()
+            .zip(&widths)
+            .enumerate()
+            .filter(|&(i, (c, _))| i != first && fixed_width(c).is_none())
+            .map(|(_, (_, &w))| w)
+            .sum();
+        widths[first] = free_width - others;
+    }
+
+    widths.into_iter().map(Constraint::Length).collect()
+}
+
+/// Fits a line in `width` cells, after skipping its first `offset` chars. If it still doesn't fit,
+/// it's cut off with an ellipsis.
+fn fit_line(line: Line<'_>, width: usize, offset: usize) -> Line<'static> {
+    let chars: Vec<(char, Style)> = line
+        .spans
+        .iter()
+        .flat_map(|span| span.content.chars().map(move |c| (c, span.style)))
+        .skip(offset)
+        .collect();
+    let char_width = |c: char| c.width().unwrap_or(0);
+
+    let total: usize = chars.iter().map(|&(c, _)| char_width(c)).sum();
+    let mut kept = Vec::new();
+    if total <= width {
+        kept = chars;
+    } else if width > 0 {
+        let mut used = 0;
+        for (c, style) in chars {
+            if used + char_width(c) > width - 1 {
+                break;
+            }
+            used += char_width(c);
+            kept.push((c, style));
+        }
+        kept.push(('…', Style::default()));
+    }
+
+    // Join consecutive chars with the same style back into spans
+    let mut spans: Vec<Span<'static>> = Vec::new();
+    for (c, style) in kept {
+        match spans.last_mut() {
+            Some(span) if span.style == style => span.content.to_mut().push(c),
+            _ => spans.push(Span::styled(c.to_string(), style)),
+        }
+    }
+    Line::from(spans)
+}
+
+/// Splits the title and the artist of a song into spans, with the parts matched by the filter
+/// query highlighted. This is only visual, and doesn't affect which songs are shown.
+fn highlight_matches<'s>(
+    song: &'s m3u::Song,
+    query: &str,
+    mode: FilterMode,
+    regex: Option<&Regex>,
+) -> (Line<'s>, Line<'s>) {
+    let artist = song.artist.as_deref().unwrap_or_default();
+    if query.is_empty() {
+        return (Line::from(song.title.as_str()), Line::from(artist));
+    }
+
+    match mode {
+        FilterMode::Substring => (
+            highlight_chars(&song.title, &substring_indices(query, &song.title)),
+            highlight_chars(artist, &substring_indices(query, artist)),
+        ),
+        FilterMode::Fuzzy => {
+            // Songs are fuzzy matched by their full title, "Artist - Title"
+            let indices = fuzzy::matched_indices(query, &song.full_title()).unwrap_or_default();
+            let title_start = match song.artist {
+                Some(_) => artist.chars().count() + " - ".len(),
+                None => 0,
+            };
+            let title_indices: Vec<_> = indices
+                .iter()
+                .filter_map(|&i| i.checked_sub(title_start))
+                .collect();
+            let artist_indices: Vec<_> = indices.into_iter().filter(|&i| i < title_start).collect();
+            (
+                highlight_chars(&song.title, &title_indices),
+                highlight_chars(artist, &artist_indices),
+            )
+        }
+        FilterMode::Regex => (
+            highlight_chars(&song.title, &regex_indices(regex, &song.title)),
+            highlight_chars(artist, &regex_indices(regex, artist)),
+        ),
+    }
+}
+
+/// Returns the indices of the chars matched by `regex` in `text`
+fn regex_indices(regex: Option<&Regex>, text: &str) -> Vec<usize> {
+    let Some(regex) = regex else {
+        return Vec::new();
+    };
+    let ranges: Vec<_> = regex.find_iter(text).map(|m| m.range()).collect();
+    text.char_indices()
+        .enumerate()
+        .filter(|(_, (byte, _))| ranges.iter().any(|r| r.contains(byte)))
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Splits a filter query into the mode it should be matched with and the query itself. A query
+/// that starts with `/` (so typed as `//pattern`) is a regex, whatever the configured mode.
+fn query_mode(query: &str, configured: FilterMode) -> (FilterMode, &str) {
+    match query.strip_prefix('/') {
+        Some(pattern) => (FilterMode::Regex, pattern),
+        None => (configured, query),
+    }
+}
+
+/// The last regex filter pattern and what it compiled to, so that the pattern isn't recompiled on
+/// every refresh
+#[derive(Debug, Default)]
+struct CachedRegex(Option<(String, Option<Regex>)>);
+
+impl CachedRegex {
+    /// Compiles `pattern` case-insensitively, unless it's the same as last time. Returns `None`
+    /// if the pattern is invalid.
+    fn get(&mut self, pattern: &str) -> Option<&Regex> {
+        if !matches!(&self.0, Some((p, _)) if p == pattern) {
+            let regex = RegexBuilder::new(pattern)
+                .case_insensitive(true)
+                .build()
+                .ok();
+            self.0 = Some((pattern.to_string(), regex));
+        }
+        self.0.as_ref().and_then(|(_, regex)| regex.as_ref())
+    }
+
+    /// The regex compiled by the last call to [Self::get]
+    fn last(&self) -> Option<&Regex> {
+        self.0.as_ref().and_then(|(_, regex)| regex.as_ref())
+    }
+}
+
+/// Returns the indices of the chars of the first case-insensitive occurrence of `query` in
+/// `text`, or nothing if it doesn't occur
+fn substring_indices(query: &str, text: &str) -> Vec<usize> {
+    let lower = |s: &str| -> Vec<char> {
+        s.chars()
+            .map(|c| c.to_lowercase().next().unwrap_or(c))
+            .collect()
+    };
+    let (query, text) = (lower(query), lower(text));
+    if query.is_empty() {
+        return Vec::new();
+    }
+
+    text.windows(query.len())
+        .position(|w| w == query.as_slice())
+        .map(|start| (start..start + query.len()).collect())
+        .unwrap_or_default()
+}
+
+/// Splits `text` into spans, with the chars at `indices` (which must be sorted) highlighted
+fn highlight_chars<'s>(text: &'s str, indices: &[usize]) -> Line<'s> {
+    let highlight = Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
+    let mut spans = Vec::new();
+    let mut indices = indices.iter().peekable();
+    let mut span_start = 0;
+    let mut span_highlighted = false;
+
+    for (i, (byte, _)) in text.char_indices().enumerate() {
+        let highlighted = indices.next_if_eq(&&i).is_some();
+        if highlighted != span_highlighted {
+            if byte > span_start {
+                spans.push(styled_span(
+                    &text[span_start..byte],
+                    span_highlighted,
+                    highlight,
+                ));
+            }
+            span_start = byte;
+            span_highlighted = highlighted;
+        }
+    }
+    if span_start < text.len() {
+        spans.push(styled_span(
+            &text[span_start..],
+            span_highlighted,
+            highlight,
+        ));
+    }
+
+    Line::from(spans)
+}
+
+fn styled_span(text: &str, highlighted: bool, style: Style) -> Span<'_> {
+    if highlighted {
+        Span::styled(text, style)
+    } else {
+        Span::raw(text)
+    }
+}
+
+fn compare_songs_by(
     i: usize,
     j: usize,
     songs: &[m3u::Song],
@@ -61,6 +378,21 @@ fn compare_songs(
     }
 }
 
+/// Compares songs by `method`, breaking ties with each of `sort_keys` in order
+fn compare_songs(
+    i: usize,
+    j: usize,
+    songs: &[m3u::Song],
+    method: SortingMethod,
+    sort_keys: &[SortingMethod],
+) -> std::cmp::Ordering {
+    sort_keys
+        .iter()
+        .fold(compare_songs_by(i, j, songs, method), |ord, &key| {
+            ord.then_with(|| compare_songs_by(i, j, songs, key))
+        })
+}
+
 //////////////////////////////////////
 //        MousePressLocation        //
 //////////////////////////////////////
@@ -73,6 +405,25 @@ enum MousePressLocation {
 /////////////////////////////
 //        SongsPane        //
 /////////////////////////////
+/// What the songs pane remembers about a playlist while another one is shown
+#[derive(Debug, Clone)]
+struct SavedState {
+    /// Selection and scroll
+    table: TableState,
+    sorting_method: SortingMethod,
+    ascending: bool,
+}
+
+impl Default for SavedState {
+    fn default() -> Self {
+        Self {
+            table: TableState::default(),
+            sorting_method: SortingMethod::Index,
+            ascending: true,
+        }
+    }
+}
+
 /// Displays the list of songs of a given playlist
 #[derive(Debug, Default)]
 pub struct SongsPane<'t> {
@@ -82,14 +433,47 @@ pub struct SongsPane<'t> {
     shown: FilteredList<TableState>,
     sorting_method: SortingMethod,
     filter: String,
+    /// Whether `filter` is applied. Turning it off shows every song without losing the filter.
+    filter_active: bool,
+    /// Show the paths of the songs in the title column. Sorting and filtering still use titles.
+    show_paths: bool,
     last_click: Option<ClickInfo>,
     mouse_press_location: Option<MousePressLocation>,
+    /// Where the song being dragged was before the drag started. The songs are only reordered in
+    /// the pane until the mouse is released, then the playlist is saved once.
+    drag_start: Option<usize>,
+    /// State of the playlists that were shown before, by playlist name
+    saved_states: HashMap<String, SavedState>,
+    /// How many rows fit in the table, as of the last render
+    page_height: usize,
+    /// Indices of the songs marked for batch operations
+    marked: HashSet<usize>,
+    /// How many chars the title of the selected song is scrolled by, if it doesn't fit
+    marquee_offset: usize,
+    /// Row whose title is being scrolled
+    marquee_row: Option<usize>,
+    /// Path of the song mpv is playing, if any
+    playing_path: Option<String>,
+    /// Whether some songs come from nested playlists (see
+    /// [expand_nested_playlists](Config::expand_nested_playlists)), so that they don't match the
+    /// lines of the playlist file
+    has_nested: bool,
+    /// Nested playlists that weren't expanded, because they include themselves or are too deep
+    skipped_nested: Vec<String>,
+    /// Whether the songs are the recently played ones, which don't come from a playlist file
+    recent: bool,
+    regex: CachedRegex,
+    /// The playlist being loaded in the background, if any. The pane is empty until it's done.
+    loading: Option<Loading>,
+    /// Id of the last background load, to tell stale results apart
+    last_load_id: u64,
 }
 
 impl<'t> SongsPane<'t> {
     pub fn new() -> Self {
         Self {
             title: " songs ".into(),
+            filter_active: true,
             ..Default::default()
         }
     }
@@ -104,11 +488,18 @@ impl<'t> SongsPane<'t> {
     pub fn update_from_playlist_pane(
         &mut self,
         playlists: &super::playlists::PlaylistsPane,
+        recent_songs: &RecentSongs,
     ) -> Result<()> {
+        if playlists.recent_selected() {
+            self.show_recent(recent_songs.to_songs());
+            return Ok(());
+        }
         match playlists.selected_item() {
             Some(playlist) => self.update_from_playlist_named(playlist),
             None => {
+                let saved_states = mem::take(&mut self.saved_states);
                 *self = SongsPane::new();
+                self.saved_states = saved_states;
                 Ok(())
             }
         }
@@ -118,50 +509,272 @@ impl<'t> SongsPane<'t> {
         self.update_from_playlist(Config::playlist_path(name))
     }
 
+    /// Like [Self::update_from_playlist_pane], but reads the songs in the background with
+    /// [Self::load_playlist]
+    pub fn load_from_playlist_pane(
+        &mut self,
+        playlists: &super::playlists::PlaylistsPane,
+        recent_songs: &RecentSongs,
+        sender: Sender<Event>,
+    ) {
+        if playlists.recent_selected() {
+            return self.show_recent(recent_songs.to_songs());
+        }
+        match playlists.selected_item() {
+            Some(playlist) => self.load_playlist(Config::playlist_path(playlist), sender),
+            None => {
+                let saved_states = mem::take(&mut self.saved_states);
+                let last_load_id = self.last_load_id;
+                *self = SongsPane::new();
+                self.saved_states = saved_states;
+                self.last_load_id = last_load_id;
+            }
+        }
+    }
+
+    /// Reads the songs of the playlist at `path` in a background thread, which sends
+    /// [Event::PlaylistLoading] as it goes and [Event::PlaylistLoaded] when it's done, to be
+    /// passed to [Self::finish_loading]. Until then, the pane is empty. Loading another playlist
+    /// cancels this one.
+    pub fn load_playlist(&mut self, path: PathBuf, sender: Sender<Event>) {
+        let title = playlist_title(&path);
+        self.cancel_loading();
+        let state = self.switch_state(&title);
+
+        self.last_load_id += 1;
+        let id = self.last_load_id;
+        let cancelled = Arc::new(AtomicBool::new(false));
+        self.loading = Some(Loading {
+            id,
+            count: 0,
+            state,
+            cancelled: cancelled.clone(),
+        });
+        self.title = title;
+        self.show_songs(LoadedSongs::default(), TableState::default());
+
+        thread::spawn(move || {
+            let progress = |count| {
+                !cancelled.load(Ordering::Relaxed)
+                    && sender.send(Event::PlaylistLoading { id, count }).is_ok()
+            };
+            let result = match load_songs(&path, progress) {
+                Ok(Some(loaded)) => Ok(loaded),
+                Ok(None) => return,
+                Err(e) => Err(load_error_message(e)),
+            };
+            sender.send(Event::PlaylistLoaded { id, result }).ok();
+        });
+    }
+
+    /// Whether the background load with this id is still waited for
+    pub fn is_loading(&self, id: u64) -> bool {
+        matches!(&self.loading, Some(loading) if loading.id == id)
+    }
+
+    pub fn loading_progress(&mut self, id: u64, count: usize) {
+        if let Some(loading) = self.loading.as_mut().filter(|l| l.id == id) {
+            loading.count = count;
+        }
+    }
+
+    /// Stops waiting for the background load, if any, and puts back the selection it was going to
+    /// restore
+    fn cancel_loading(&mut self) {
+        if let Some(mut loading) = self.loading.take() {
+            self.set_state(mem::take(&mut loading.state));
+        }
+    }
+
+    /// Shows the songs read by the current background load
+    pub fn finish_loading(&mut self, loaded: LoadedSongs) {
+        if let Some(mut loading) = self.loading.take() {
+            let state = mem::take(&mut loading.state);
+            self.show_songs(loaded, state);
+        }
+    }
+
+    /// Follows a playlist being renamed, keeping the selection and any saved state
+    pub fn rename_playlist(&mut self, old_name: &str, new_name: &str) {
+        if self.title == old_name {
+            self.title = Cow::Owned(new_name.to_string());
+        }
+        if let Some(state) = self.saved_states.remove(old_name) {
+            self.saved_states.insert(new_name.to_string(), state);
+        }
+    }
+
+    /// Reads the songs of the playlist at `path` right away, cancelling any background load
     pub fn update_from_playlist(&mut self, path: impl AsRef<Path>) -> Result<()> {
-        let file = std::fs::File::open(&path)
-            .map_err(|_| format!("Couldn't open playlist file {}", path.as_ref().display()))?;
-
-        let title = Cow::Owned(
-            path.as_ref()
-                .file_stem()
-                .unwrap()
-                .to_string_lossy()
-                .to_string(),
-        );
+        let loaded = load_songs(path.as_ref(), |_| true)?.unwrap_or_default();
+        let title = playlist_title(path.as_ref());
+        self.cancel_loading();
+        let state = self.switch_state(&title);
 
-        let songs = m3u::Parser::from_reader(file).all_songs()?;
-        let state = self.state();
+        self.title = title;
+        self.show_songs(loaded, state);
+        Ok(())
+    }
 
-        // Update stuff
+    /// Shows the [recently played](Config::recent_songs) songs, most recent first
+    pub fn show_recent(&mut self, songs: Vec<m3u::Song>) {
+        self.cancel_loading();
+        let title: Cow<'static, str> = RECENT_PLAYLIST.into();
+        let state = self.switch_state(&title);
         self.title = title;
+        self.show_songs(
+            LoadedSongs {
+                songs,
+                ..Default::default()
+            },
+            state,
+        );
+        self.recent = true;
+    }
+
+    /// Replaces the recently played songs being shown with `songs`, which were just updated. The
+    /// filter is kept, and so are the selected and marked songs, wherever they moved.
+    pub fn refresh_recent(&mut self, songs: Vec<m3u::Song>) {
+        let selected = self.selected_item().map(|song| song.path.clone());
+        let marked: HashSet<String> = self
+            .marked
+            .iter()
+            .filter_map(|&i| self.songs.get(i))
+            .map(|song| song.path.clone())
+            .collect();
+
         self.songs = songs;
+        self.marked = (self.songs.iter().enumerate())
+            .filter(|(_, song)| marked.contains(&song.path))
+            .map(|(i, _)| i)
+            .collect();
+        self.refresh_shown();
+        if let Some(path) = selected {
+            self.select_path(&path);
+        }
+    }
+
+    /// Shows `loaded` in place of the current songs, then restores the selection in `state` if
+    /// it's still in range
+    fn show_songs(&mut self, loaded: LoadedSongs, state: TableState) {
+        self.songs = loaded.songs;
+        self.has_nested = loaded.has_nested;
+        self.skipped_nested = loaded.skipped_nested;
+        self.recent = false;
+        // The new songs replace the ones being dragged, which were never saved
+        self.drag_start = None;
+        self.marked.clear();
         self.filter.clear();
         self.refresh_shown();
+        self.restore_selection(state);
+    }
 
-        // Try to reuse previous state
-        if matches!(state.selected(), Some(i) if i < self.songs.len()) {
+    /// Restores the selection and scroll in `state` if it's still in range, or selects the first
+    /// shown song otherwise
+    fn restore_selection(&mut self, state: TableState) {
+        if matches!(state.selected(), Some(i) if i < self.shown.items.len()) {
             self.set_state(state);
         } else if self.shown.items.is_empty() {
             self.select_index(None);
         } else {
             self.select_index(Some(0));
         }
+    }
 
-        Ok(())
+    /// Reuses the current state when reloading the same playlist. Otherwise, saves the state of
+    /// the shown playlist, and restores the sort that `title` had when it was last shown. Returns
+    /// the selection and scroll to restore once its songs are shown.
+    fn switch_state(&mut self, title: &str) -> TableState {
+        if title == self.title {
+            return self.state();
+        }
+
+        let current = SavedState {
+            table: self.state(),
+            sorting_method: self.sorting_method,
+            ascending: self.shown.ascending,
+        };
+        self.saved_states.insert(self.title.to_string(), current);
+
+        let saved = self.saved_states.get(title).cloned().unwrap_or_default();
+        self.sorting_method = saved.sorting_method;
+        self.shown.ascending = saved.ascending;
+        saved.table
+    }
+
+    /// The filter text, without the leading `/`. Empty if the filter is turned off.
+    fn filter_query(&self) -> &str {
+        if !self.filter_active {
+            return "";
+        }
+        self.filter
+            .get(1..)
+            .unwrap_or_default()
+            .trim_end_matches('\n')
+    }
+
+    /// Whether the filter is a regex that doesn't compile, in which case it matches nothing
+    fn invalid_regex(&self) -> bool {
+        let (mode, _) = query_mode(self.filter_query(), Config::global().filter_mode);
+        mode == FilterMode::Regex && self.regex.last().is_none()
     }
 
     fn refresh_shown(&mut self) {
+        let (mode, query) = query_mode(self.filter_query(), Config::global().filter_mode);
+        if mode == FilterMode::Fuzzy && !query.is_empty() {
+            return self.refresh_shown_fuzzy(query.to_string());
+        }
+        if mode == FilterMode::Regex {
+            let query = query.to_string();
+            return self.refresh_shown_regex(&query);
+        }
+
+        let query = query.to_lowercase();
+        let pred = |s: &m3u::Song| {
+            query.is_empty()
+                || s.title.to_lowercase().contains(&query)
+                || s.artist
+                    .as_ref()
+                    .map_or(false, |a| a.to_lowercase().contains(&query))
+                || s.path.to_lowercase().contains(&query)
+        };
+        let sort_keys = &Config::global().sort_keys;
+        let comparison = |i, j| compare_songs(i, j, &self.songs, self.sorting_method, sort_keys);
+        self.shown.filter(&self.songs, pred, comparison);
+    }
+
+    /// Like [Self::refresh_shown], but fuzzy matches songs and sorts them by best match
+    fn refresh_shown_fuzzy(&mut self, query: String) {
+        let scores: Vec<_> = self
+            .songs
+            .iter()
+            .map(|s| {
+                let title_score = fuzzy::score(&query, &s.full_title());
+                let path_score = fuzzy::score(&query, &s.path);
+                title_score.max(path_score)
+            })
+            .collect();
+
+        let sort_keys = &Config::global().sort_keys;
+        self.shown.filter(&scores, Option::is_some, |i, j| {
+            scores[j]
+                .cmp(&scores[i])
+                .then_with(|| compare_songs(i, j, &self.songs, self.sorting_method, sort_keys))
+        });
+    }
+
+    /// Like [Self::refresh_shown], but matches the title, artist and path of songs against a regex
+    fn refresh_shown_regex(&mut self, pattern: &str) {
+        let regex = self.regex.get(pattern);
         let pred = |s: &m3u::Song| {
-            self.filter.is_empty()
-                || s.title
-                    .to_lowercase()
-                    .contains(&self.filter[1..].trim_end_matches('\n').to_lowercase())
-                || s.path
-                    .to_lowercase()
-                    .contains(&self.filter[1..].trim_end_matches('\n').to_lowercase())
+            regex.map_or(false, |re| {
+                re.is_match(&s.title)
+                    || s.artist.as_ref().map_or(false, |a| re.is_match(a))
+                    || re.is_match(&s.path)
+            })
         };
-        let comparison = |i, j| compare_songs(i, j, &self.songs, self.sorting_method);
+        let sort_keys = &Config::global().sort_keys;
+        let comparison = |i, j| compare_songs(i, j, &self.songs, self.sorting_method, sort_keys);
         self.shown.filter(&self.songs, pred, comparison);
     }
 
@@ -190,24 +803,16 @@ impl<'t> SongsPane<'t> {
                         self.filter.clear();
                         self.refresh_shown();
                     }
-                    // Go to the top, kind of like in vim
-                    Char('g') if self.mode() == Mode::Normal => {
-                        if !self.shown.items.is_empty() {
-                            self.shown.state.select(Some(0));
-                        }
-                    }
-                    // Go to the bottom, also like in vim
-                    Char('G') if self.mode() == Mode::Normal => {
-                        if !self.shown.items.is_empty() {
-                            self.shown.state.select(Some(self.shown.items.len() - 1));
-                        }
-                    }
                     Up => self.select_prev(),
                     Down => self.select_next(),
-                    Char('/') => self.filter = "/".into(),
+                    Char('/') => self.start_filter(),
                     _ => {}
                 }
             }
+            crossterm::event::Event::Paste(text) if self.mode() == Mode::Insert => {
+                self.filter.push_str(&util::single_line(&text));
+                self.refresh_shown();
+            }
             _ => {}
         }
         Ok(())
@@ -219,17 +824,30 @@ impl<'t> SongsPane<'t> {
         match cmd {
             SelectNext => self.select_next(),
             SelectPrev => self.select_prev(),
+            SelectFirst => self.shown.select_first(),
+            SelectLast => self.shown.select_last(),
+            SelectPageDown => self.select_page_down(),
+            SelectPageUp => self.select_page_up(),
+            ToggleMark => self.toggle_mark(Config::global().wrap_navigation),
+            PlaySelectedOnly => self.play_selected_only(app)?,
             QueueSong => {
                 if let Some(song) = self.selected_item() {
                     app.player.queue(&song.path)?;
                 }
             }
             QueueShown => {
-                for &i in self.shown.items.iter() {
-                    let path = self.songs[i].path.as_str();
+                let (paths, skipped) = self.shown_paths(0);
+                for path in paths {
                     app.player.queue(path)?;
                 }
+                notify_skipped(app, skipped);
+            }
+            PlayShown => {
+                let (paths, skipped) = self.shown_paths(0);
+                play_all(app, paths)?;
+                notify_skipped(app, skipped);
             }
+            PlayFromHere => self.play_selected(app)?,
             Shuffle => {
                 app.player.shuffle()?;
             }
@@ -239,9 +857,22 @@ impl<'t> SongsPane<'t> {
                     webbrowser::open(&song.path)?;
                 }
             }
+            RevealInFileManager => {
+                if let Some(song) = self.selected_item() {
+                    if song.path.contains("://") {
+                        webbrowser::open(&song.path)?;
+                    } else {
+                        let path = Path::new(&song.path);
+                        match path.parent() {
+                            Some(dir) if path.exists() => util::open_in_file_manager(dir)?,
+                            _ => app.notify_err(format!("{} doesn't exist", song.path)),
+                        }
+                    }
+                }
+            }
             CopyUrl => {
                 if let Some(song) = self.selected_item() {
-                    util::copy_to_clipboard(song.path.clone());
+                    util::copy_to_clipboard(song.path.clone())?;
                     #[cfg(feature = "clip")]
                     app.notify_info(format!("Copied {} to the clipboard", song.path));
                     #[cfg(not(feature = "clip"))]
@@ -250,7 +881,7 @@ impl<'t> SongsPane<'t> {
             }
             CopyTitle => {
                 if let Some(song) = self.selected_item() {
-                    util::copy_to_clipboard(song.title.clone());
+                    util::copy_to_clipboard(song.title.clone())?;
                     #[cfg(feature = "clip")]
                     app.notify_info(format!("Copied {} to the clipboard", song.title));
                     #[cfg(not(feature = "clip"))]
@@ -259,7 +890,7 @@ impl<'t> SongsPane<'t> {
             }
             SwapSongUp if self.filter.is_empty() => match self.selected_index() {
                 Some(i) if i >= 1 => {
-                    m3u::playlist_management::swap_song(&self.title, i - 1)?;
+                    app.push_undo(m3u::playlist_management::swap_song(&self.title, i - 1)?);
                     self.songs.swap(i - 1, i);
                     self.select_prev();
                 }
@@ -267,7 +898,7 @@ impl<'t> SongsPane<'t> {
             },
             SwapSongDown if self.filter.is_empty() => match self.selected_index() {
                 Some(i) if i + 1 < self.songs.len() => {
-                    m3u::playlist_management::swap_song(&self.title, i)?;
+                    app.push_undo(m3u::playlist_management::swap_song(&self.title, i)?);
                     self.songs.swap(i, i + 1);
                     self.select_next();
                 }
@@ -277,12 +908,29 @@ impl<'t> SongsPane<'t> {
                 self.next_sorting_method();
                 self.refresh_shown();
             }
-            Search => self.filter = "/".into(),
+            ReverseSort => {
+                self.shown.toggle_sort_direction();
+                self.refresh_shown();
+            }
+            Search => self.start_filter(),
+            ToggleFilter if !self.filter.is_empty() => {
+                self.filter_active = !self.filter_active;
+                self.refresh_shown();
+            }
+            ToggleShowPaths => {
+                self.show_paths = !self.show_paths;
+                self.marquee_offset = 0;
+            }
             _ => {}
         }
         Ok(())
     }
 
+    fn start_filter(&mut self) {
+        self.filter = "/".into();
+        self.filter_active = true;
+    }
+
     /// Handles a key event when the filter is active.
     pub fn handle_filter_key_event(&mut self, event: KeyEvent) -> Result<bool> {
         match event.code {
@@ -317,6 +965,7 @@ impl<'t> SongsPane<'t> {
         match kind {
             MouseEventKind::Up(MouseButton::Left) => {
                 self.mouse_press_location = None;
+                self.finish_drag(app)?;
             }
             // If the mouse press (MouseEventKind::Down event) was done on the scrollbar,
             // any drag events will still be handled by the scrollbar, even if the mouse
@@ -368,6 +1017,8 @@ impl<'t> SongsPane<'t> {
     ) -> Result<()> {
         if let MouseEventKind::Down(MouseButton::Left) = kind {
             self.mouse_press_location = Some(MousePressLocation::List);
+            // In case the last drag was released somewhere tori didn't see
+            self.finish_drag(app)?;
         }
 
         // Compute clicked item
@@ -388,6 +1039,16 @@ impl<'t> SongsPane<'t> {
             return Ok(());
         }
 
+        // Dragging a song around reorders it, as long as the rows are in the playlist's order
+        if matches!(kind, MouseEventKind::Drag(MouseButton::Left))
+            && self.mouse_press_location == Some(MousePressLocation::List)
+            && self.filter.is_empty()
+            && self.sorting_method == SortingMethod::Index
+        {
+            self.drag_selected_to(index);
+            return Ok(());
+        }
+
         // Select song
         self.select_index(Some(index));
 
@@ -398,7 +1059,63 @@ impl<'t> SongsPane<'t> {
         Ok(())
     }
 
+    /// Moves the selected song to the `to`-th row. The playlist is saved by [Self::finish_drag].
+    fn drag_selected_to(&mut self, to: usize) {
+        let (Some(from), Some(&to)) = (self.selected_index(), self.shown.items.get(to)) else {
+            return;
+        };
+        if from == to || self.has_nested || self.recent {
+            return;
+        }
+
+        self.drag_start.get_or_insert(from);
+        self.move_song(from, to);
+    }
+
+    /// Saves the order the songs were dragged into, as a single edit
+    fn finish_drag(&mut self, app: &mut App) -> Result<()> {
+        let (Some(from), Some(to)) = (self.drag_start.take(), self.selected_index()) else {
+            return Ok(());
+        };
+        if from == to {
+            return Ok(());
+        }
+
+        match m3u::playlist_management::move_song_to_index(&self.title, from, to) {
+            Ok(edit) => {
+                app.push_undo(edit);
+                Ok(())
+            }
+            Err(e) => {
+                // Show the songs as they still are in the file
+                self.move_song(to, from);
+                Err(e)
+            }
+        }
+    }
+
+    fn move_song(&mut self, from: usize, to: usize) {
+        let song = self.songs.remove(from);
+        self.songs.insert(to, song);
+        self.refresh_shown();
+        self.select_song(to);
+    }
+
+    /// Plays the selected song, then queues the songs shown after it, so playback continues
+    /// through the rest of the playlist. Local files that don't exist anymore are skipped.
     pub fn play_selected(&self, app: &mut App) -> Result<()> {
+        let Some(selected) = self.shown.state.selected() else {
+            return Ok(());
+        };
+
+        let (paths, skipped) = self.shown_paths(selected);
+        play_all(app, paths)?;
+        notify_skipped(app, skipped);
+        Ok(())
+    }
+
+    /// Plays only the selected song, replacing the queue
+    pub fn play_selected_only(&self, app: &mut App) -> Result<()> {
         if let Some(song) = self.selected_item() {
             app.player.play(&song.path)?;
         }
@@ -406,29 +1123,194 @@ impl<'t> SongsPane<'t> {
     }
 
     pub fn select_next(&mut self) {
-        self.shown.select_next();
+        self.shown.select_next(Config::global().wrap_navigation);
     }
 
     pub fn select_prev(&mut self) {
-        self.shown.select_prev();
+        self.shown.select_prev(Config::global().wrap_navigation);
+    }
+
+    /// Moves the selection down by the number of visible rows, stopping at the last song
+    pub fn select_page_down(&mut self) {
+        let last = match self.shown.items.len() {
+            0 => return,
+            n => n - 1,
+        };
+        let i = self.shown.state.selected().unwrap_or(0);
+        self.select_index(Some((i + self.page_height.max(1)).min(last)));
+    }
+
+    /// Moves the selection up by the number of visible rows, stopping at the first song
+    pub fn select_page_up(&mut self) {
+        if self.shown.items.is_empty() {
+            return;
+        }
+        let i = self.shown.state.selected().unwrap_or(0);
+        self.select_index(Some(i.saturating_sub(self.page_height.max(1))));
+    }
+
+    /// Scrolls the title of the selected song by one more char, if `marquee` is enabled
+    pub fn tick_marquee(&mut self) {
+        if Config::global().marquee {
+            self.marquee_offset += 1;
+        }
+    }
+
+    /// Shows the title that mpv got from a stream's metadata for the stream being played. The
+    /// playlist file isn't changed.
+    pub fn update_stream_title(&mut self, state: &PlayerState) {
+        if state.idle || !state.path.contains("://") {
+            return;
+        }
+        // mpv falls back to the URL when the stream has no title yet
+        let title = &state.media_title;
+        if title.is_empty() || *title == state.path {
+            return;
+        }
+
+        for song in self.songs.iter_mut().filter(|s| s.path == state.path) {
+            song.title.clone_from(title);
+        }
+    }
+
+    /// Remembers which song is playing, to mark it in the list
+    pub fn update_playing(&mut self, state: &PlayerState) {
+        self.playing_path = (!state.idle && !state.path.is_empty()).then(|| state.path.clone());
+    }
+
+    fn is_playing(&self, song: &m3u::Song) -> bool {
+        self.playing_path.as_deref() == Some(song.path.as_str())
+    }
+
+    /// Scrolls the table up if it would end with blank rows, like after the terminal grows. The
+    /// table itself keeps the selected song in view.
+    fn clamp_scroll(&mut self) {
+        let max_offset = self.shown.items.len().saturating_sub(self.page_height);
+        let offset = self.shown.state.offset_mut();
+        *offset = (*offset).min(max_offset);
+    }
+
+    /// See [FilteredList::select_nth]
+    pub fn select_nth(&mut self, n: usize) {
+        self.shown.select_nth(n);
     }
 
     pub fn select_index(&mut self, i: Option<usize>) {
         self.shown.state.select(i);
     }
 
+    /// Selects the song with the given index in the playlist, if it's shown
+    pub fn select_song(&mut self, index: usize) {
+        if let Some(position) = self.shown.items.iter().position(|&i| i == index) {
+            self.select_index(Some(position));
+        }
+    }
+
+    /// Selects the first shown song at `path`. Returns whether there's one.
+    pub fn select_path(&mut self, path: &str) -> bool {
+        let position = self
+            .shown
+            .items
+            .iter()
+            .position(|&i| self.songs[i].path == path);
+        if position.is_some() {
+            self.select_index(position);
+        }
+        position.is_some()
+    }
+
+    /// Paths of the shown songs from the `start`th row on, without the local files that don't
+    /// exist anymore. Also returns how many were left out.
+    fn shown_paths(&self, start: usize) -> (Vec<&str>, usize) {
+        let mut paths = Vec::new();
+        let mut missing = 0;
+        for &i in self.shown.items.iter().skip(start) {
+            let path = self.songs[i].path.as_str();
+            // URLs are left for mpv to check
+            if path.contains("://") || Path::new(path).exists() {
+                paths.push(path);
+            } else {
+                missing += 1;
+            }
+        }
+        (paths, missing)
+    }
+
     pub fn selected_item(&self) -> Option<&m3u::Song> {
         self.shown.selected_item().and_then(|i| self.songs.get(i))
     }
 
+    /// Index of the selected song in the playlist, which differs from the selected row when the
+    /// songs are filtered or sorted
     pub fn selected_index(&self) -> Option<usize> {
         self.shown.selected_item()
     }
+
+    /// Whether the songs don't match the lines of the playlist file, because nested playlists
+    /// were expanded. Songs can't be edited then.
+    pub fn has_nested(&self) -> bool {
+        self.has_nested
+    }
+
+    /// Whether the songs are the recently played ones, which can't be edited
+    pub fn is_recent(&self) -> bool {
+        self.recent
+    }
+
+    /// Nested playlists that were left out when the playlist was last loaded
+    pub fn skipped_nested(&self) -> &[String] {
+        &self.skipped_nested
+    }
+
+    /// The song at `index` in the playlist
+    pub fn song(&self, index: usize) -> Option<&m3u::Song> {
+        self.songs.get(index)
+    }
+
+    /// Marks the selected song, or unmarks it if it was marked, and selects the next one. After
+    /// the last song, that's the first one if `wrap` (see `wrap_navigation`).
+    fn toggle_mark(&mut self, wrap: bool) {
+        if let Some(i) = self.selected_index() {
+            if !self.marked.remove(&i) {
+                self.marked.insert(i);
+            }
+            self.shown.select_next(wrap);
+        }
+    }
+
+    /// Indices of the marked songs, in ascending order
+    pub fn marked_indices(&self) -> Vec<usize> {
+        let mut indices: Vec<_> = self.marked.iter().copied().collect();
+        indices.sort_unstable();
+        indices
+    }
 }
 
 impl<'t> Component for SongsPane<'t> {
     type RenderState = bool;
 
+    fn status_hint(&self) -> Option<String> {
+        let mut hints = Vec::new();
+        if !self.filter_active && !self.filter.is_empty() {
+            hints.push("filter off, showing every song".to_string());
+        } else {
+            hints.extend(super::filter_hint(&self.filter, self.filter_query()));
+        }
+        if !self.marked.is_empty() {
+            hints.push(format!("{} marked", self.marked.len()));
+        }
+        if self.has_nested {
+            hints.push("nested playlists expanded, can't be edited".to_string());
+        }
+        if self.recent {
+            hints.push("most recent first, can't be edited".to_string());
+        }
+        if self.show_paths {
+            hints.push("showing paths".to_string());
+        }
+        (!hints.is_empty()).then(|| hints.join(" · "))
+    }
+
     fn mode(&self) -> Mode {
         if self.filter.is_empty() || self.filter.as_bytes().last() == Some(&b'\n') {
             Mode::Normal
@@ -438,16 +1320,47 @@ impl<'t> Component for SongsPane<'t> {
     }
 
     fn render(&mut self, frame: &mut Frame<'_, MyBackend>, chunk: layout::Rect, is_focused: bool) {
-        let sorting = match self.sorting_method {
-            SortingMethod::Index => "",
-            SortingMethod::Title => " [↑ Title]",
-            SortingMethod::Duration => " [↑ Duration]",
+        let compact = Config::global().compact;
+        // Without borders, only the title takes a line
+        let page_height = chunk.height.saturating_sub(if compact { 1 } else { 2 }) as usize;
+        if page_height != self.page_height {
+            // The terminal was resized
+            self.page_height = page_height;
+            self.clamp_scroll();
+        }
+
+        let sorting = match (self.sorting_method, self.shown.ascending) {
+            (SortingMethod::Index, true) => "",
+            (SortingMethod::Index, false) => " [↓ Index]",
+            (SortingMethod::Title, true) => " [↑ Title]",
+            (SortingMethod::Title, false) => " [↓ Title]",
+            (SortingMethod::Duration, true) => " [↑ Duration]",
+            (SortingMethod::Duration, false) => " [↓ Duration]",
         };
 
-        let title = if !self.filter.is_empty() {
+        let title = if !self.filter.is_empty() && !self.filter_active {
+            format!(" {} (off){} ", self.filter.trim_end_matches('\n'), sorting)
+        } else if !self.filter.is_empty() {
             format!(" {}{} ", self.filter, sorting)
-        } else {
+        } else if self.loading.is_some() {
+            format!(" {} (loading…) ", self.title)
+        } else if self.songs.is_empty() {
             format!(" {}{} ", self.title, sorting)
+        } else if compact {
+            format!(" {} ({}){} ", self.title, self.songs.len(), sorting)
+        } else {
+            let total: Duration = self.songs.iter().map(|s| s.duration).sum();
+            let count = match self.songs.len() {
+                1 => "1 song".to_string(),
+                n => format!("{} songs", n),
+            };
+            format!(
+                " {} ({}, {}){} ",
+                self.title,
+                count,
+                format_total_duration(total),
+                sorting
+            )
         };
 
         let border_style = if is_focused {
@@ -456,59 +1369,153 @@ impl<'t> Component for SongsPane<'t> {
             Style::default()
         };
 
+        let title = if self.invalid_regex() {
+            Span::styled(title, Style::default().fg(Color::LightRed))
+        } else if compact {
+            // The title is what shows the focus then
+            Span::styled(title, border_style)
+        } else {
+            Span::raw(title)
+        };
+
         let block = Block::default()
             .title(title)
-            .borders(Borders::ALL)
+            .borders(if compact { Borders::NONE } else { Borders::ALL })
             .border_type(BorderType::Plain)
             .border_style(border_style);
 
         if !self.songs.is_empty() {
             // Render songlist
+            let columns = &Config::global().song_columns;
+            let widths = column_widths(columns, chunk.width, compact);
+            let width_of = |column| {
+                columns
+                    .iter()
+                    .position(|c| *c == column)
+                    .map_or(0, |i| match widths[i] {
+                        Constraint::Length(w) => w as usize,
+                        _ => 0,
+                    })
+            };
+            let (title_width, artist_width, path_width) = (
+                width_of(SongColumn::Title),
+                width_of(SongColumn::Artist),
+                width_of(SongColumn::Path),
+            );
+
+            let selected = self.shown.state.selected();
+            if selected != self.marquee_row {
+                self.marquee_row = selected;
+                self.marquee_offset = 0;
+            }
+            let marquee_offset = self.marquee_offset;
+            let mut marquee_done = false;
+
+            let (filter_mode, query) =
+                query_mode(self.filter_query(), Config::global().filter_mode);
+            let regex = self.regex.last();
             let songlist: Vec<_> = self
                 .shown
                 .items
                 .iter()
-                .map(|&i| &self.songs[i])
-                .map(|song| {
-                    Row::new(vec![
-                        format!(" {}", song.title),
-                        format!(
+                .enumerate()
+                .map(|(row_index, &i)| {
+                    let song = &self.songs[i];
+                    let (title, artist) = highlight_matches(song, query, filter_mode, regex);
+                    let playing = self.is_playing(song);
+                    let mut title = if self.show_paths {
+                        vec![Span::raw(song.path.as_str())]
+                    } else {
+                        title.spans
+                    };
+                    title.insert(0, Span::raw(if playing { "▶" } else { " " }));
+                    let title = Line::from(title);
+
+                    let mut title_offset = 0;
+                    if Some(row_index) == selected {
+                        // Stay at the end for a couple of ticks before going back to the start
+                        let overflow = title.width().saturating_sub(title_width);
+                        title_offset = marquee_offset.min(overflow);
+                        marquee_done = marquee_offset > overflow + 2;
+                    }
+
+                    let cells = columns.iter().map(|column| match column {
+                        SongColumn::Index => Cell::from(format!("{:>4}", i + 1)),
+                        SongColumn::Title => {
+                            Cell::from(fit_line(title.clone(), title_width, title_offset))
+                        }
+                        SongColumn::Artist => Cell::from(fit_line(artist.clone(), artist_width, 0)),
+                        SongColumn::Duration => Cell::from(format!(
                             "{}:{:02}",
                             song.duration.as_secs() / 60,
                             song.duration.as_secs() % 60
-                        ),
-                    ])
+                        )),
+                        SongColumn::Path => {
+                            Cell::from(fit_line(Line::from(song.path.as_str()), path_width, 0))
+                        }
+                    });
+                    let row = Row::new(cells.collect::<Vec<_>>());
+                    if self.marked.contains(&i) {
+                        row.style(
+                            Style::default()
+                                .fg(Color::LightCyan)
+                                .add_modifier(Modifier::BOLD),
+                        )
+                    } else if playing {
+                        row.style(
+                            Style::default()
+                                .fg(Color::LightGreen)
+                                .add_modifier(Modifier::BOLD),
+                        )
+                    } else {
+                        row
+                    }
                 })
                 .collect();
             let songlist_len = songlist.len();
+            if marquee_done {
+                self.marquee_offset = 0;
+            }
 
             // Render table
-            let widths = &[Constraint::Length(chunk.width - 11), Constraint::Length(10)];
             let widget = Table::new(songlist)
                 .block(block)
-                .widths(widths)
+                .widths(&widths)
                 .highlight_style(Style::default().bg(Color::Yellow).fg(Color::Black))
                 .highlight_symbol(" ◇");
             frame.render_stateful_widget(widget, chunk, &mut self.shown.state);
 
-            if self.shown.items.len() > chunk.height as usize - 2 {
+            if self.shown.items.len() > page_height {
                 // Render scrollbar
-                let scrollbar = Scrollbar::new(
+                let scrollbar = Scrollbar::themed(
                     self.shown.state.selected().unwrap_or(0) as u16,
                     songlist_len as u16,
-                )
-                .with_style(border_style);
-                frame.render_widget(
-                    scrollbar,
+                    border_style,
+                );
+                // Without borders, there's no bottom line to skip
+                let area = if compact {
+                    Rect {
+                        y: chunk.y + 1,
+                        height: chunk.height.saturating_sub(1),
+                        ..chunk
+                    }
+                } else {
                     chunk.inner(&tui::layout::Margin {
                         vertical: 1,
                         horizontal: 0,
-                    }),
-                );
+                    })
+                };
+                frame.render_widget(scrollbar, area);
             }
+        } else if let Some(loading) = &self.loading {
+            let widget = Paragraph::new(format!("loading… ({} songs)", loading.count))
+                .block(block)
+                .style(Style::default().fg(Color::DarkGray));
+            frame.render_widget(widget, chunk);
         } else {
             // Help message
-            let key = Config::global()
+            let config = Config::global();
+            let key = config
                 .keybindings
                 .0
                 .iter()
@@ -559,7 +1566,9 @@ impl<'a> MouseHandler for SongsPane<'a> {
         match event.kind {
             MouseEventKind::ScrollUp => self.select_prev(),
             MouseEventKind::ScrollDown => self.select_next(),
-            MouseEventKind::Down(MouseButton::Left) | MouseEventKind::Drag(MouseButton::Left) => {
+            MouseEventKind::Down(MouseButton::Left)
+            | MouseEventKind::Drag(MouseButton::Left)
+            | MouseEventKind::Up(MouseButton::Left) => {
                 self.click(app, chunk, (event.column, event.row), event.kind)?
             }
             _ => {}
@@ -567,3 +1576,466 @@ impl<'a> MouseHandler for SongsPane<'a> {
         Ok(())
     }
 }
+
+/// Formats a duration as `h:mm:ss`, or `m:ss` if it's shorter than an hour
+fn format_total_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let (hours, minutes, seconds) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{}:{:02}", minutes, seconds)
+    }
+}
+
+/// Replaces the queue with `paths`: playing the first one replaces it, then the others are
+/// appended to it
+fn play_all(app: &mut App, paths: Vec<&str>) -> Result<()> {
+    for (n, path) in paths.into_iter().enumerate() {
+        if n == 0 {
+            app.player.play(path)?;
+        } else {
+            app.player.queue(path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Tells the user that some songs weren't played because their files are missing
+fn notify_skipped(app: &mut App, skipped: usize) {
+    match skipped {
+        0 => {}
+        1 => app.notify_info("1 file skipped (missing)"),
+        n => app.notify_info(format!("{} files skipped (missing)", n)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn song(path: &str, title: &str) -> m3u::Song {
+        m3u::Song {
+            title: title.into(),
+            path: path.into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_update_from_nonexistent_playlist() {
+        let mut songs = SongsPane::default();
+        assert!(songs
+            .update_from_playlist("/this/playlist/does/not/exist.m3u8")
+            .is_err());
+    }
+
+    #[test]
+    fn test_load_songs_cancelled() {
+        let path = std::env::temp_dir().join(format!("tori-load-{}.m3u", std::process::id()));
+        let content: String = (0..1200).map(|i| format!("{}.mp3\n", i)).collect();
+        std::fs::write(&path, content).unwrap();
+
+        let mut counts = Vec::new();
+        let loaded = load_songs(&path, |count| {
+            counts.push(count);
+            count < 1000
+        });
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(matches!(loaded, Ok(None)));
+        assert_eq!(counts, vec![500, 1000]);
+    }
+
+    #[test]
+    fn test_loading_state() {
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let mut songs = SongsPane {
+            loading: Some(Loading {
+                id: 2,
+                count: 0,
+                state: TableState::default(),
+                cancelled: cancelled.clone(),
+            }),
+            ..Default::default()
+        };
+        assert!(songs.is_loading(2));
+        assert!(!songs.is_loading(1));
+
+        songs.loading_progress(1, 500);
+        assert_eq!(songs.loading.as_ref().unwrap().count, 0);
+        songs.loading_progress(2, 500);
+        assert_eq!(songs.loading.as_ref().unwrap().count, 500);
+
+        // Dropping the load stops its thread
+        assert!(!cancelled.load(Ordering::Relaxed));
+        songs.cancel_loading();
+        assert!(!songs.is_loading(2));
+        assert!(cancelled.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn test_rename_playlist() {
+        let mut songs = SongsPane {
+            title: "old".into(),
+            ..Default::default()
+        };
+        songs.rename_playlist("other", "new");
+        assert_eq!(songs.title, "old");
+        songs.rename_playlist("old", "new");
+        assert_eq!(songs.title, "new");
+    }
+
+    #[test]
+    fn test_update_stream_title() {
+        let mut songs = SongsPane {
+            songs: vec![song("https://example.com/radio", "radio")],
+            ..Default::default()
+        };
+        let mut state = PlayerState {
+            path: "https://example.com/radio".into(),
+            media_title: "https://example.com/radio".into(),
+            ..Default::default()
+        };
+
+        songs.update_stream_title(&state);
+        assert_eq!(songs.songs[0].title, "radio");
+
+        state.media_title = "Artist - Live".into();
+        songs.update_stream_title(&state);
+        assert_eq!(songs.songs[0].title, "Artist - Live");
+    }
+
+    #[test]
+    fn test_update_playing() {
+        let song = song("/music/a.mp3", "");
+        let mut songs = SongsPane::new();
+        let mut state = PlayerState {
+            path: "/music/a.mp3".into(),
+            ..Default::default()
+        };
+
+        songs.update_playing(&state);
+        assert!(songs.is_playing(&song));
+
+        state.idle = true;
+        songs.update_playing(&state);
+        assert!(!songs.is_playing(&song));
+    }
+
+    #[test]
+    fn test_shown_paths() {
+        let existing = std::env::temp_dir();
+        let mut songs = SongsPane {
+            songs: vec![
+                song("/this/song/does/not/exist.mp3", ""),
+                song("https://example.com/radio", ""),
+                song(existing.to_str().unwrap(), ""),
+            ],
+            ..Default::default()
+        };
+        songs.shown.items = vec![0, 1, 2];
+
+        let (paths, skipped) = songs.shown_paths(0);
+        assert_eq!(
+            paths,
+            vec!["https://example.com/radio", existing.to_str().unwrap()]
+        );
+        assert_eq!(skipped, 1);
+
+        let (paths, skipped) = songs.shown_paths(2);
+        assert_eq!(paths, vec![existing.to_str().unwrap()]);
+        assert_eq!(skipped, 0);
+    }
+
+    #[test]
+    fn test_switch_state_restores_sort() {
+        let mut songs = SongsPane {
+            title: "a".into(),
+            sorting_method: SortingMethod::Title,
+            ..Default::default()
+        };
+        songs.shown.ascending = false;
+
+        // A playlist that wasn't shown before is sorted by index
+        songs.switch_state("b");
+        songs.title = "b".into();
+        assert_eq!(songs.sorting_method, SortingMethod::Index);
+        assert!(songs.shown.ascending);
+
+        songs.switch_state("a");
+        assert_eq!(songs.sorting_method, SortingMethod::Title);
+        assert!(!songs.shown.ascending);
+    }
+
+    #[test]
+    fn test_format_total_duration() {
+        assert_eq!(format_total_duration(Duration::from_secs(0)), "0:00");
+        assert_eq!(format_total_duration(Duration::from_secs(427)), "7:07");
+        assert_eq!(format_total_duration(Duration::from_secs(10267)), "2:51:07");
+    }
+
+    #[test]
+    fn test_compare_songs_tiebreak() {
+        use std::cmp::Ordering;
+        let songs = [song("", "b"), song("", "a")].map(|s| m3u::Song {
+            duration: Duration::from_secs(10),
+            ..s
+        });
+
+        let by_duration =
+            |keys: &[SortingMethod]| compare_songs(0, 1, &songs, SortingMethod::Duration, keys);
+        assert_eq!(by_duration(&[SortingMethod::Index]), Ordering::Less);
+        assert_eq!(
+            by_duration(&[SortingMethod::Title, SortingMethod::Index]),
+            Ordering::Greater
+        );
+    }
+
+    #[test]
+    fn test_column_widths() {
+        use SongColumn::*;
+        let lengths = |columns: &[SongColumn], width| -> Vec<_> {
+            column_widths(columns, width, false)
+                .into_iter()
+                .map(|c| match c {
+                    Constraint::Length(l) => l,
+                    _ => unreachable!(),
+                })
+                .collect()
+        };
+
+        assert_eq!(lengths(&[Title, Artist, Duration], 106), vec![60, 30, 10]);
+        assert_eq!(lengths(&[Title, Artist, Duration], 107), vec![61, 30, 10]);
+        assert_eq!(lengths(&[Index, Path, Duration], 61), vec![5, 40, 10]);
+        assert_eq!(lengths(&[Duration], 5), vec![10]);
+
+        let compact: Vec<_> = column_widths(&[Title, Artist, Duration], 100, true)
+            .into_iter()
+            .map(|c| match c {
+                Constraint::Length(l) => l,
+                _ => unreachable!(),
+            })
+            .collect();
+        // The last cell is left for the scrollbar
+        assert_eq!(compact, vec![60, 29, 6]);
+    }
+
+    #[test]
+    fn test_fit_line() {
+        let text =
+            |line: Line| -> String { line.spans.iter().map(|s| s.content.as_ref()).collect() };
+        assert_eq!(text(fit_line(Line::from("short"), 10, 0)), "short");
+        assert_eq!(text(fit_line(Line::from("a long title"), 8, 0)), "a long …");
+        assert_eq!(text(fit_line(Line::from("a long title"), 8, 2)), "long ti…");
+        assert_eq!(text(fit_line(Line::from("a long title"), 8, 4)), "ng title");
+        assert_eq!(text(fit_line(Line::from("おはよう"), 5, 0)), "おは…");
+
+        let highlighted = Line::from(vec![
+            Span::raw("ab"),
+            Span::styled("cd", Style::default().add_modifier(Modifier::BOLD)),
+        ]);
+        let contents: Vec<_> = fit_line(highlighted, 3, 1)
+            .spans
+            .into_iter()
+            .map(|s| s.content)
+            .collect();
+        assert_eq!(contents, vec!["b", "cd"]);
+    }
+
+    #[test]
+    fn test_highlight_matches() {
+        let contents = |line: &Line| -> Vec<String> {
+            line.spans.iter().map(|s| s.content.to_string()).collect()
+        };
+        let song = m3u::Song {
+            artist: Some("Queen".into()),
+            ..song("", "Bohemian Rhapsody")
+        };
+
+        let (title, artist) = highlight_matches(&song, "RHAP", FilterMode::Substring, None);
+        assert_eq!(contents(&title), vec!["Bohemian ", "Rhap", "sody"]);
+        assert_eq!(contents(&artist), vec!["Queen"]);
+
+        let (title, artist) = highlight_matches(&song, "qbr", FilterMode::Fuzzy, None);
+        assert_eq!(contents(&title), vec!["B", "ohemian ", "R", "hapsody"]);
+        assert_eq!(contents(&artist), vec!["Q", "ueen"]);
+    }
+
+    #[test]
+    fn test_filter_query() {
+        let mut songs = SongsPane::new();
+        songs.filter = "/queen\n".into();
+        assert_eq!(songs.filter_query(), "queen");
+
+        // Turning the filter off keeps it around for later
+        songs.filter_active = false;
+        assert_eq!(songs.filter_query(), "");
+        assert_eq!(songs.filter, "/queen\n");
+    }
+
+    #[test]
+    fn test_status_hint() {
+        let mut songs = SongsPane::new();
+        assert_eq!(songs.status_hint(), None);
+
+        songs.filter = "/queen".into();
+        songs.marked.insert(3);
+        assert_eq!(
+            songs.status_hint().unwrap(),
+            "filtering: queen (enter keeps the filter, esc clears it) · 1 marked"
+        );
+
+        songs.filter.push('\n');
+        songs.marked.clear();
+        assert_eq!(
+            songs.status_hint().unwrap(),
+            "filtered by 'queen', esc clears"
+        );
+
+        songs.filter.clear();
+        songs.show_paths = true;
+        assert_eq!(songs.status_hint().unwrap(), "showing paths");
+    }
+
+    #[test]
+    fn test_query_mode() {
+        assert_eq!(
+            query_mode("queen", FilterMode::Fuzzy),
+            (FilterMode::Fuzzy, "queen")
+        );
+        assert_eq!(
+            query_mode("/^que+n$", FilterMode::Substring),
+            (FilterMode::Regex, "^que+n$")
+        );
+    }
+
+    #[test]
+    fn test_cached_regex() {
+        let mut cache = CachedRegex::default();
+        assert!(cache
+            .get("^boh.*dy$")
+            .unwrap()
+            .is_match("Bohemian Rhapsody"));
+        assert!(cache.last().is_some());
+
+        // Invalid patterns, like a half-typed group, don't compile
+        assert!(cache.get("(rhap").is_none());
+        assert!(cache.last().is_none());
+
+        let song = m3u::Song {
+            artist: Some("Queen".into()),
+            ..song("", "Bohemian Rhapsody")
+        };
+        let regex = cache.get("rhap|ee");
+        let (title, artist) = highlight_matches(&song, "rhap|ee", FilterMode::Regex, regex);
+        let contents = |line: &Line| -> Vec<String> {
+            line.spans.iter().map(|s| s.content.to_string()).collect()
+        };
+        assert_eq!(contents(&title), vec!["Bohemian ", "Rhap", "sody"]);
+        assert_eq!(contents(&artist), vec!["Qu", "ee", "n"]);
+    }
+
+    #[test]
+    fn test_selected_index_when_filtered() {
+        let mut songs = SongsPane {
+            songs: vec![song("", "a"), song("", "b"), song("", "ab")],
+            ..Default::default()
+        };
+        songs
+            .shown
+            .filter(&songs.songs, |s| s.title.contains('b'), |i, j| i.cmp(&j));
+        songs.select_index(Some(1));
+
+        // The second shown row is the third song, which is the one that should be deleted
+        assert_eq!(songs.selected_index(), Some(2));
+        assert_eq!(songs.song(2).unwrap().title, "ab");
+    }
+
+    #[test]
+    fn test_delete_when_filtered() {
+        let content = "#EXTM3U\n#EXTINF:1,a\na.mp3\n#EXTINF:2,b\nb.mp3\n#EXTINF:3,ab\nab.mp3\n";
+        let mut songs = SongsPane {
+            songs: m3u::Parser::from_string(content).all_songs().unwrap(),
+            ..Default::default()
+        };
+        songs
+            .shown
+            .filter(&songs.songs, |s| s.title.contains('b'), |i, j| i.cmp(&j));
+        songs.select_index(Some(1));
+
+        let index = songs.selected_index().unwrap();
+        let (content, deleted) = m3u::playlist_management::delete_song_within(content, index)
+            .unwrap()
+            .unwrap();
+        assert_eq!(deleted, "#EXTINF:3,ab\nab.mp3\n");
+        assert_eq!(content, "#EXTM3U\n#EXTINF:1,a\na.mp3\n#EXTINF:2,b\nb.mp3\n");
+
+        // Reloading clears the filter, and the selection has to point at a song that's left
+        let state = songs.state();
+        songs.songs = m3u::Parser::from_string(&content).all_songs().unwrap();
+        songs.shown.filter(&songs.songs, |_| true, |i, j| i.cmp(&j));
+        songs.restore_selection(state);
+        let selected = songs.selected_index().unwrap();
+        assert_eq!(songs.song(selected).unwrap().title, "b");
+    }
+
+    #[test]
+    fn test_toggle_mark() {
+        let mut songs = SongsPane {
+            songs: vec![m3u::Song::default(); 3],
+            ..Default::default()
+        };
+        songs.shown.filter(&songs.songs, |_| true, |i, j| i.cmp(&j));
+        songs.select_index(Some(2));
+        songs.toggle_mark(true);
+        // Wrapped around to the first song
+        assert_eq!(songs.shown.state.selected(), Some(0));
+        songs.toggle_mark(true);
+        assert_eq!(songs.marked_indices(), vec![0, 2]);
+
+        songs.select_index(Some(2));
+        songs.toggle_mark(false);
+        assert_eq!(songs.marked_indices(), vec![0]);
+        assert_eq!(songs.shown.state.selected(), Some(2));
+    }
+
+    #[test]
+    fn test_clamp_scroll() {
+        let mut songs = SongsPane {
+            songs: vec![m3u::Song::default(); 10],
+            page_height: 4,
+            ..Default::default()
+        };
+        songs.shown.filter(&songs.songs, |_| true, |i, j| i.cmp(&j));
+        *songs.shown.state.offset_mut() = 6;
+
+        songs.clamp_scroll();
+        assert_eq!(songs.shown.state.offset(), 6);
+
+        songs.page_height = 8;
+        songs.clamp_scroll();
+        assert_eq!(songs.shown.state.offset(), 2);
+    }
+
+    #[test]
+    fn test_select_page() {
+        let mut songs = SongsPane {
+            songs: vec![m3u::Song::default(); 10],
+            page_height: 4,
+            ..Default::default()
+        };
+        songs.shown.filter(&songs.songs, |_| true, |i, j| i.cmp(&j));
+
+        songs.select_page_down();
+        assert_eq!(songs.shown.state.selected(), Some(4));
+        songs.select_page_down();
+        songs.select_page_down();
+        assert_eq!(songs.shown.state.selected(), Some(9));
+        songs.select_page_up();
+        assert_eq!(songs.shown.state.selected(), Some(5));
+        songs.select_page_up();
+        songs.select_page_up();
+        assert_eq!(songs.shown.state.selected(), Some(0));
+    }
+}
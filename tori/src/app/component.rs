@@ -24,6 +24,12 @@ pub trait Component {
         render_state: Self::RenderState,
     );
     fn handle_event(&mut self, app: &mut App, event: events::Event) -> Result<()>;
+
+    /// A short hint about the component's state, like an active filter, shown under it while it's
+    /// focused
+    fn status_hint(&self) -> Option<String> {
+        None
+    }
 }
 
 pub trait MouseHandler {
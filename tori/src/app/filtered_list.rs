@@ -31,14 +31,28 @@ impl Selectable for TableState {
 /////////////////////////////////
 //        Filtered List        //
 /////////////////////////////////
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct FilteredList<St: Selectable> {
     /// List of indices of the original list
     pub items: Vec<usize>,
     pub state: St,
+    /// Whether items are sorted in the order given by the sorting function, or in reverse
+    pub ascending: bool,
+}
+
+impl<St: Selectable + Default> Default for FilteredList<St> {
+    fn default() -> Self {
+        Self {
+            items: Vec::new(),
+            state: St::default(),
+            ascending: true,
+        }
+    }
 }
 
 impl<St: Selectable> FilteredList<St> {
+    /// Keeps the items matching `pred`, sorted by `sorting`. The sort is stable: items that
+    /// compare equal stay in their original order, whatever the sort direction.
     pub fn filter<T, P, S>(&mut self, items: &[T], pred: P, sorting: S)
     where
         P: Fn(&T) -> bool,
@@ -53,7 +67,11 @@ impl<St: Selectable> FilteredList<St> {
             })
             .collect();
 
-        self.items.sort_by(|&i, &j| sorting(i, j));
+        if self.ascending {
+            self.items.sort_by(|&i, &j| sorting(i, j));
+        } else {
+            self.items.sort_by(|&i, &j| sorting(i, j).reverse());
+        }
 
         let new_selection = self
             .items
@@ -66,22 +84,55 @@ impl<St: Selectable> FilteredList<St> {
         self.state.select(new_selection);
     }
 
-    pub fn select_next(&mut self) {
+    /// Flips between ascending and descending order. Takes effect on the next [Self::filter].
+    pub fn toggle_sort_direction(&mut self) {
+        self.ascending = !self.ascending;
+    }
+
+    /// Selects the next item. At the last item, goes back to the first one if `wrap`, or stays
+    /// there otherwise (see the `wrap_navigation` config option).
+    pub fn select_next(&mut self, wrap: bool) {
+        let len = self.items.len();
         self.state.select(match self.state.selected() {
-            Some(x) => Some(wrap_inc(x, self.items.len())),
+            Some(x) if wrap => Some(wrap_inc(x, len)),
+            Some(x) => Some((x + 1).min(len.saturating_sub(1))),
             None if !self.items.is_empty() => Some(0),
             None => None,
         });
     }
 
-    pub fn select_prev(&mut self) {
+    /// Selects the previous item. At the first item, goes to the last one if `wrap`, or stays
+    /// there otherwise.
+    pub fn select_prev(&mut self, wrap: bool) {
         self.state.select(match self.state.selected() {
-            Some(x) => Some(wrap_dec(x, self.items.len())),
+            Some(x) if wrap => Some(wrap_dec(x, self.items.len())),
+            Some(x) => Some(x.saturating_sub(1)),
             None if !self.items.is_empty() => Some(0),
             None => None,
         });
     }
 
+    /// Selects the first shown item, if there is one
+    pub fn select_first(&mut self) {
+        if !self.items.is_empty() {
+            self.state.select(Some(0));
+        }
+    }
+
+    /// Selects the last shown item, if there is one
+    pub fn select_last(&mut self) {
+        if !self.items.is_empty() {
+            self.state.select(Some(self.items.len() - 1));
+        }
+    }
+
+    /// Selects the `n`-th shown item, counting from 0, or the last one if there are fewer
+    pub fn select_nth(&mut self, n: usize) {
+        if !self.items.is_empty() {
+            self.state.select(Some(n.min(self.items.len() - 1)));
+        }
+    }
+
     pub fn selected_item(&self) -> Option<usize> {
         self.state.selected().map(|i| self.items[i])
     }
@@ -102,3 +153,75 @@ fn wrap_dec(x: usize, modulo: usize) -> usize {
         x - 1
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sort_direction() {
+        let items = ["c", "a", "b", "d"];
+        let mut list = FilteredList::<ListState>::default();
+
+        list.filter(&items, |&s| s != "d", |i, j| items[i].cmp(items[j]));
+        assert_eq!(list.items, vec![1, 2, 0]);
+
+        list.toggle_sort_direction();
+        list.filter(&items, |&s| s != "d", |i, j| items[i].cmp(items[j]));
+        assert_eq!(list.items, vec![0, 2, 1]);
+    }
+
+    #[test]
+    fn test_sort_is_stable() {
+        let items = [2, 1, 2, 1, 2];
+        let mut list = FilteredList::<ListState>::default();
+
+        list.filter(&items, |_| true, |i, j| items[i].cmp(&items[j]));
+        assert_eq!(list.items, vec![1, 3, 0, 2, 4]);
+
+        list.toggle_sort_direction();
+        list.filter(&items, |_| true, |i, j| items[i].cmp(&items[j]));
+        assert_eq!(list.items, vec![0, 2, 4, 1, 3]);
+    }
+
+    #[test]
+    fn test_select_first_last() {
+        let items = ["a", "b", "c"];
+        let mut list = FilteredList::<ListState>::default();
+
+        list.filter(&items, |&s| s != "c", |i, j| i.cmp(&j));
+        list.select_last();
+        assert_eq!(list.selected_item(), Some(1));
+        list.select_first();
+        assert_eq!(list.selected_item(), Some(0));
+
+        list.select_nth(1);
+        assert_eq!(list.selected_item(), Some(1));
+        list.select_nth(5);
+        assert_eq!(list.selected_item(), Some(1));
+
+        list.filter(&items, |_| false, |i, j| i.cmp(&j));
+        list.select_last();
+        assert_eq!(list.state.selected(), None);
+    }
+
+    #[test]
+    fn test_select_next_prev() {
+        let items = ["a", "b", "c"];
+        let mut list = FilteredList::<ListState>::default();
+        list.filter(&items, |_| true, |i, j| i.cmp(&j));
+
+        // Clamping
+        list.select_prev(false);
+        assert_eq!(list.selected_item(), Some(0));
+        list.select_last();
+        list.select_next(false);
+        assert_eq!(list.selected_item(), Some(2));
+
+        // Wrapping
+        list.select_next(true);
+        assert_eq!(list.selected_item(), Some(0));
+        list.select_prev(true);
+        assert_eq!(list.selected_item(), Some(2));
+    }
+}
@@ -1,14 +1,21 @@
 use crossterm::{
     event::{
-        DisableMouseCapture, EnableMouseCapture, Event as CrosstermEvent, KeyEvent, KeyEventKind,
-        KeyModifiers,
+        DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+        Event as CrosstermEvent, KeyEvent, KeyEventKind, KeyModifiers,
     },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use std::{borrow::Cow, cell::RefCell, rc::Rc, sync::mpsc};
+use std::{
+    borrow::Cow,
+    cell::RefCell,
+    collections::{HashMap, VecDeque},
+    rc::Rc,
+    sync::mpsc,
+};
 use std::{
     io,
+    path::{Path, PathBuf},
     time::{self, Duration},
 };
 use tui::{backend::CrosstermBackend, layout::Rect, style::Color, Terminal};
@@ -16,12 +23,22 @@ use tui::{backend::CrosstermBackend, layout::Rect, style::Color, Terminal};
 use crate::{
     app::component::Mode,
     command,
-    config::Config,
+    config::{
+        shortcuts::{InputStr, Lookup},
+        Config, GAPLESS_AUDIO_VALUES,
+    },
     error::Result,
     events::{self, Channel},
-    player::{DefaultPlayer, Player},
+    m3u::playlist_management::{self, Edit},
+    player::{DefaultPlayer, Player, PlayerState},
+    recent::RecentSongs,
+    resume::ResumePoint,
+    session::Session,
     visualizer::{self, Visualizer},
-    widgets::notification::Notification,
+    widgets::{
+        notification::{LogEntry, Notification, NotificationKind},
+        Osd,
+    },
 };
 
 pub mod app_screen;
@@ -41,16 +58,51 @@ use self::{
 const FRAME_DELAY_MS: u16 = 16;
 const HIGH_EVENT_TIMEOUT: u16 = 1000;
 const LOW_EVENT_TIMEOUT: u16 = 17;
+/// How long to wait for the next key of a chord, like `g g`
+const CHORD_TIMEOUT_MS: u64 = 600;
+/// How many notifications [ShowLog](command::Command::ShowLog) shows
+const NOTIFICATION_LOG_SIZE: usize = 100;
 
 pub struct App<'a> {
     pub channel: Channel,
     terminal: Terminal<MyBackend>,
     player: DefaultPlayer,
+    /// The player's properties as of the last [Self::refresh_player_state]
+    player_state: PlayerState,
     next_render: time::Instant,
     next_poll_timeout: u16,
     notification: Notification<'a>,
+    /// The last [NOTIFICATION_LOG_SIZE] notifications, oldest first
+    notification_log: VecDeque<LogEntry>,
+    /// Shows the volume or position for a moment after they change
+    osd: Osd,
     visualizer: Option<Visualizer>,
     screen: Rc<RefCell<AppScreen<'a>>>,
+    /// Keys pressed so far that are the start of some chord
+    pending_keys: Vec<KeyEvent>,
+    /// When to give up waiting for the rest of the chord in `pending_keys`
+    chord_deadline: Option<time::Instant>,
+    /// When each command was last fired by a key, for `key_repeat_ms`
+    last_fired: HashMap<command::Command, time::Instant>,
+    /// Where the config was loaded from, if it's being watched for changes
+    config_path: Option<PathBuf>,
+    /// Edits that undo the latest playlist changes, most recent last
+    undo_stack: VecDeque<Edit>,
+    /// Name of the equalizer preset in use, if any
+    eq_preset: Option<String>,
+    /// Whether mpv shows the video of songs that have one
+    video: bool,
+    /// Raw m3u lines of the song copied by [YankSong](command::Command::YankSong)
+    yanked_song: Option<String>,
+    /// Songs played last, for the "Recently played" playlist. Shared with the browse screen, which
+    /// shows them.
+    recent_songs: Rc<RefCell<RecentSongs>>,
+    #[cfg(all(feature = "mpris", target_os = "linux"))]
+    mpris: Option<crate::mpris::Mpris>,
+    #[cfg(feature = "scrobble")]
+    scrobbler: Option<crate::scrobble::Scrobbler>,
+    #[cfg(feature = "discord")]
+    presence: Option<crate::discord::Presence>,
     quit: bool,
 }
 
@@ -61,8 +113,11 @@ impl<'a> App<'a> {
         let terminal = Terminal::new(backend)?;
 
         let player = DefaultPlayer::new()?;
+        // `mpv_options` may have turned the video off already
+        let video = player.video()?;
 
-        let screen = Rc::new(RefCell::new(AppScreen::new()?));
+        let recent_songs = Rc::new(RefCell::new(RecentSongs::load()));
+        let screen = Rc::new(RefCell::new(AppScreen::new(recent_songs.clone())?));
 
         let channel = Channel::default();
 
@@ -75,21 +130,106 @@ impl<'a> App<'a> {
             channel,
             terminal,
             player,
+            player_state: PlayerState::default(),
             next_render,
             next_poll_timeout,
             notification,
+            notification_log: VecDeque::new(),
+            osd: Osd::default(),
             visualizer: None,
             screen,
+            pending_keys: Vec::new(),
+            chord_deadline: None,
+            last_fired: HashMap::new(),
+            config_path: None,
+            undo_stack: VecDeque::new(),
+            eq_preset: None,
+            video,
+            yanked_song: None,
+            recent_songs,
+            #[cfg(all(feature = "mpris", target_os = "linux"))]
+            mpris: None,
+            #[cfg(feature = "scrobble")]
+            scrobbler: None,
+            #[cfg(feature = "discord")]
+            presence: None,
             quit: false,
         })
     }
 
+    /// Reopens the playlist, song and pane from the last run, if that playlist still exists
+    pub fn restore_session(&mut self) {
+        let Some(session) = Session::load() else {
+            return;
+        };
+        let result = self.screen.borrow_mut().restore_session(&session);
+        if let Err(e) = result {
+            self.notify_err(format!("Failed to restore the last session: {}", e));
+        }
+    }
+
+    /// Selects a playlist by name in the browse screen. Returns whether the playlist exists.
+    pub fn select_playlist(&mut self, name: &str) -> Result<bool> {
+        self.screen.borrow_mut().select_playlist(name)
+    }
+
     pub fn run(&mut self) -> Result<()> {
         self.chain_hook();
         setup_terminal()?;
 
         self.channel.spawn_terminal_event_getter();
         self.channel.spawn_ticks();
+        #[cfg(unix)]
+        if let Err(e) = self.channel.spawn_signal_handler() {
+            self.notify_err(format!("Failed to listen for signals: {}", e));
+        }
+
+        let playlists_dir = &Config::global().playlists_dir;
+        let playlists = playlist_management::list_playlists(Path::new(playlists_dir));
+        if matches!(playlists, Ok(p) if p.is_empty()) {
+            self.notify_info(format!("Playlists are kept in {}", playlists_dir));
+        }
+
+        let gapless_audio = &Config::global().gapless_audio;
+        if Config::global().valid_gapless_audio().is_none() {
+            self.notify_err(format!(
+                "Invalid gapless_audio '{}' in the config, expected one of: {}",
+                gapless_audio,
+                GAPLESS_AUDIO_VALUES.join(", ")
+            ));
+        }
+
+        let rejected_options = self.player.rejected_options().join(", ");
+        if !rejected_options.is_empty() {
+            self.notify_err(format!(
+                "mpv didn't accept some mpv_options in the config: {}",
+                rejected_options
+            ));
+        }
+
+        #[cfg(all(feature = "mpris", target_os = "linux"))]
+        match crate::mpris::Mpris::new(self.channel.sender.clone()) {
+            Ok(mpris) => self.mpris = Some(mpris),
+            Err(e) => self.notify_err(format!("Failed to register on D-Bus for MPRIS: {}", e)),
+        }
+
+        #[cfg(feature = "scrobble")]
+        if let Some(config) = &Config::global().scrobble {
+            let sender = self.channel.sender.clone();
+            self.scrobbler = Some(crate::scrobble::Scrobbler::new(config.clone(), sender));
+        }
+
+        #[cfg(feature = "discord")]
+        if let Some(client_id) = &Config::global().discord_client_id {
+            self.presence = Some(crate::discord::Presence::new(client_id));
+        }
+
+        if Config::global().resume_playback {
+            if let Some(point) = ResumePoint::load() {
+                self.resume(point)
+                    .unwrap_or_else(|e| self.notify_err(format!("Failed to resume: {}", e)));
+            }
+        }
 
         while !self.quit {
             self.render()
@@ -100,18 +240,49 @@ impl<'a> App<'a> {
                 .ok();
         }
 
+        if Config::global().resume_playback {
+            let point = ResumePoint::from_state(self.refresh_player_state());
+            if let Err(e) = ResumePoint::save(point.as_ref()) {
+                log::error!("Failed to save the resume point: {}", e);
+            }
+        }
+
+        if let Err(e) = self.screen.borrow().session().save() {
+            log::error!("Failed to save the session: {}", e);
+        }
+
         reset_terminal()?;
         Ok(())
     }
 
+    /// Loads the song from the last run, paused where it was left
+    fn resume(&mut self, point: ResumePoint) -> Result<()> {
+        self.player.play_from(&point.path, point.time_pos)?;
+        self.player.toggle_pause()?;
+
+        let name = Path::new(&point.path)
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or(point.path);
+        self.notify_info(format!(
+            "Resuming {} at {}:{:02}, paused",
+            name,
+            point.time_pos / 60,
+            point.time_pos % 60
+        ));
+        Ok(())
+    }
+
     #[inline]
     fn render(&mut self) -> Result<()> {
         if time::Instant::now() >= self.next_render {
             self.terminal.draw(|frame| {
                 let chunk = frame.size();
                 self.screen.borrow_mut().render(frame, chunk, ());
+                self.osd.render(frame, chunk, ());
                 self.notification.render(frame, frame.size(), ());
             })?;
+            self.screen.borrow_mut().draw_images()?;
 
             let mut err = None; // kind of ugly, but simplifies &mut self borrows
             if let Some(ref mut visualizer) = self.visualizer {
@@ -143,22 +314,39 @@ impl<'a> App<'a> {
         // NOTE: Big timeout if the last event was long ago, small timeout otherwise.
         // This makes it so after a burst of events, like a Ctrl+V, we get a small timeout
         // immediately after the last event, which triggers a fast render.
-        let timeout = Duration::from_millis(self.next_poll_timeout as u64);
-        match self.channel.receiver.recv_timeout(timeout) {
+        let mut timeout = Duration::from_millis(self.next_poll_timeout as u64);
+        if let Some(deadline) = self.chord_deadline {
+            timeout = timeout.min(deadline.saturating_duration_since(time::Instant::now()));
+        }
+
+        let received = self.channel.receiver.recv_timeout(timeout);
+
+        // The rest of the chord didn't come in time
+        if self
+            .chord_deadline
+            .map_or(false, |deadline| time::Instant::now() >= deadline)
+        {
+            for event in self.flush_pending_keys() {
+                self.handle_event(event)?;
+            }
+        }
+
+        match received {
             Ok(Event::Terminal(CrosstermEvent::Key(key))) if key.kind == KeyEventKind::Release => {
                 // WARN: we ignore every key release event for now because of a crossterm 0.26
                 // quirk: https://github.com/crossterm-rs/crossterm/pull/745
                 self.next_poll_timeout = FRAME_DELAY_MS;
             }
             Ok(event) => {
-                let event = self.transform_event(event);
-                self.handle_event(event)?;
+                for event in self.transform_event(event) {
+                    self.handle_event(event)?;
+                }
                 self.next_poll_timeout = FRAME_DELAY_MS;
             }
             Err(mpsc::RecvTimeoutError::Timeout) => {
                 self.next_poll_timeout = self.suitable_event_timeout();
             }
-            Err(e) => return Err(e.into()),
+            Err(e) => return Err(e.to_string().into()),
         }
 
         Ok(())
@@ -172,31 +360,110 @@ impl<'a> App<'a> {
         }
     }
 
-    /// Transforms an event, according to the current app state.
-    fn transform_event(&self, event: Event) -> Event {
+    /// Transforms an event, according to the current app state. Key events may be held back while
+    /// they're the start of a chord, or released all at once when the chord is broken.
+    fn transform_event(&mut self, event: Event) -> Vec<Event> {
         use Event::*;
         match event {
             Terminal(CrosstermEvent::Key(key_event)) => {
                 let has_mods = key_event.modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT)
                     != KeyModifiers::NONE;
-                match self.screen.borrow().mode() {
+                let mode = self.screen.borrow().mode();
+                match mode {
                     // In insert mode, key events pass through untransformed, unless there's a
                     // control or alt modifier
-                    Mode::Insert if !has_mods => event,
+                    Mode::Insert if !has_mods => vec![event],
 
                     // Otherwise, events may be transformed into commands
-                    _ => self.transform_normal_mode_key(key_event),
+                    _ => {
+                        let events = self.transform_normal_mode_key(key_event);
+                        self.throttle(events)
+                    }
                 }
             }
-            _ => event,
+            _ => vec![event],
         }
     }
 
+    /// The player's properties as of the last second. Prefer this over querying the player.
+    pub fn player_state(&self) -> &PlayerState {
+        &self.player_state
+    }
+
+    /// Adds the song being played to the recently played songs if it just started
+    fn update_recent_songs(&mut self) {
+        let max = Config::global().recent_songs;
+        let mut recent_songs = self.recent_songs.borrow_mut();
+        if max == 0 || !recent_songs.update(&self.player_state, max) {
+            return;
+        }
+        if let Err(e) = recent_songs.save() {
+            log::error!("Failed to save the recently played songs: {}", e);
+        }
+        self.channel.send(Event::RecentSongsChanged).ok();
+    }
+
+    /// Reads the player's properties again, e.g. right after changing them
+    pub fn refresh_player_state(&mut self) -> &PlayerState {
+        self.player_state = PlayerState::from_player(&self.player);
+        &self.player_state
+    }
+
     fn handle_event(&mut self, event: events::Event) -> Result<()> {
+        if let Event::SecondTick = event {
+            self.refresh_player_state();
+            self.update_recent_songs();
+        }
+
+        #[cfg(feature = "scrobble")]
+        if let (Event::SecondTick, Some(scrobbler)) = (&event, &mut self.scrobbler) {
+            scrobbler.update(&self.player_state);
+        }
+
+        #[cfg(feature = "discord")]
+        if let (Event::SecondTick, Some(presence)) = (&event, &mut self.presence) {
+            presence.update(&self.player_state);
+        }
+
         match &event {
             Event::Command(command::Command::ToggleVisualizer) => {
                 self.toggle_visualizer()?;
             }
+            Event::Command(command::Command::ToggleVideo) => {
+                self.toggle_video();
+            }
+            Event::Command(command::Command::CyclePreset) => {
+                let presets = &Config::global().eq_presets;
+                if presets.is_empty() {
+                    self.notify_info("There are no eq_presets in the config");
+                } else {
+                    let next = next_eq_preset(presets.keys().map(String::as_str), self.eq_preset());
+                    self.set_eq_preset(next);
+                }
+            }
+            Event::Command(command::Command::EditConfig) => {
+                self.edit_config()?;
+            }
+            #[cfg(all(feature = "mpris", target_os = "linux"))]
+            Event::SecondTick if self.mpris.is_some() => {
+                let state = self.player_state.clone();
+                if let Err(e) = self.mpris.as_ref().unwrap().update(state) {
+                    log::error!("Failed to update MPRIS state: {}", e);
+                }
+                let screen = self.screen.clone();
+                screen.borrow_mut().handle_event(self, event)?;
+            }
+            Event::ConfigReloaded(Ok(())) => {
+                self.notify_ok("Reloaded the config");
+            }
+            Event::ConfigReloaded(Err(e)) => {
+                self.notify_err(format!("Failed to reload the config: {}", e));
+            }
+            Event::ScrobbleFailed(e) => {
+                self.notify_err(format!("Failed to scrobble to Last.fm: {}", e));
+            }
+            // Unlike the Quit command, this doesn't ask for confirmation
+            Event::Terminate => self.quit(),
             Event::Terminal(crossterm::event::Event::Mouse(mouse_event)) => {
                 let screen = self.screen.clone();
                 let chunk = self.frame_size();
@@ -214,16 +481,122 @@ impl<'a> App<'a> {
 
     /// Transforms a key event into the corresponding command, if there is one.
     /// Assumes state is in normal mode
-    fn transform_normal_mode_key(&self, key_event: KeyEvent) -> Event {
-        use crate::command::Command::Nop;
-        use crossterm::event::Event::Key;
-        use Event::*;
-        match Config::global().keybindings.get_from_event(key_event) {
-            Some(cmd) if cmd != Nop => Command(cmd),
-            _ => Terminal(Key(key_event)),
+    fn transform_normal_mode_key(&mut self, key_event: KeyEvent) -> Vec<Event> {
+        self.pending_keys.push(key_event);
+        let keys: Vec<InputStr> = self.pending_keys.iter().map(|&k| k.into()).collect();
+
+        match Config::global().keybindings.lookup(&keys) {
+            Lookup::Prefix(_) => {
+                self.chord_deadline =
+                    Some(time::Instant::now() + Duration::from_millis(CHORD_TIMEOUT_MS));
+                vec![]
+            }
+            Lookup::Found(cmd) => {
+                self.pending_keys.clear();
+                self.chord_deadline = None;
+                vec![command_or_key(cmd, key_event)]
+            }
+            Lookup::NotFound if self.pending_keys.len() > 1 => {
+                // The chord is broken: give up on the keys before this one, and try this one
+                // again by itself
+                self.pending_keys.pop();
+                let mut events = self.flush_pending_keys();
+                events.extend(self.transform_normal_mode_key(key_event));
+                events
+            }
+            Lookup::NotFound => self.flush_pending_keys(),
         }
     }
 
+    /// Drops the commands that fired less than their `key_repeat_ms` ago, like when a key is held
+    fn throttle(&mut self, mut events: Vec<Event>) -> Vec<Event> {
+        let now = time::Instant::now();
+        let key_repeat_ms = &Config::global().key_repeat_ms;
+        events.retain(|event| {
+            let Event::Command(cmd) = event else {
+                return true;
+            };
+            let Some(&cooldown) = key_repeat_ms.get(cmd) else {
+                return true;
+            };
+            let last = self.last_fired.get(cmd).copied();
+            if is_throttled(last, now, cooldown) {
+                return false;
+            }
+            self.last_fired.insert(*cmd, now);
+            true
+        });
+        events
+    }
+
+    /// Stops waiting for the rest of a chord. Fires the command bound to the keys pressed so far,
+    /// or passes them along as terminal events if there's none.
+    fn flush_pending_keys(&mut self) -> Vec<Event> {
+        let keys = std::mem::take(&mut self.pending_keys);
+        self.chord_deadline = None;
+
+        let chord: Vec<InputStr> = keys.iter().map(|&k| k.into()).collect();
+        match (Config::global().keybindings.lookup(&chord), keys.last()) {
+            (Lookup::Found(cmd) | Lookup::Prefix(Some(cmd)), Some(&key)) => {
+                vec![command_or_key(cmd, key)]
+            }
+            _ => keys
+                .into_iter()
+                .map(|k| Event::Terminal(CrosstermEvent::Key(k)))
+                .collect(),
+        }
+    }
+
+    /// Watches the config file at `path` for changes (see [Channel::spawn_config_watcher]), and
+    /// remembers it for [EditConfig](command::Command::EditConfig)
+    pub fn watch_config(&mut self, path: PathBuf) {
+        self.config_path = Some(path.clone());
+        self.channel.spawn_config_watcher(path);
+    }
+
+    /// Opens a file in `editor`, and waits for the editor to close
+    pub fn open_in_editor(&mut self, editor: &str, path: &Path) -> Result<()> {
+        let _lock = self.channel.receiving_crossterm.lock().unwrap();
+        execute!(io::stdout(), LeaveAlternateScreen)?;
+
+        let res = std::process::Command::new(editor)
+            .arg(path)
+            .status()
+            .map_err(|err| format!("Failed to execute editor '{}': {}", editor, err));
+
+        // The editor may have turned bracketed paste off when it quit
+        execute!(io::stdout(), EnterAlternateScreen, EnableBracketedPaste)?;
+        res?;
+        self.terminal.clear()?;
+        Ok(())
+    }
+
+    /// Opens the config file in `$EDITOR`. The config watcher reloads it once it's saved.
+    fn edit_config(&mut self) -> Result<()> {
+        let Ok(editor) = std::env::var("EDITOR") else {
+            self.notify_err("Set the EDITOR environment variable to edit the config");
+            return Ok(());
+        };
+        let Some(path) = self.config_path.clone() else {
+            return Ok(());
+        };
+        self.open_in_editor(&editor, &path)
+    }
+
+    fn toggle_video(&mut self) {
+        let video = !self.video;
+        if let Err(e) = self.player.set_video(video) {
+            self.notify_err(format!("Failed to toggle the video: {}", e));
+            return;
+        }
+        self.video = video;
+        self.notify_info(if video {
+            "Showing videos"
+        } else {
+            "Hiding videos"
+        });
+    }
+
     fn toggle_visualizer(&mut self) -> Result<()> {
         if self.visualizer.take().is_none() {
             let opts = crate::visualizer::CavaOptions {
@@ -244,6 +617,41 @@ impl<'a> App<'a> {
         }));
     }
 
+    /// Name of the [equalizer preset](Config::eq_presets) in use, if any
+    pub fn eq_preset(&self) -> Option<&str> {
+        self.eq_preset.as_deref()
+    }
+
+    /// Switches to the equalizer preset called `name`, or turns the equalizer off if it's `None`.
+    /// If mpv rejects the preset's filter, the previous preset is kept.
+    pub fn set_eq_preset(&mut self, name: Option<&str>) {
+        let presets = &Config::global().eq_presets;
+        let filter = match name.map(|name| (name, presets.get(name))) {
+            Some((_, Some(filter))) => filter.as_str(),
+            Some((name, None)) => {
+                self.notify_err(format!("There's no equalizer preset called '{}'", name));
+                return;
+            }
+            None => "",
+        };
+
+        if let Err(e) = self.player.set_audio_filter(filter) {
+            let previous = self.eq_preset.as_ref().and_then(|p| presets.get(p));
+            let _ = self
+                .player
+                .set_audio_filter(previous.map_or("", String::as_str));
+            self.notify_err(format!(
+                "mpv couldn't use the equalizer preset '{}': {}",
+                name.unwrap_or_default(),
+                e
+            ));
+            return;
+        }
+
+        self.eq_preset = name.map(str::to_string);
+        self.notify_info(format!("Equalizer: {}", name.unwrap_or("off")));
+    }
+
     pub fn select_screen(&mut self, screen: app_screen::Selected) {
         self.screen.borrow_mut().select(screen);
     }
@@ -252,21 +660,75 @@ impl<'a> App<'a> {
         self.quit = true;
     }
 
+    /// Shows `label` with a bar filled up to `ratio` (0 to 1) in the middle of the screen for a
+    /// moment
+    pub fn show_osd(&mut self, label: impl Into<String>, ratio: f64) {
+        self.osd = Osd::new(label, ratio);
+    }
+
     ////////////////////////////////
     //        Notification        //
     ////////////////////////////////
     pub fn notify_err(&mut self, err: impl Into<Cow<'a, str>>) {
-        self.notification = Notification::new(err, Duration::from_secs(5)).colored(Color::LightRed);
+        self.notify(NotificationKind::Error, err.into(), Color::LightRed);
     }
 
     pub fn notify_info(&mut self, info: impl Into<Cow<'a, str>>) {
-        self.notification =
-            Notification::new(info, Duration::from_secs(4)).colored(Color::LightCyan);
+        self.notify(NotificationKind::Info, info.into(), Color::LightCyan);
     }
 
     pub fn notify_ok(&mut self, text: impl Into<Cow<'a, str>>) {
-        self.notification =
-            Notification::new(text, Duration::from_secs(4)).colored(Color::LightGreen);
+        self.notify(NotificationKind::Ok, text.into(), Color::LightGreen);
+    }
+
+    /// Shows a notification, replacing the previous one, for as long as the config says
+    fn notify(&mut self, kind: NotificationKind, text: Cow<'a, str>, color: Color) {
+        self.log_notification(kind, &text);
+        let duration = kind.duration(Config::global().notification_timeout_secs);
+        self.notification = Notification::new(text, duration).colored(color);
+    }
+
+    fn log_notification(&mut self, kind: NotificationKind, text: &str) {
+        self.notification_log.push_back(LogEntry {
+            kind,
+            text: text.to_string(),
+            shown_at: time::Instant::now(),
+        });
+        while self.notification_log.len() > NOTIFICATION_LOG_SIZE {
+            self.notification_log.pop_front();
+        }
+    }
+
+    /// The latest notifications, oldest first
+    pub fn notification_log(&self) -> &VecDeque<LogEntry> {
+        &self.notification_log
+    }
+
+    ////////////////////////
+    //        Undo        //
+    ////////////////////////
+    /// Remembers an edit that undoes some playlist change. Does nothing if `edit` is `None`, so
+    /// the results of `playlist_management` functions can be passed directly.
+    pub fn push_undo(&mut self, edit: Option<Edit>) {
+        if let Some(edit) = edit {
+            self.undo_stack.push_back(edit);
+            while self.undo_stack.len() > Config::global().undo_depth {
+                self.undo_stack.pop_front();
+            }
+        }
+    }
+
+    pub fn pop_undo(&mut self) -> Option<Edit> {
+        self.undo_stack.pop_back()
+    }
+
+    /// Keeps a song's m3u lines to be pasted later, in any playlist
+    pub fn yank_song(&mut self, song: String) {
+        self.yanked_song = Some(song);
+    }
+
+    pub fn yanked_song(&self) -> Option<&str> {
+        self.yanked_song.as_deref()
     }
 
     /////////////////////////
@@ -277,14 +739,78 @@ impl<'a> App<'a> {
     }
 }
 
+/// The preset after `current` in `names`, or `None` (no preset) after the last one
+fn next_eq_preset<'n>(
+    names: impl IntoIterator<Item = &'n str>,
+    current: Option<&str>,
+) -> Option<&'n str> {
+    let mut names = names.into_iter();
+    match current {
+        None => names.next(),
+        Some(current) => names.skip_while(|&name| name != current).nth(1),
+    }
+}
+
+/// Whether a command that last fired at `last` has to wait longer than `now` to fire again
+fn is_throttled(last: Option<time::Instant>, now: time::Instant, cooldown_ms: u64) -> bool {
+    last.map_or(false, |last| {
+        now.duration_since(last) < Duration::from_millis(cooldown_ms)
+    })
+}
+
+/// `Nop` unbinds a key, so it's passed along as a terminal event instead
+fn command_or_key(cmd: command::Command, key_event: KeyEvent) -> Event {
+    match cmd {
+        command::Command::Nop => Event::Terminal(CrosstermEvent::Key(key_event)),
+        cmd => Event::Command(cmd),
+    }
+}
+
 pub fn setup_terminal() -> Result<()> {
-    execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture)?;
+    execute!(
+        io::stdout(),
+        EnterAlternateScreen,
+        EnableMouseCapture,
+        EnableBracketedPaste
+    )?;
     enable_raw_mode()?;
     Ok(())
 }
 
 pub fn reset_terminal() -> Result<()> {
     disable_raw_mode()?;
-    execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture)?;
+    execute!(
+        io::stdout(),
+        LeaveAlternateScreen,
+        DisableMouseCapture,
+        DisableBracketedPaste
+    )?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_eq_preset() {
+        let names = ["bass", "treble", "vocal"];
+        assert_eq!(next_eq_preset(names, None), Some("bass"));
+        assert_eq!(next_eq_preset(names, Some("bass")), Some("treble"));
+        assert_eq!(next_eq_preset(names, Some("vocal")), None);
+        assert_eq!(next_eq_preset([], None), None);
+    }
+
+    #[test]
+    fn test_is_throttled() {
+        let now = time::Instant::now();
+        assert!(!is_throttled(None, now, 30));
+        assert!(is_throttled(Some(now), now + Duration::from_millis(10), 30));
+        assert!(!is_throttled(
+            Some(now),
+            now + Duration::from_millis(30),
+            30
+        ));
+        assert!(!is_throttled(Some(now), now, 0));
+    }
+}
@@ -12,12 +12,12 @@ use unicode_width::UnicodeWidthStr;
 use crate::{
     app::component::{Mode, MyBackend},
     command::Command,
-    config::{shortcuts::InputStr, Config},
+    config::{shortcuts::Shortcuts, Config},
     error::Result,
     events::Event,
 };
 
-/// A modal box that asks for user input
+/// A modal box that lists the current key bindings
 #[derive(Debug, Default)]
 pub struct HelpModal {
     playlists_dir: String,
@@ -30,11 +30,10 @@ impl HelpModal {
 
         let playlists_dir = format!("playlists folder: {}", config.playlists_dir);
 
-        let mut entries: Vec<_> = config.keybindings.0.iter().collect();
-        entries.sort_unstable_by(|(k0, _), (k1, _)| k0.cmp(k1));
+        let entries = group_by_command(&config.keybindings);
         let max_key_length = entries
             .iter()
-            .map(|(k, _)| k.0.width())
+            .map(|(keys, _)| keys.width())
             .max()
             .unwrap_or_default();
         let pad = |x: &str| format!("{}{}", " ".repeat(max_key_length - x.width()), x);
@@ -42,10 +41,10 @@ impl HelpModal {
         let rows: Vec<_> = entries
             .chunks(3)
             .map(|chunk| {
-                let make_cell = |(k, v): &(&InputStr, &Command)| {
+                let make_cell = |(keys, command): &(String, Command)| {
                     Line::from(vec![
-                        Span::styled(pad(&k.0), Style::default().fg(Color::LightBlue)),
-                        Span::raw(format!(" {:?}", v)),
+                        Span::styled(pad(keys), Style::default().fg(Color::LightBlue)),
+                        Span::raw(format!(" {:?}", command)),
                     ])
                 };
 
@@ -60,6 +59,31 @@ impl HelpModal {
     }
 }
 
+/// Lists every bound command once, with all of its hotkeys separated by commas, sorted by the
+/// command's name
+fn group_by_command(shortcuts: &Shortcuts) -> Vec<(String, Command)> {
+    let mut commands: Vec<Command> = Vec::new();
+    for &command in shortcuts.0.values() {
+        if !commands.contains(&command) {
+            commands.push(command);
+        }
+    }
+
+    let mut entries: Vec<_> = commands
+        .into_iter()
+        .map(|command| {
+            let keys: Vec<_> = shortcuts
+                .keys_for(command)
+                .iter()
+                .map(|k| k.0.as_str())
+                .collect();
+            (keys.join(", "), command)
+        })
+        .collect();
+    entries.sort_by_cached_key(|(_, command)| format!("{:?}", command));
+    entries
+}
+
 impl Modal for HelpModal {
     fn apply_style(&mut self, _style: Style) {}
 
@@ -98,8 +122,8 @@ impl Modal for HelpModal {
 
         chunk.x += 2;
         chunk.y += 3;
-        chunk.width -= 2;
-        chunk.height -= 3;
+        chunk.width = chunk.width.saturating_sub(2);
+        chunk.height = chunk.height.saturating_sub(3);
         frame.render_widget(table, chunk);
     }
 
@@ -107,3 +131,22 @@ impl Modal for HelpModal {
         Mode::Insert
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_group_by_command() {
+        let yaml = "q: Quit\nC-c: Quit\n' ': TogglePause\nm: Mute\n";
+        let shortcuts: Shortcuts = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(
+            group_by_command(&shortcuts),
+            vec![
+                ("m".to_string(), Command::Mute),
+                ("C-c, q".to_string(), Command::Quit),
+                (" ".to_string(), Command::TogglePause),
+            ]
+        );
+    }
+}
@@ -2,9 +2,9 @@ use super::{get_modal_chunk, Message, Modal};
 
 use std::{borrow::Cow, mem};
 
-use crossterm::event::KeyCode;
+use crossterm::event::{KeyCode, KeyModifiers};
 use tui::{
-    layout::Alignment,
+    layout::{Alignment, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
     widgets::{Block, BorderType, Borders, Clear, Paragraph},
@@ -15,8 +15,11 @@ use crate::{
     app::component::{Mode, MyBackend},
     error::Result,
     events::Event,
+    util,
 };
 
+const PREFIX: &str = " ❯ ";
+
 /// A modal box that asks for user input
 #[derive(Debug, Default)]
 pub struct InputModal<'t> {
@@ -25,6 +28,18 @@ pub struct InputModal<'t> {
     scroll: u16,
     input: String,
     style: Style,
+    /// Previously submitted inputs, oldest first
+    history: Vec<String>,
+    /// Which history entry is currently shown, if any
+    history_index: Option<usize>,
+    /// What the user had typed before browsing the history
+    draft: String,
+    /// Lists the possible completions of the input, for Tab
+    complete: Option<fn(&str) -> Vec<String>>,
+    /// Completions listed by the last Tab, when there were several
+    completions: Vec<String>,
+    /// Which of the listed completions is in the input, after pressing Tab again
+    completion_index: Option<usize>,
 }
 
 impl<'t> InputModal<'t> {
@@ -35,7 +50,86 @@ impl<'t> InputModal<'t> {
             scroll: 0,
             input: String::default(),
             style: Style::default().fg(Color::LightBlue),
+            history: Vec::new(),
+            history_index: None,
+            draft: String::default(),
+            complete: None,
+            completions: Vec::new(),
+            completion_index: None,
+        }
+    }
+
+    /// Lets the user complete the input with Tab, e.g. with [util::path_completions]
+    pub fn with_completion(mut self, complete: fn(&str) -> Vec<String>) -> Self {
+        self.complete = Some(complete);
+        self
+    }
+
+    /// Completes the input as far as all of its completions agree, and lists them if there are
+    /// several. Pressing Tab again puts each of the listed completions in the input in turn.
+    fn complete(&mut self) {
+        if !self.completions.is_empty() {
+            let index = self
+                .completion_index
+                .map_or(0, |i| (i + 1) % self.completions.len());
+            self.completion_index = Some(index);
+            self.input = self.completions[index].clone();
+            self.cursor = self.input.len();
+            return;
+        }
+
+        let Some(complete) = self.complete else {
+            return;
+        };
+        let completions = complete(&self.input);
+        let common = common_prefix(&completions);
+        if common.len() > self.input.len() {
+            self.input = common.to_string();
+            self.cursor = self.input.len();
         }
+
+        self.completions = if completions.len() > 1 {
+            completions
+        } else {
+            Vec::new()
+        };
+    }
+
+    /// Lets the user recall previous inputs with the up and down arrows
+    pub fn with_history(mut self, history: Vec<String>) -> Self {
+        self.history = history;
+        self
+    }
+
+    fn history_prev(&mut self) {
+        let index = match self.history_index {
+            _ if self.history.is_empty() => return,
+            // Only start browsing the history when the cursor is at the start, like in a shell
+            None if self.cursor != 0 => return,
+            None => {
+                self.draft = mem::take(&mut self.input);
+                self.history.len() - 1
+            }
+            Some(i) => i.saturating_sub(1),
+        };
+        self.history_index = Some(index);
+        self.input = self.history[index].clone();
+        self.cursor = self.input.len();
+    }
+
+    fn history_next(&mut self) {
+        match self.history_index {
+            Some(i) if i + 1 < self.history.len() => {
+                self.history_index = Some(i + 1);
+                self.input = self.history[i + 1].clone();
+            }
+            Some(_) => {
+                self.history_index = None;
+                self.input = mem::take(&mut self.draft);
+            }
+            None => return,
+        }
+        self.cursor = self.input.len();
     }
 
     pub fn set_input(mut self, input: String) -> Self {
@@ -44,6 +138,14 @@ impl<'t> InputModal<'t> {
         self
     }
 
+    /// Inserts some text at the cursor, like a paste. Newlines are dropped, since the input is a
+    /// single line.
+    fn insert_str(&mut self, text: &str) {
+        let text = util::single_line(text);
+        self.input.insert_str(self.cursor, &text);
+        self.cursor += text.len();
+    }
+
     fn move_cursor(&mut self, x: isize) {
         let inc = |y: usize| (y as isize + x).min(self.input.len() as isize).max(0) as usize;
         self.cursor = inc(self.cursor);
@@ -62,8 +164,28 @@ impl<'t> Modal for InputModal<'t> {
     fn handle_event(&mut self, event: Event) -> Result<Message> {
         use Event::*;
         use KeyCode::*;
+        if let Terminal(crossterm::event::Event::Resize(width, height)) = event {
+            self.calculate_scroll(input_width(Rect::new(0, 0, width, height)));
+            return Ok(Message::Nothing);
+        }
+
+        if let Terminal(crossterm::event::Event::Paste(text)) = &event {
+            self.insert_str(text);
+            return Ok(Message::Nothing);
+        }
+
         if let Terminal(crossterm::event::Event::Key(event)) = event {
+            if event.code != Tab {
+                self.completions.clear();
+                self.completion_index = None;
+            }
             match event.code {
+                Tab => self.complete(),
+                Char('v') if event.modifiers.contains(KeyModifiers::CONTROL) => {
+                    if let Some(text) = util::paste_from_clipboard() {
+                        self.insert_str(&text);
+                    }
+                }
                 Char(c) => {
                     self.input.insert(self.cursor, c);
                     self.move_cursor(1);
@@ -85,6 +207,8 @@ impl<'t> Modal for InputModal<'t> {
                 Right => {
                     self.move_cursor(1);
                 }
+                Up => self.history_prev(),
+                Down => self.history_next(),
                 Home => {
                     self.cursor = 0;
                 }
@@ -108,8 +232,7 @@ impl<'t> Modal for InputModal<'t> {
     fn render(&mut self, frame: &mut Frame<'_, MyBackend>) {
         let size = frame.size();
         let chunk = get_modal_chunk(size);
-        let prefix = " ❯ ";
-        let scroll = self.calculate_scroll(chunk.width - prefix.len() as u16);
+        let scroll = self.calculate_scroll(input_width(size));
 
         let block = Block::default()
             .title(self.title.as_ref())
@@ -129,7 +252,7 @@ impl<'t> Modal for InputModal<'t> {
         let paragraph = Paragraph::new(vec![
             Line::from(vec![]), // empty first line
             Line::from(vec![
-                Span::styled(prefix, self.style),
+                Span::styled(PREFIX, self.style),
                 Span::raw(left),
                 Span::styled(in_cursor, Style::default().add_modifier(Modifier::REVERSED)),
                 Span::raw(right),
@@ -141,6 +264,36 @@ impl<'t> Modal for InputModal<'t> {
 
         frame.render_widget(Clear, chunk);
         frame.render_widget(paragraph, chunk);
+
+        // The completions go in the empty first line, without the part that they all share
+        if !self.completions.is_empty() {
+            let shared = common_prefix(&self.completions).len();
+            let shared = self.completions[0][..shared]
+                .rfind(std::path::is_separator)
+                .map_or(0, |i| i + 1);
+            let mut names = Vec::new();
+            for (i, completion) in self.completions.iter().enumerate() {
+                if i > 0 {
+                    names.push(Span::raw("  "));
+                }
+                let style = match self.completion_index {
+                    Some(index) if index == i => self.style,
+                    _ => Style::default(),
+                };
+                names.push(Span::styled(&completion[shared..], style));
+            }
+            let line = Rect {
+                x: chunk.x + 1 + PREFIX.chars().count() as u16,
+                y: chunk.y + 1,
+                width: chunk
+                    .width
+                    .saturating_sub(2 + PREFIX.chars().count() as u16),
+                height: 1,
+            };
+            let completions =
+                Paragraph::new(Line::from(names)).style(Style::default().fg(Color::DarkGray));
+            frame.render_widget(completions, line);
+        }
     }
 
     fn mode(&self) -> Mode {
@@ -151,6 +304,7 @@ impl<'t> Modal for InputModal<'t> {
 impl<'t> InputModal<'t> {
     /// Updates and calculates the Paragraph's scroll based on the current cursor and input
     fn calculate_scroll(&mut self, chunk_width: u16) -> u16 {
+        let chunk_width = chunk_width.max(1);
         if self.cursor as u16 > self.scroll + chunk_width - 1 {
             self.scroll = self.cursor as u16 + 1 - chunk_width;
         }
@@ -163,6 +317,29 @@ impl<'t> InputModal<'t> {
     }
 }
 
+/// The longest prefix shared by all `strings`
+fn common_prefix(strings: &[String]) -> &str {
+    let Some((first, rest)) = strings.split_first() else {
+        return "";
+    };
+    let mut len = first.len();
+    for s in rest {
+        len = first
+            .char_indices()
+            .zip(s.chars())
+            .find(|((_, a), b)| a != b)
+            .map_or(len.min(s.len()), |((i, _), _)| i.min(len));
+    }
+    &first[..len]
+}
+
+/// Width of the input text in a modal shown in a frame of some size
+fn input_width(frame: Rect) -> u16 {
+    get_modal_chunk(frame)
+        .width
+        .saturating_sub(PREFIX.len() as u16)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -210,4 +387,112 @@ mod tests {
         modal.move_cursor(-1);
         assert_eq!(modal.cursor, 0);
     }
+
+    #[test]
+    fn test_modal_paste() {
+        let mut modal = InputModal::new("modal paste").set_input("ab".into());
+        modal.move_cursor(-1);
+
+        modal.insert_str("おはよう\n");
+        assert_eq!(modal.input, "aおはようb");
+        assert_eq!(modal.cursor, 1 + "おはよう".len());
+
+        modal.move_cursor(1);
+        assert_eq!(modal.cursor, modal.input.len());
+
+        let long = "x".repeat(200);
+        modal.insert_str(&long);
+        assert_eq!(modal.cursor, modal.input.len());
+        assert_eq!(modal.calculate_scroll(50), modal.cursor as u16 + 1 - 50);
+    }
+
+    #[test]
+    fn test_modal_history() {
+        let mut modal = InputModal::new("modal history")
+            .with_history(vec!["first".into(), "second".into()])
+            .set_input("draft".into());
+
+        // The cursor isn't at the start, so the history isn't opened
+        modal.history_prev();
+        assert_eq!(modal.input, "draft");
+
+        modal.cursor = 0;
+        modal.history_prev();
+        assert_eq!(modal.input, "second");
+        assert_eq!(modal.cursor, modal.input.len());
+
+        modal.history_prev();
+        assert_eq!(modal.input, "first");
+        modal.history_prev();
+        assert_eq!(modal.input, "first");
+
+        modal.history_next();
+        assert_eq!(modal.input, "second");
+        modal.history_next();
+        assert_eq!(modal.input, "draft");
+        modal.history_next();
+        assert_eq!(modal.input, "draft");
+    }
+
+    #[test]
+    fn test_paste_event() {
+        // A pasted newline doesn't commit the input
+        let mut modal = InputModal::new("paste event");
+        let paste = crossterm::event::Event::Paste("first\nsecond\n".into());
+        let msg = modal.handle_event(Event::Terminal(paste)).unwrap();
+        assert_eq!(msg, Message::Nothing);
+        assert_eq!(modal.input, "first second");
+    }
+
+    #[test]
+    fn test_scroll_after_resize() {
+        let mut modal = InputModal::new("resize").set_input("x".repeat(100));
+        modal.calculate_scroll(input_width(Rect::new(0, 0, 200, 50)));
+        assert_eq!(modal.scroll, 100 + 1 - 65);
+
+        // The cursor stays in view when the modal gets narrower
+        let msg = modal
+            .handle_event(Event::Terminal(crossterm::event::Event::Resize(40, 50)))
+            .unwrap();
+        assert_eq!(msg, Message::Nothing);
+        assert_eq!(modal.scroll, 100 + 1 - 35);
+    }
+
+    #[test]
+    fn test_modal_completion() {
+        fn complete(input: &str) -> Vec<String> {
+            ["/music/", "/music/song.mp3", "/music/songs/"]
+                .into_iter()
+                .filter(|c| c.starts_with(input))
+                .map(String::from)
+                .collect()
+        }
+        let mut modal = InputModal::new("modal completion")
+            .with_completion(complete)
+            .set_input("/music/s".into());
+
+        modal.complete();
+        assert_eq!(modal.input, "/music/song");
+        assert_eq!(modal.cursor, modal.input.len());
+        assert_eq!(modal.completions.len(), 2);
+
+        // Tab again cycles through the listed completions
+        modal.complete();
+        assert_eq!(modal.input, "/music/song.mp3");
+        modal.complete();
+        assert_eq!(modal.input, "/music/songs/");
+        assert_eq!(modal.cursor, modal.input.len());
+        modal.complete();
+        assert_eq!(modal.input, "/music/song.mp3");
+
+        modal.completions.clear();
+        modal.completion_index = None;
+        modal.input = "/music/songs".into();
+        modal.complete();
+        assert_eq!(modal.input, "/music/songs/");
+        assert!(modal.completions.is_empty());
+
+        assert_eq!(common_prefix(&["ab".into(), "a".into()]), "a");
+        assert_eq!(common_prefix(&[]), "");
+    }
 }
@@ -0,0 +1,110 @@
+use super::{get_modal_chunk, Message, Modal};
+
+use crossterm::event::KeyCode;
+use tui::{
+    style::{Color, Style},
+    widgets::{Block, BorderType, Borders, Clear, List, ListItem, ListState},
+    Frame,
+};
+
+use crate::{
+    app::component::{Mode, MyBackend},
+    error::Result,
+    events::Event,
+};
+
+/// A modal box that asks the user to pick an item from a list
+#[derive(Debug, Default)]
+pub struct ListModal {
+    title: String,
+    items: Vec<String>,
+    state: ListState,
+    style: Style,
+}
+
+impl ListModal {
+    pub fn new(title: impl Into<String>, items: Vec<String>) -> Self {
+        let mut state = ListState::default();
+        if !items.is_empty() {
+            state.select(Some(0));
+        }
+
+        Self {
+            title: title.into(),
+            items,
+            state,
+            style: Style::default().fg(Color::LightBlue),
+        }
+    }
+
+    fn select_next(&mut self) {
+        if let Some(i) = self.state.selected() {
+            self.state.select(Some((i + 1) % self.items.len()));
+        }
+    }
+
+    fn select_prev(&mut self) {
+        if let Some(i) = self.state.selected() {
+            self.state
+                .select(Some((i + self.items.len() - 1) % self.items.len()));
+        }
+    }
+}
+
+impl Modal for ListModal {
+    fn apply_style(&mut self, style: Style) {
+        self.style = style;
+    }
+
+    fn handle_event(&mut self, event: Event) -> Result<Message> {
+        use Event::*;
+        use KeyCode::*;
+
+        if let Terminal(crossterm::event::Event::Key(event)) = event {
+            match event.code {
+                Down | Char('j') => self.select_next(),
+                Up | Char('k') => self.select_prev(),
+                Esc | Char('q') => return Ok(Message::Quit),
+                Enter => {
+                    return Ok(match self.state.selected() {
+                        Some(i) => Message::Commit(self.items[i].clone()),
+                        None => Message::Quit,
+                    })
+                }
+                _ => {}
+            }
+        }
+        Ok(Message::Nothing)
+    }
+
+    fn render(&mut self, frame: &mut Frame<'_, MyBackend>) {
+        let size = frame.size();
+        let mut chunk = get_modal_chunk(size);
+        let height = (self.items.len() as u16 + 2).min(size.height.saturating_sub(4));
+        chunk.y = size.height.saturating_sub(height) / 2;
+        chunk.height = height;
+
+        let block = Block::default()
+            .title(self.title.as_str())
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(self.style);
+
+        let items: Vec<_> = self
+            .items
+            .iter()
+            .map(|s| ListItem::new(format!(" {}", s)))
+            .collect();
+
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(Style::default().bg(Color::LightBlue).fg(Color::Black));
+
+        frame.render_widget(Clear, chunk);
+        frame.render_stateful_widget(list, chunk, &mut self.state);
+    }
+
+    fn mode(&self) -> Mode {
+        Mode::Insert
+    }
+}
@@ -2,11 +2,13 @@ pub mod confirmation_modal;
 pub mod help_modal;
 pub mod hotkey_modal;
 pub mod input_modal;
+pub mod list_modal;
 
 pub use confirmation_modal::ConfirmationModal;
 pub use help_modal::HelpModal;
 pub use hotkey_modal::HotkeyModal;
 pub use input_modal::InputModal;
+pub use list_modal::ListModal;
 
 use tui::{layout::Rect, style::Style, Frame};
 
@@ -67,7 +69,7 @@ mod tests {
     use crate::events::Event;
     use crossterm::event::{
         Event::Key,
-        KeyCode::{self, Backspace, Char, Enter, Esc},
+        KeyCode::{self, Backspace, Char, Down, Enter, Esc, Up},
         KeyEvent, KeyEventKind, KeyEventState, KeyModifiers,
     };
 
@@ -133,4 +135,25 @@ mod tests {
             Some(Message::Quit)
         );
     }
+
+    #[test]
+    fn test_list_modal_lifecycle() {
+        let mut modal = ListModal::new("list lifecycle", vec!["a".into(), "b".into()]);
+        assert_eq!(
+            modal.handle_event(Event::Terminal(key_event(Up))).ok(),
+            Some(Message::Nothing)
+        );
+        assert_eq!(
+            modal.handle_event(Event::Terminal(key_event(Down))).ok(),
+            Some(Message::Nothing)
+        );
+        assert_eq!(
+            modal.handle_event(Event::Terminal(key_event(Down))).ok(),
+            Some(Message::Nothing)
+        );
+        assert_eq!(
+            modal.handle_event(Event::Terminal(key_event(Enter))).ok(),
+            Some(Message::Commit("b".into()))
+        );
+    }
 }
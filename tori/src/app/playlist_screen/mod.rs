@@ -1,4 +1,3 @@
-use self::centered_list::{CenteredList, CenteredListItem, CenteredListState};
 use super::{
     component::{Component, MouseHandler},
     App, Mode,
@@ -6,18 +5,17 @@ use super::{
 use crate::{command, error::Result, events, player::Player, widgets::Scrollbar};
 use std::{thread, time::Duration};
 use tui::{
-    layout::{Alignment, Rect},
-    style::{Color, Style},
-    widgets::{Block, BorderType, Borders},
+    layout::{Alignment, Constraint, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Block, BorderType, Borders, Row, Table, TableState},
 };
 
-mod centered_list;
-
 /// Screen that shows the current mpv playlist. You can press '2' to access it.
 #[derive(Debug, Default)]
 pub struct PlaylistScreen {
     songs: Vec<String>,
-    playing: CenteredListState,
+    playing: Option<usize>,
+    state: TableState,
 }
 
 impl PlaylistScreen {
@@ -29,15 +27,22 @@ impl PlaylistScreen {
             .map(|i| player.playlist_track_title(i))
             .collect::<Result<_>>()?;
 
-        self.playing.select(player.playlist_position().ok());
+        self.playing = player.playlist_position().ok().filter(|&i| i < n);
+
+        let selected = match self.state.selected() {
+            _ if n == 0 => None,
+            Some(i) => Some(i.min(n - 1)),
+            None => Some(self.playing.unwrap_or(0)),
+        };
+        self.state.select(selected);
 
         Ok(self)
     }
 
     /// Waits a couple of milliseconds, then calls [update](PlaylistScreen::update). It's used
-    /// primarily by [select_next](PlaylistScreen::select_next) and
-    /// [select_prev](PlaylistScreen::select_prev) because mpv takes a while to update the playlist
-    /// properties after changing the selection.
+    /// primarily by [play_selected](PlaylistScreen::play_selected) and
+    /// [remove_selected](PlaylistScreen::remove_selected) because mpv takes a while to update the
+    /// playlist properties after changing it.
     pub fn update_after_delay(&self, app: &App) {
         let sender = app.channel.sender.clone();
         thread::spawn(move || {
@@ -49,8 +54,9 @@ impl PlaylistScreen {
     fn handle_command(&mut self, app: &mut App, cmd: command::Command) -> Result<()> {
         use command::Command::*;
         match cmd {
-            SelectNext | NextSong => self.select_next(app),
-            SelectPrev | PrevSong => self.select_prev(app),
+            SelectNext => self.select_next(),
+            SelectPrev => self.select_prev(),
+            Delete => self.remove_selected(app),
             _ => {}
         }
         Ok(())
@@ -64,26 +70,47 @@ impl PlaylistScreen {
         use crossterm::event::{Event, KeyCode};
         if let Event::Key(key_event) = event {
             match key_event.code {
-                KeyCode::Up => self.select_prev(app),
-                KeyCode::Down => self.select_next(app),
+                KeyCode::Up => self.select_prev(),
+                KeyCode::Down => self.select_next(),
+                KeyCode::Enter => self.play_selected(app),
+                KeyCode::Delete => self.remove_selected(app),
                 _ => {}
             }
         }
         Ok(())
     }
 
-    pub fn select_next(&self, app: &mut App) {
-        app.player
-            .playlist_next()
-            .unwrap_or_else(|_| app.notify_err("No next song"));
-        self.update_after_delay(app);
+    pub fn select_next(&mut self) {
+        if let Some(i) = self.state.selected() {
+            self.state.select(Some((i + 1) % self.songs.len()));
+        }
     }
 
-    pub fn select_prev(&self, app: &mut App) {
-        app.player
-            .playlist_previous()
-            .unwrap_or_else(|_| app.notify_err("No previous song"));
-        self.update_after_delay(app);
+    pub fn select_prev(&mut self) {
+        if let Some(i) = self.state.selected() {
+            self.state
+                .select(Some((i + self.songs.len() - 1) % self.songs.len()));
+        }
+    }
+
+    /// Jumps to the selected entry of the playlist
+    pub fn play_selected(&self, app: &mut App) {
+        if let Some(i) = self.state.selected() {
+            app.player
+                .playlist_play_index(i)
+                .unwrap_or_else(|e| app.notify_err(format!("Couldn't play song: {}", e)));
+            self.update_after_delay(app);
+        }
+    }
+
+    /// Removes the selected entry from the playlist
+    pub fn remove_selected(&self, app: &mut App) {
+        if let Some(i) = self.state.selected() {
+            app.player
+                .playlist_remove(i)
+                .unwrap_or_else(|e| app.notify_err(format!("Couldn't remove song: {}", e)));
+            self.update_after_delay(app);
+        }
     }
 }
 
@@ -102,23 +129,40 @@ impl Component for PlaylistScreen {
             .border_type(BorderType::Rounded)
             .border_style(Style::default().fg(Color::LightRed));
 
-        let items: Vec<_> = self
+        let rows: Vec<_> = self
             .songs
             .iter()
-            .map(|x| CenteredListItem::new(x.as_str()))
+            .enumerate()
+            .map(|(i, title)| {
+                let row = Row::new(vec![format!("{:>4}", i + 1), title.clone()]);
+                if self.playing == Some(i) {
+                    row.style(
+                        Style::default()
+                            .fg(Color::LightRed)
+                            .add_modifier(Modifier::BOLD),
+                    )
+                } else {
+                    row
+                }
+            })
             .collect();
-        let list = CenteredList::new(items)
+
+        let widths = &[Constraint::Length(5), Constraint::Percentage(100)];
+        let table = Table::new(rows)
             .block(block)
+            .widths(widths)
             .highlight_style(Style::default().bg(Color::Red).fg(Color::White))
-            .highlight_symbol("›")
-            .highlight_symbol_right("‹");
+            .highlight_symbol("›");
 
-        frame.render_stateful_widget(list, chunk, &mut self.playing);
+        frame.render_stateful_widget(table, chunk, &mut self.state);
 
         if self.songs.len() > chunk.height as usize - 2 {
-            if let Some(index) = self.playing.selected() {
-                let scrollbar = Scrollbar::new(index as u16, self.songs.len() as u16)
-                    .with_style(Style::default().fg(Color::Red));
+            if let Some(index) = self.state.selected() {
+                let scrollbar = Scrollbar::themed(
+                    index as u16,
+                    self.songs.len() as u16,
+                    Style::default().fg(Color::Red),
+                );
                 frame.render_widget(scrollbar, chunk);
             }
         }
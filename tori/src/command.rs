@@ -1,33 +1,124 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Default, Clone, Copy, PartialEq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Command {
     #[default]
     Nop,
     Quit,
+
+    /// Skip to the next song in the queue (also accepted as `NextTrack`)
+    #[serde(alias = "NextTrack")]
     NextSong,
+
+    /// Go back to the previous song in the queue (also accepted as `PrevTrack`)
+    #[serde(alias = "PrevTrack")]
     PrevSong,
+
     TogglePause,
+
+    /// Loop the current song (also accepted as `ToggleLoopFile`)
+    #[serde(alias = "ToggleLoopFile")]
     ToggleLoop,
+
+    /// Loop the whole queue
+    ToggleLoopPlaylist,
+
     SeekForward,
     SeekBackward,
+
+    /// Jump to a time in the song being played, typed as `mm:ss` or seconds
+    SeekTo,
+
     OpenInBrowser,
+
+    /// Open the directory of the selected song in the file manager (or the song in the browser if
+    /// it's a URL)
+    RevealInFileManager,
+
     CopyUrl,
     CopyTitle,
+
+    /// Copy the path of the selected playlist file, when the playlists pane is focused
+    CopyPlaylistPath,
+
     VolumeUp,
     VolumeDown,
+    /// Toggle mute, without changing the volume (also accepted as `ToggleMute`)
+    #[serde(alias = "ToggleMute")]
     Mute,
+
+    /// Play faster, in steps of 0.1x
+    SpeedUp,
+
+    /// Play slower, in steps of 0.1x
+    SpeedDown,
+
+    /// Go back to the normal playback speed
+    SpeedReset,
+
+    /// Switch to the next equalizer preset, or turn the equalizer off after the last one
+    CyclePreset,
+
+    /// Pick an equalizer preset from a list
+    SetPreset,
+
+    /// Set the start of an A-B loop at the current position, or its end if the start is set
+    SetLoopPoint,
+
+    /// Stop looping between the A-B loop points
+    ClearLoopPoints,
+
     ToggleVisualizer,
+
+    /// Show or hide the video of songs that have one, like music videos
+    ToggleVideo,
+
     NextSortingMode,
+
+    /// Reverse the order of the songs, for any sorting mode
+    ReverseSort,
+
+    /// Show the current key bindings (also accepted as `ShowHelp`)
+    #[serde(alias = "ShowHelp")]
     OpenHelpModal,
+
+    /// Show the latest notifications
+    ShowLog,
+
+    /// Go from the browse screen to the queue screen, or back
+    SwitchScreen,
     OpenHotkeyModal,
 
     /// Rename selected song or playlist
     Rename,
 
+    /// Rename the selected playlist, even when the songs pane is focused
+    RenamePlaylist,
+
+    /// Copy the selected playlist to a new name
+    DuplicatePlaylist,
+
     /// Delete selected song or playlist
     Delete,
 
+    /// Mark or unmark the selected song. Delete and MoveSong act on all marked songs, if any.
+    ToggleMark,
+
+    /// Delete the selected playlist, even when the songs pane is focused
+    DeletePlaylist,
+
+    /// Undo the last change to a playlist (delete, rename, move or swap a song)
+    Undo,
+
+    /// Move the selected song to another playlist
+    MoveSong,
+
+    /// Copy the selected song, to be pasted with PasteSong
+    YankSong,
+
+    /// Insert the song copied with YankSong after the selected one
+    PasteSong,
+
     /// Swap the selected song with the one below it
     SwapSongDown,
 
@@ -37,12 +128,30 @@ pub enum Command {
     /// Shuffle current playlist
     Shuffle,
 
+    /// Stop playing and remove every song from the queue
+    ClearQueue,
+
+    /// Save the songs in the queue as a new playlist
+    SaveQueue,
+
     /// Select next item (like a song or playlist)
     SelectNext,
 
     /// Select previous item (like a song or playlist)
     SelectPrev,
 
+    /// Select the first item (like a song or playlist)
+    SelectFirst,
+
+    /// Select the last item (like a song or playlist)
+    SelectLast,
+
+    /// Move the selection down by a page of songs
+    SelectPageDown,
+
+    /// Move the selection up by a page of songs
+    SelectPageUp,
+
     /// Select the pane to the right (the same as pressing the \<right> key)
     SelectRight,
 
@@ -52,21 +161,55 @@ pub enum Command {
     /// Add a new song or playlist
     Add,
 
+    /// Add the song being played to a playlist, picked from a list
+    AddCurrentToPlaylist,
+
+    /// Create a playlist with the audio files in some directory
+    ImportDirectory,
+
+    /// Write a copy of the selected playlist in the PLS format, next to the original
+    ExportPlaylist,
+
     /// Add song to the queue
     QueueSong,
 
     /// Add all shown songs to the queue
     QueueShown,
 
+    /// Play only the selected song. Enter plays it and queues the rest of the playlist.
+    PlaySelectedOnly,
+
+    /// Replace the queue with all shown songs
+    PlayShown,
+
+    /// Replace the queue with the shown songs from the selected one to the end, like Enter
+    PlayFromHere,
+
     /// Queries the user for a song to play, without adding it to a playlist
     PlayFromModal,
 
     /// Open the playlist file in the configured by the environment variable `EDITOR`.
     OpenInEditor,
 
+    /// Open the config file in the editor set by the environment variable `EDITOR`. It's
+    /// reloaded when saved.
+    EditConfig,
+
     /// Filter/search the selected pane (playlists or songs).
     /// The same as pressing '/'
     Search,
+
+    /// Show every song without clearing the filter, or apply the filter again
+    ToggleFilter,
+
+    /// Show the paths of the songs in place of their titles, or go back to the titles
+    ToggleShowPaths,
+
+    /// Search songs across all playlists
+    GlobalSearch,
+
+    /// Select the song being played, in the shown playlist or the first other playlist that has it
+    GoToPlaying,
 }
 
 #[cfg(test)]
@@ -93,5 +236,25 @@ mod tests {
             serde_yaml::from_str::<Command>("VolumeUp").unwrap(),
             Command::VolumeUp
         );
+        assert_eq!(
+            serde_yaml::from_str::<Command>("ToggleLoopFile").unwrap(),
+            Command::ToggleLoop
+        );
+        assert_eq!(
+            serde_yaml::from_str::<Command>("ShowHelp").unwrap(),
+            Command::OpenHelpModal
+        );
+        assert_eq!(
+            serde_yaml::from_str::<Command>("ToggleMute").unwrap(),
+            Command::Mute
+        );
+        assert_eq!(
+            serde_yaml::from_str::<Command>("NextTrack").unwrap(),
+            Command::NextSong
+        );
+        assert_eq!(
+            serde_yaml::from_str::<Command>("PrevTrack").unwrap(),
+            Command::PrevSong
+        );
     }
 }
@@ -1,7 +1,13 @@
-use crate::error::Result;
+use crate::{command::Command, error::Result};
 use once_cell::sync::OnceCell;
 use serde::{Deserialize, Serialize};
-use std::{io, path::PathBuf};
+use std::{
+    collections::{BTreeMap, HashMap},
+    ffi::OsString,
+    io,
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+};
 
 pub mod shortcuts;
 use shortcuts::Shortcuts;
@@ -12,50 +18,266 @@ pub struct Config {
     pub visualizer_gradient: [(u8, u8, u8); 2],
     pub keybindings: Shortcuts,
     pub mpv_ao: Option<String>,
+    /// mpv's `gapless-audio` option: `no`, `yes` or `weak`
+    pub gapless_audio: String,
+    pub seek_seconds: f64,
+    pub probe_durations: bool,
+    pub filter_mode: FilterMode,
+    pub undo_depth: usize,
+    pub import_recursive: bool,
+    /// Maximum time between two clicks for them to count as a double click, in milliseconds
+    pub double_click_ms: u64,
+    /// Tiebreakers for when songs are equal by the selected sorting method
+    pub sort_keys: Vec<SortingMethod>,
+    /// Columns of the songs table, in order
+    pub song_columns: Vec<SongColumn>,
+    /// Go from the last item of a list to the first one (and vice versa) instead of stopping
+    pub wrap_navigation: bool,
+    /// Scroll the title of the selected song when it's too long to fit
+    pub marquee: bool,
+    /// Show the cover of the playing song, if tori was built with the `album-art` feature
+    pub album_art: bool,
+    /// Save the song being played when quitting, and resume it on the next launch
+    pub resume_playback: bool,
+    /// How long notifications are shown, in seconds. Errors are shown for a second longer.
+    pub notification_timeout_secs: u64,
+    /// Minimum time between two runs of a command, in milliseconds, for commands that shouldn't
+    /// fire on every key repeat. Commands that aren't listed are never throttled.
+    pub key_repeat_ms: HashMap<Command, u64>,
+    /// Equalizer presets, by name, as mpv audio filters. They're cycled through in name order.
+    pub eq_presets: BTreeMap<String, String>,
+    /// Show the songs of playlists that are listed in a playlist, instead of the playlists
+    /// themselves. Playlists with nested playlists can't be edited then.
+    pub expand_nested_playlists: bool,
+    /// Ask before quitting while there are songs in the queue, since mpv doesn't keep them
+    pub confirm_quit: bool,
+    /// Character the scrollbars are drawn with
+    pub scrollbar_symbol: String,
+    /// Color of the scrollbars. Without one, they take the color of their pane's border.
+    pub scrollbar_color: Option<(u8, u8, u8)>,
+    /// How many of the songs played last are kept in the "Recently played" playlist. 0 hides it.
+    pub recent_songs: usize,
+    /// Color of the box that shows the volume and position when they change. Defaults to the color
+    /// of info notifications.
+    pub osd_color: Option<(u8, u8, u8)>,
+    /// Options passed to mpv when it starts, by mpv's name for them, like `audio-device`
+    pub mpv_options: HashMap<String, String>,
+    /// Leaves out the borders of the playlists and songs panes and shortens their titles, for small
+    /// terminals
+    pub compact: bool,
+    pub scrobble: Option<ScrobbleConfig>,
+    pub discord_client_id: Option<String>,
 }
 
-static INSTANCE: OnceCell<Config> = OnceCell::new();
+/// Values that mpv accepts for its `gapless-audio` option
+pub const GAPLESS_AUDIO_VALUES: [&str; 3] = ["no", "yes", "weak"];
+
+/// The config can be replaced while tori is running (see [Config::set_global]). Each instance is
+/// freed once the last [Config::global] handle to it is dropped.
+static INSTANCE: OnceCell<RwLock<Arc<Config>>> = OnceCell::new();
 
 impl Config {
-    pub fn global() -> &'static Self {
-        INSTANCE.get().expect("Config instance not loaded!")
+    pub fn global() -> Arc<Self> {
+        INSTANCE
+            .get()
+            .expect("Config instance not loaded!")
+            .read()
+            .unwrap()
+            .clone()
     }
 
+    /// Sets the global config, replacing the previous one if there is one
     pub fn set_global(instance: Self) {
-        INSTANCE.set(instance).unwrap();
+        let instance = Arc::new(instance);
+        if let Err(lock) = INSTANCE.set(RwLock::new(instance)) {
+            *INSTANCE.get().unwrap().write().unwrap() = lock.into_inner().unwrap();
+        }
+    }
+
+    /// Loads the default config, merged with the user's config at `path`
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let config = Config::default().merge(OptionalConfig::from_path(path)?)?;
+
+        let errors = config.keybindings.validate();
+        if !errors.is_empty() {
+            let errors: Vec<_> = errors.iter().map(ToString::to_string).collect();
+            return Err(format!(
+                "Your tori.yaml configuration file has invalid keybindings: {}",
+                errors.join("; ")
+            )
+            .into());
+        }
+
+        Ok(config)
+    }
+
+    /// Where the config is loaded from when no path is given on the command line: the
+    /// `TORI_CONFIG` environment variable if it's set, or `$CONFIG_DIR/tori.yaml` otherwise
+    pub fn default_path() -> Result<PathBuf> {
+        config_path_from(std::env::var_os("TORI_CONFIG"))
+    }
+
+    /// The `gapless_audio` option, or `None` if mpv wouldn't accept it
+    pub fn valid_gapless_audio(&self) -> Option<&str> {
+        let value = self.gapless_audio.as_str();
+        GAPLESS_AUDIO_VALUES.contains(&value).then_some(value)
     }
 
     pub fn playlist_path(playlist_name: &str) -> PathBuf {
         PathBuf::from(&Config::global().playlists_dir).join(format!("{}.m3u8", playlist_name))
     }
 
-    pub fn merge(mut self, other: OptionalConfig) -> Self {
+    pub fn merge(mut self, other: OptionalConfig) -> Result<Self> {
         if let Some(playlists_dir) = other.playlists_dir {
             self.playlists_dir = playlists_dir;
         }
 
         if let Some(keybindings) = other.keybindings {
-            for (k, v) in keybindings.0 {
-                self.keybindings.0.insert(k, v);
-            }
+            self.keybindings.extend(keybindings);
         }
 
         if let Some(visualizer_gradient) = other.visualizer_gradient {
             let color_at = |i: usize| {
-                visualizer_gradient[i].to_rgb().unwrap_or_else(|| {
-                    eprintln!(
+                visualizer_gradient[i].to_rgb().ok_or_else(|| {
+                    format!(
                         "Your tori.yaml configuration file has an invalid color in visualizer_gradient: {:?}",
                         visualizer_gradient[i]
-                    );
-                    std::process::exit(1);
+                    )
                 })
             };
-            self.visualizer_gradient = [color_at(0), color_at(1)];
+            self.visualizer_gradient = [color_at(0)?, color_at(1)?];
         }
 
         self.mpv_ao = other.mpv_ao;
+        self.scrobble = other.scrobble;
+        self.discord_client_id = other.discord_client_id;
+
+        if let Some(gapless_audio) = other.gapless_audio {
+            self.gapless_audio = gapless_audio;
+        }
+
+        if let Some(seek_seconds) = other.seek_seconds {
+            self.seek_seconds = seek_seconds;
+        }
+
+        if let Some(probe_durations) = other.probe_durations {
+            self.probe_durations = probe_durations;
+        }
+
+        if let Some(filter_mode) = other.filter_mode {
+            self.filter_mode = filter_mode;
+        }
+
+        if let Some(undo_depth) = other.undo_depth {
+            self.undo_depth = undo_depth;
+        }
+
+        if let Some(import_recursive) = other.import_recursive {
+            self.import_recursive = import_recursive;
+        }
+
+        if let Some(double_click_ms) = other.double_click_ms {
+            self.double_click_ms = double_click_ms;
+        }
+
+        if let Some(sort_keys) = other.sort_keys {
+            self.sort_keys = sort_keys;
+        }
+
+        if let Some(song_columns) = other.song_columns {
+            self.song_columns = song_columns;
+        }
+
+        if let Some(wrap_navigation) = other.wrap_navigation {
+            self.wrap_navigation = wrap_navigation;
+        }
+
+        if let Some(marquee) = other.marquee {
+            self.marquee = marquee;
+        }
+
+        if let Some(album_art) = other.album_art {
+            self.album_art = album_art;
+        }
+
+        if let Some(resume_playback) = other.resume_playback {
+            self.resume_playback = resume_playback;
+        }
+
+        if let Some(notification_timeout_secs) = other.notification_timeout_secs {
+            self.notification_timeout_secs = notification_timeout_secs;
+        }
+
+        if let Some(key_repeat_ms) = other.key_repeat_ms {
+            self.key_repeat_ms = key_repeat_ms;
+        }
+
+        if let Some(eq_presets) = other.eq_presets {
+            self.eq_presets = eq_presets;
+        }
+
+        if let Some(expand_nested_playlists) = other.expand_nested_playlists {
+            self.expand_nested_playlists = expand_nested_playlists;
+        }
 
-        self
+        if let Some(confirm_quit) = other.confirm_quit {
+            self.confirm_quit = confirm_quit;
+        }
+
+        if let Some(scrollbar_symbol) = other.scrollbar_symbol {
+            self.scrollbar_symbol = scrollbar_symbol;
+        }
+
+        if let Some(scrollbar_color) = other.scrollbar_color {
+            let rgb = scrollbar_color.to_rgb().ok_or_else(|| {
+                format!(
+                    "Your tori.yaml configuration file has an invalid color in scrollbar_color: {:?}",
+                    scrollbar_color
+                )
+            })?;
+            self.scrollbar_color = Some(rgb);
+        }
+
+        if let Some(recent_songs) = other.recent_songs {
+            self.recent_songs = recent_songs;
+        }
+
+        if let Some(osd_color) = other.osd_color {
+            let rgb = osd_color.to_rgb().ok_or_else(|| {
+                format!(
+                    "Your tori.yaml configuration file has an invalid color in osd_color: {:?}",
+                    osd_color
+                )
+            })?;
+            self.osd_color = Some(rgb);
+        }
+
+        if let Some(mpv_options) = other.mpv_options {
+            self.mpv_options = mpv_options;
+        }
+
+        if let Some(compact) = other.compact {
+            self.compact = compact;
+        }
+
+        Ok(self)
+    }
+}
+
+fn config_path_from(tori_config: Option<OsString>) -> Result<PathBuf> {
+    match tori_config {
+        Some(path) => {
+            let path = PathBuf::from(path);
+            if !path.is_file() {
+                return Err(format!(
+                    "TORI_CONFIG is set to {}, but there's no such file",
+                    path.display()
+                )
+                .into());
+            }
+            Ok(path)
+        }
+        None => Ok(dirs::config_dir().unwrap_or_default().join("tori.yaml")),
     }
 }
 
@@ -79,6 +301,59 @@ impl Default for Config {
     }
 }
 
+/// How the songs filter matches songs
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FilterMode {
+    /// Case-insensitive substring search
+    #[default]
+    Substring,
+    /// fzf-like fuzzy search, sorted by best match
+    Fuzzy,
+    /// Case-insensitive regular expression search
+    Regex,
+}
+
+/// How songs are sorted in the songs pane
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SortingMethod {
+    /// Order of the songs in the playlist
+    #[default]
+    Index,
+    Title,
+    Duration,
+}
+
+impl SortingMethod {
+    pub fn next(&self) -> Self {
+        use SortingMethod::*;
+        match self {
+            Index => Title,
+            Title => Duration,
+            Duration => Index,
+        }
+    }
+}
+
+/// A column of the songs table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SongColumn {
+    /// Position of the song in the playlist
+    Index,
+    Title,
+    Artist,
+    Duration,
+    /// Path or URL of the song
+    Path,
+}
+
+/// Last.fm credentials for scrobbling. Only used when tori is built with the `scrobble` feature.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScrobbleConfig {
+    pub api_key: String,
+    pub api_secret: String,
+    pub session_key: String,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum Color {
@@ -110,6 +385,32 @@ pub struct OptionalConfig {
     pub visualizer_gradient: Option<[Color; 2]>,
     pub keybindings: Option<Shortcuts>,
     pub mpv_ao: Option<String>,
+    pub gapless_audio: Option<String>,
+    pub seek_seconds: Option<f64>,
+    pub probe_durations: Option<bool>,
+    pub filter_mode: Option<FilterMode>,
+    pub undo_depth: Option<usize>,
+    pub import_recursive: Option<bool>,
+    pub double_click_ms: Option<u64>,
+    pub sort_keys: Option<Vec<SortingMethod>>,
+    pub song_columns: Option<Vec<SongColumn>>,
+    pub wrap_navigation: Option<bool>,
+    pub marquee: Option<bool>,
+    pub album_art: Option<bool>,
+    pub resume_playback: Option<bool>,
+    pub notification_timeout_secs: Option<u64>,
+    pub key_repeat_ms: Option<HashMap<Command, u64>>,
+    pub eq_presets: Option<BTreeMap<String, String>>,
+    pub expand_nested_playlists: Option<bool>,
+    pub confirm_quit: Option<bool>,
+    pub scrollbar_symbol: Option<String>,
+    pub scrollbar_color: Option<Color>,
+    pub recent_songs: Option<usize>,
+    pub osd_color: Option<Color>,
+    pub mpv_options: Option<HashMap<String, String>>,
+    pub compact: Option<bool>,
+    pub scrobble: Option<ScrobbleConfig>,
+    pub discord_client_id: Option<String>,
 }
 
 impl OptionalConfig {
@@ -124,3 +425,52 @@ impl OptionalConfig {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_path_from_env() {
+        assert!(config_path_from(None).unwrap().ends_with("tori.yaml"));
+        assert!(config_path_from(Some("/this/config/does/not/exist.yaml".into())).is_err());
+
+        let path = std::env::temp_dir().join(format!("tori-config-{}.yaml", std::process::id()));
+        std::fs::write(&path, "").unwrap();
+        assert_eq!(
+            config_path_from(Some(path.clone().into())).ok(),
+            Some(path.clone())
+        );
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_valid_gapless_audio() {
+        let mut config = Config::default();
+        assert_eq!(config.valid_gapless_audio(), Some("weak"));
+        config.gapless_audio = "crossfade".into();
+        assert_eq!(config.valid_gapless_audio(), None);
+    }
+
+    #[test]
+    fn test_merge_scrollbar_color() {
+        let merge = |yaml: &str| Config::default().merge(serde_yaml::from_str(yaml).unwrap());
+
+        assert_eq!(Config::default().scrollbar_color, None);
+        let config = merge("scrollbar_color: \"#ffa500\"").unwrap();
+        assert_eq!(config.scrollbar_color, Some((255, 165, 0)));
+        let config = merge("scrollbar_color: [1, 2, 3]").unwrap();
+        assert_eq!(config.scrollbar_color, Some((1, 2, 3)));
+        assert!(merge("scrollbar_color: orange").is_err());
+    }
+
+    #[test]
+    fn test_merge_osd_color() {
+        let merge = |yaml: &str| Config::default().merge(serde_yaml::from_str(yaml).unwrap());
+
+        assert_eq!(Config::default().osd_color, None);
+        let config = merge("osd_color: \"#00ff00\"").unwrap();
+        assert_eq!(config.osd_color, Some((0, 255, 0)));
+        assert!(merge("osd_color: green").is_err());
+    }
+}
@@ -1,7 +1,12 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt};
 
 use crossterm::event::{KeyCode, KeyModifiers};
-use serde::{Deserialize, Serialize};
+use serde::{
+    de::{MapAccess, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use crate::command::Command;
 
 /// Encapsulates a string representing some key event.
 ///
@@ -72,21 +77,246 @@ impl From<crossterm::event::KeyEvent> for InputStr {
     }
 }
 
-/// Stores a table of [Command](crate::command::Command) shortcuts.
-#[derive(Debug, Default, Serialize, Deserialize)]
-pub struct Shortcuts(pub HashMap<InputStr, crate::command::Command>);
+impl InputStr {
+    /// Joins the keys of a chord, so `["g", "g"]` becomes `"g g"`
+    pub fn chord(keys: &[InputStr]) -> Self {
+        let keys: Vec<_> = keys.iter().map(|k| k.0.as_str()).collect();
+        InputStr(keys.join(" "))
+    }
+
+    /// Splits a chord like `"g g"` into its keys. Returns `None` if some key couldn't be produced
+    /// by [InputStr::from]: modifiers must come in the same order as [InputStr::from] writes them,
+    /// and chars don't take `S-`.
+    pub fn keys(&self) -> Option<Vec<&str>> {
+        let mut keys = Vec::new();
+        let mut rest = self.0.as_str();
+        loop {
+            let (key, after) = split_key(rest)?;
+            keys.push(key);
+            match after.strip_prefix(' ') {
+                None => return Some(keys),
+                Some(next) if !next.is_empty() => rest = next,
+                Some(_) => return None,
+            }
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.keys().is_some()
+    }
+}
+
+/// Splits the first key off a chord. The rest is either empty or starts with the space that
+/// separates keys.
+fn split_key(s: &str) -> Option<(&str, &str)> {
+    let mut key = s;
+    let mut shift = false;
+    if let Some(rest) = key.strip_prefix("C-") {
+        key = rest;
+    }
+    if let Some(rest) = key.strip_prefix("S-") {
+        key = rest;
+        shift = true;
+    }
+    if let Some(rest) = key.strip_prefix("A-") {
+        key = rest;
+    }
+    let modifiers_len = s.len() - key.len();
+
+    let name = &key[..key.find(' ').unwrap_or(key.len())];
+    let is_named = NAMED_KEYS.contains(&name)
+        || name
+            .strip_prefix("f(")
+            .and_then(|n| n.strip_suffix(')'))
+            .map_or(false, |n| n.parse::<u8>().is_ok());
+
+    let len = match key.chars().next() {
+        _ if is_named => name.len(),
+        Some(c) if !shift => c.len_utf8(),
+        _ => return None,
+    };
+
+    let rest = &key[len..];
+    if !rest.is_empty() && !rest.starts_with(' ') {
+        return None;
+    }
+    Some((&s[..modifiers_len + len], rest))
+}
+
+/// Names of the non-char keys, as written by [InputStr::from]
+const NAMED_KEYS: &[&str] = &[
+    "backspace",
+    "enter",
+    "left",
+    "right",
+    "up",
+    "down",
+    "home",
+    "end",
+    "pageup",
+    "pagedown",
+    "tab",
+    "backtab",
+    "delete",
+    "insert",
+    "null",
+    "esc",
+    "capslock",
+    "scrolllock",
+    "numlock",
+    "printscreen",
+    "pause",
+    "menu",
+    "keypadbegin",
+];
+
+/// A problem found by [Shortcuts::validate]
+#[derive(Debug, Clone, PartialEq)]
+pub enum ShortcutError {
+    /// The key is bound more than once. Only the last command is used.
+    Conflict {
+        key: InputStr,
+        commands: Vec<Command>,
+    },
+    /// The key string doesn't name any key, so the binding can never be triggered
+    Unrecognized(InputStr),
+}
+
+impl fmt::Display for ShortcutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShortcutError::Conflict { key, commands } => {
+                let commands: Vec<_> = commands.iter().map(|c| format!("{:?}", c)).collect();
+                write!(f, "'{}' is bound to {}", key.0, commands.join(", "))
+            }
+            ShortcutError::Unrecognized(key) => write!(f, "'{}' is not a recognized key", key.0),
+        }
+    }
+}
+
+/// The result of looking up a sequence of keys with [Shortcuts::lookup]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Lookup {
+    /// No binding starts with these keys
+    NotFound,
+    /// The keys are bound to a command
+    Found(Command),
+    /// The keys are the start of a longer chord. If they're also bound to a command by themselves,
+    /// it should only fire if no other key comes in time.
+    Prefix(Option<Command>),
+}
+
+/// Stores a table of [Command](crate::command::Command) shortcuts. A shortcut can also be a chord
+/// of keys separated by spaces, like `g g`.
+#[derive(Debug, Default)]
+pub struct Shortcuts(
+    pub HashMap<InputStr, Command>,
+    /// Bindings that were shadowed by a later binding of the same key in the same file
+    Vec<(InputStr, Command)>,
+);
 
 impl Shortcuts {
-    pub fn new(map: HashMap<InputStr, crate::command::Command>) -> Self {
-        Self(map)
+    pub fn new(map: HashMap<InputStr, Command>) -> Self {
+        Self(map, Vec::new())
     }
 
-    pub fn get_from_event(
-        &self,
-        event: crossterm::event::KeyEvent,
-    ) -> Option<crate::command::Command> {
+    pub fn get_from_event(&self, event: crossterm::event::KeyEvent) -> Option<Command> {
         self.0.get(&event.into()).cloned()
     }
+
+    /// Looks up the command bound to a sequence of keys
+    pub fn lookup(&self, keys: &[InputStr]) -> Lookup {
+        let chord = InputStr::chord(keys);
+        let prefix = format!("{} ", chord.0);
+        let exact = self.0.get(&chord).copied();
+
+        if self.0.keys().any(|k| k.0.starts_with(&prefix)) {
+            Lookup::Prefix(exact)
+        } else {
+            exact.map_or(Lookup::NotFound, Lookup::Found)
+        }
+    }
+
+    /// Lists every key bound to `command`, sorted
+    pub fn keys_for(&self, command: Command) -> Vec<&InputStr> {
+        let mut keys: Vec<_> = self
+            .0
+            .iter()
+            .filter(|(_, &c)| c == command)
+            .map(|(key, _)| key)
+            .collect();
+        keys.sort_unstable();
+        keys
+    }
+
+    /// Adds the bindings of `other`, replacing the ones bound to the same keys
+    pub fn extend(&mut self, other: Shortcuts) {
+        self.0.extend(other.0);
+        self.1.extend(other.1);
+    }
+
+    /// Lists the keys that were bound more than once and the keys that aren't recognized
+    pub fn validate(&self) -> Vec<ShortcutError> {
+        let mut conflicts: HashMap<&InputStr, Vec<Command>> = HashMap::new();
+        for (key, cmd) in &self.1 {
+            conflicts.entry(key).or_default().push(*cmd);
+        }
+
+        let mut errors: Vec<_> = conflicts
+            .into_iter()
+            .map(|(key, mut commands)| {
+                commands.extend(self.0.get(key));
+                ShortcutError::Conflict {
+                    key: key.clone(),
+                    commands,
+                }
+            })
+            .collect();
+
+        errors.extend(
+            self.0
+                .keys()
+                .filter(|key| !key.is_valid())
+                .map(|key| ShortcutError::Unrecognized(key.clone())),
+        );
+
+        errors.sort_by_key(ToString::to_string);
+        errors
+    }
+}
+
+impl Serialize for Shortcuts {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Shortcuts {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(ShortcutsVisitor)
+    }
+}
+
+/// Deserializes [Shortcuts] while keeping track of keys that are bound more than once, which a
+/// plain HashMap would silently overwrite
+struct ShortcutsVisitor;
+
+impl<'de> Visitor<'de> for ShortcutsVisitor {
+    type Value = Shortcuts;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a map from keys to commands")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut access: A) -> Result<Self::Value, A::Error> {
+        let mut shortcuts = Shortcuts::default();
+        while let Some((key, cmd)) = access.next_entry::<InputStr, Command>()? {
+            if let Some(shadowed) = shortcuts.0.insert(key.clone(), cmd) {
+                shortcuts.1.push((key, shadowed));
+            }
+        }
+        Ok(shortcuts)
+    }
 }
 
 #[cfg(test)]
@@ -137,4 +367,115 @@ mod tests {
             InputStr("C-S-tab".into())
         );
     }
+
+    #[test]
+    fn test_validate_conflicts() {
+        let yaml = "q: Quit\nn: NextSong\nq: TogglePause\n";
+        let shortcuts: Shortcuts = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(
+            shortcuts.validate(),
+            vec![ShortcutError::Conflict {
+                key: InputStr("q".into()),
+                commands: vec![Command::Quit, Command::TogglePause],
+            }]
+        );
+        assert_eq!(
+            shortcuts.get_from_event(key_event(KeyModifiers::NONE, KeyCode::Char('q'))),
+            Some(Command::TogglePause)
+        );
+    }
+
+    #[test]
+    fn test_validate_unrecognized() {
+        let yaml = "C-S-tab: Quit\nf(5): Quit\nS-a: Quit\nctrl-a: Quit\nA-: Quit\nEnter: Quit\n\
+                    g g: Quit\nC-  up: Quit\ngg: Quit\n'g ': Quit\n";
+        let shortcuts: Shortcuts = serde_yaml::from_str(yaml).unwrap();
+        let mut unrecognized: Vec<_> = shortcuts
+            .validate()
+            .into_iter()
+            .map(|e| match e {
+                ShortcutError::Unrecognized(key) => key.0,
+                other => panic!("unexpected error: {}", other),
+            })
+            .collect();
+        unrecognized.sort();
+        assert_eq!(
+            unrecognized,
+            vec!["A-", "Enter", "S-a", "ctrl-a", "g ", "gg"]
+        );
+    }
+
+    #[test]
+    fn test_default_shortcuts_are_valid() {
+        assert_eq!(
+            crate::config::Config::default().keybindings.validate(),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_lookup_chords() {
+        let yaml = "g: SelectNext\ng g: SelectPrev\nq: Quit\nC-w l: Nop\n";
+        let shortcuts: Shortcuts = serde_yaml::from_str(yaml).unwrap();
+        let keys = |s: &str| -> Vec<_> { s.split(',').map(|k| InputStr(k.into())).collect() };
+
+        assert_eq!(shortcuts.lookup(&keys("q")), Lookup::Found(Command::Quit));
+        assert_eq!(
+            shortcuts.lookup(&keys("g")),
+            Lookup::Prefix(Some(Command::SelectNext))
+        );
+        assert_eq!(
+            shortcuts.lookup(&keys("g,g")),
+            Lookup::Found(Command::SelectPrev)
+        );
+        assert_eq!(shortcuts.lookup(&keys("C-w")), Lookup::Prefix(None));
+        assert_eq!(
+            shortcuts.lookup(&keys("C-w,l")),
+            Lookup::Found(Command::Nop)
+        );
+        assert_eq!(shortcuts.lookup(&keys("g,q")), Lookup::NotFound);
+        assert_eq!(shortcuts.lookup(&keys("x")), Lookup::NotFound);
+    }
+
+    #[test]
+    fn test_keys_for() {
+        let yaml = "j: SelectNext
+down: SelectNext
+C-n: SelectNext
+k: SelectPrev
+";
+        let shortcuts: Shortcuts = serde_yaml::from_str(yaml).unwrap();
+        let keys_for = |command| -> Vec<_> {
+            shortcuts
+                .keys_for(command)
+                .into_iter()
+                .map(|k| k.0.as_str())
+                .collect()
+        };
+
+        assert_eq!(keys_for(Command::SelectNext), vec!["C-n", "down", "j"]);
+        assert_eq!(keys_for(Command::SelectPrev), vec!["k"]);
+        assert!(keys_for(Command::Quit).is_empty());
+        assert_eq!(shortcuts.validate(), vec![]);
+
+        // Every key still triggers the command
+        for code in [KeyCode::Char('j'), KeyCode::Down] {
+            assert_eq!(
+                shortcuts.get_from_event(key_event(KeyModifiers::NONE, code)),
+                Some(Command::SelectNext)
+            );
+        }
+
+        // Rebinding one of the keys in a user config leaves the others alone
+        let mut shortcuts = shortcuts;
+        shortcuts.extend(
+            serde_yaml::from_str(
+                "down: Quit
+",
+            )
+            .unwrap(),
+        );
+        let keys: Vec<_> = shortcuts.keys_for(Command::SelectNext);
+        assert_eq!(keys, vec![&InputStr("C-n".into()), &InputStr("j".into())]);
+    }
 }
@@ -0,0 +1,133 @@
+//! [Discord Rich Presence](https://discord.com/developers/docs/rich-presence/overview) integration,
+//! so the song being played shows up in the user's Discord status.
+//!
+//! The app calls [Presence::update] every second. If Discord isn't running, nothing happens, and
+//! the connection is retried on the next update.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use discord_rich_presence::{
+    activity::{Activity, ActivityType, Timestamps},
+    DiscordIpc, DiscordIpcClient,
+};
+
+use crate::player::PlayerState;
+
+/// How many seconds the computed start of a song may drift before the presence is updated. The
+/// start is computed from the current position, so it's off by a second every now and then.
+const START_TOLERANCE: i64 = 2;
+
+/// What's shown in the Discord status
+#[derive(Debug, Clone, PartialEq)]
+struct Shown {
+    title: String,
+    paused: bool,
+    /// When the song started, in seconds since the unix epoch
+    start: i64,
+    /// Duration of the song, in seconds
+    duration: i64,
+}
+
+impl Shown {
+    fn from_state(state: &PlayerState) -> Option<Self> {
+        if state.idle || state.media_title.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            title: state.media_title.clone(),
+            paused: state.paused,
+            start: unix_time() - state.time_pos,
+            duration: state.duration(),
+        })
+    }
+
+    /// Whether `other` looks the same in Discord, so there's no need to update it
+    fn same_as(&self, other: &Shown) -> bool {
+        self.title == other.title
+            && self.paused == other.paused
+            && self.duration == other.duration
+            && (self.start - other.start).abs() <= START_TOLERANCE
+    }
+
+    fn activity(&self) -> Activity<'_> {
+        let activity = Activity::new()
+            .activity_type(ActivityType::Listening)
+            .details(self.title.as_str());
+
+        if self.paused {
+            return activity.state("Paused");
+        }
+
+        let mut timestamps = Timestamps::new().start(self.start * 1000);
+        if self.duration > 0 {
+            timestamps = timestamps.end((self.start + self.duration) * 1000);
+        }
+        activity.timestamps(timestamps)
+    }
+}
+
+pub struct Presence {
+    client: DiscordIpcClient,
+    connected: bool,
+    /// What was last sent to Discord. `None` if the presence is clear.
+    shown: Option<Shown>,
+}
+
+impl Presence {
+    /// `client_id` is the id of the Discord application shown as the activity's name
+    pub fn new(client_id: &str) -> Self {
+        Self {
+            client: DiscordIpcClient::new(client_id),
+            connected: false,
+            shown: None,
+        }
+    }
+
+    /// Shows the song being played, or clears the presence if nothing is playing
+    pub fn update(&mut self, state: &PlayerState) {
+        if !self.connected {
+            if self.client.connect().is_err() {
+                return;
+            }
+            self.connected = true;
+            self.shown = None;
+        }
+
+        let next = Shown::from_state(state);
+        let unchanged = match (&self.shown, &next) {
+            (Some(shown), Some(next)) => shown.same_as(next),
+            (None, None) => true,
+            _ => false,
+        };
+        if unchanged {
+            return;
+        }
+
+        let result = match &next {
+            Some(next) => self.client.set_activity(next.activity()),
+            None => self.client.clear_activity(),
+        };
+
+        match result {
+            Ok(()) => self.shown = next,
+            // Discord was probably closed, so try to connect again on the next update
+            Err(_) => self.connected = false,
+        }
+    }
+}
+
+impl Drop for Presence {
+    fn drop(&mut self) {
+        if self.connected {
+            self.client.close().ok();
+        }
+    }
+}
+
+fn unix_time() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default()
+}
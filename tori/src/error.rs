@@ -1,2 +1,102 @@
-pub type Error = Box<dyn std::error::Error>;
+//! tori's error type. Most errors are only shown to the user in a notification, but the variants
+//! let callers handle some failures differently, e.g. a playlist that doesn't parse.
+
+use std::{fmt, io};
+
+use crate::m3u::parser::ParserError;
+
+#[derive(Debug)]
+pub enum Error {
+    /// Reading or writing a file, spawning a process, or talking to the terminal
+    Io(io::Error),
+    /// A playlist file couldn't be parsed
+    M3u(ParserError),
+    /// The player failed to do something
+    #[cfg(feature = "mpv")]
+    Mpv(crate::player::MpvError),
+    /// The clipboard couldn't be read or written
+    Clipboard(String),
+    /// Anything else, with a message meant for the user
+    Other(String),
+}
+
 pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => e.fmt(f),
+            Error::M3u(e) => e.fmt(f),
+            #[cfg(feature = "mpv")]
+            Error::Mpv(e) => write!(f, "mpv error: {}", e),
+            Error::Clipboard(e) => write!(f, "Clipboard error: {}", e),
+            Error::Other(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::M3u(e) => Some(e),
+            #[cfg(feature = "mpv")]
+            Error::Mpv(e) => Some(e),
+            Error::Clipboard(_) | Error::Other(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<ParserError> for Error {
+    fn from(e: ParserError) -> Self {
+        match e {
+            ParserError::Io(e) => Error::Io(e),
+            e => Error::M3u(e),
+        }
+    }
+}
+
+#[cfg(feature = "mpv")]
+impl From<crate::player::MpvError> for Error {
+    fn from(e: crate::player::MpvError) -> Self {
+        Error::Mpv(e)
+    }
+}
+
+impl From<String> for Error {
+    fn from(e: String) -> Self {
+        Error::Other(e)
+    }
+}
+
+impl From<&str> for Error {
+    fn from(e: &str) -> Self {
+        Error::Other(e.to_string())
+    }
+}
+
+impl From<serde_yaml::Error> for Error {
+    fn from(e: serde_yaml::Error) -> Self {
+        Error::Other(e.to_string())
+    }
+}
+
+#[cfg(all(feature = "mpris", target_os = "linux"))]
+impl From<zbus::Error> for Error {
+    fn from(e: zbus::Error) -> Self {
+        Error::Other(format!("D-Bus error: {}", e))
+    }
+}
+
+/// Errors from other libraries that only give us a message, like tori-player
+impl From<Box<dyn std::error::Error>> for Error {
+    fn from(e: Box<dyn std::error::Error>) -> Self {
+        Error::Other(e.to_string())
+    }
+}
@@ -1,4 +1,5 @@
 use crossterm::event::Event as CrosstermEvent;
+use std::path::PathBuf;
 use std::sync::{mpsc, Arc, Mutex};
 use std::time;
 use std::{
@@ -7,14 +8,37 @@ use std::{
 };
 
 use super::command::Command;
+use super::config::Config;
+use super::m3u::LoadedSongs;
 
 #[derive(Debug, Clone)]
 pub enum Event {
     SecondTick,
-    SongAdded { playlist: String, song: String },
+    SongAdded {
+        playlist: String,
+        song: String,
+    },
     ChangedPlaylist,
     Command(Command),
     Terminal(CrosstermEvent),
+    /// The config file changed and was reloaded, or failed to reload with some error message
+    ConfigReloaded(Result<(), String>),
+    /// A request to Last.fm failed with some error message
+    ScrobbleFailed(String),
+    /// tori received SIGINT or SIGTERM, and should quit right away
+    Terminate,
+    /// A song started playing and was added to the recently played songs
+    RecentSongsChanged,
+    /// A playlist being loaded in the background has read this many songs so far
+    PlaylistLoading {
+        id: u64,
+        count: usize,
+    },
+    /// A playlist finished loading in the background, or failed to with some error message
+    PlaylistLoaded {
+        id: u64,
+        result: Result<LoadedSongs, String>,
+    },
 }
 
 pub struct Channel {
@@ -76,6 +100,67 @@ impl Channel {
         })
     }
 
+    /// Turns SIGINT and SIGTERM into [Event::Terminate], so that tori goes through its usual
+    /// shutdown and restores the terminal instead of being killed in raw mode. If tori is stuck
+    /// and doesn't quit, a second signal kills it right away, leaving the terminal as it is.
+    #[cfg(unix)]
+    pub fn spawn_signal_handler(&self) -> std::io::Result<thread::JoinHandle<()>> {
+        use signal_hook::{
+            consts::{SIGINT, SIGTERM},
+            flag,
+            iterator::Signals,
+        };
+        use std::sync::atomic::AtomicBool;
+
+        let signalled = Arc::new(AtomicBool::new(false));
+        for signal in [SIGINT, SIGTERM] {
+            // The shutdown is registered first, so that it only sees the flag set by an earlier
+            // signal
+            flag::register_conditional_shutdown(signal, 1, signalled.clone())?;
+            flag::register(signal, signalled.clone())?;
+        }
+
+        let mut signals = Signals::new([SIGINT, SIGTERM])?;
+        let sender = self.sender.clone();
+        Ok(thread::spawn(move || {
+            for _ in signals.forever() {
+                if sender.send(Event::Terminate).is_err() {
+                    return;
+                }
+            }
+        }))
+    }
+
+    /// Polls the config file for changes every second, and reloads the global config when it
+    /// changes. If the new config is invalid, the previous one stays active.
+    pub fn spawn_config_watcher(&self, path: PathBuf) -> thread::JoinHandle<()> {
+        let sender = self.sender.clone();
+        let modified = |path: &PathBuf| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+        thread::spawn(move || {
+            let mut last_modified = modified(&path);
+            loop {
+                thread::sleep(time::Duration::from_secs(1));
+                let current = modified(&path);
+                if current == last_modified {
+                    continue;
+                }
+                last_modified = current;
+
+                let result = match Config::load(&path) {
+                    Ok(config) => {
+                        Config::set_global(config);
+                        Ok(())
+                    }
+                    Err(e) => Err(e.to_string()),
+                };
+
+                if sender.send(Event::ConfigReloaded(result)).is_err() {
+                    return;
+                }
+            }
+        })
+    }
+
     pub fn send(&mut self, event: Event) -> Result<(), mpsc::SendError<Event>> {
         self.sender.send(event)
     }
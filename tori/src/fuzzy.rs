@@ -0,0 +1,105 @@
+//! A small fzf-like fuzzy matcher.
+
+/// Bonus for a matched char right after the previous matched char
+const CONSECUTIVE_BONUS: i64 = 8;
+/// Bonus for a matched char at the start of a word
+const WORD_START_BONUS: i64 = 6;
+/// Penalty for every skipped char between two matched chars
+const GAP_PENALTY: i64 = 1;
+/// Score of every matched char
+const MATCH_SCORE: i64 = 4;
+
+/// Scores how well `pattern` fuzzy-matches `text`, ignoring case. The chars of `pattern` must
+/// appear in `text` in the same order, but not necessarily next to each other. Returns `None` if
+/// there's no match, and the higher the score, the better the match.
+///
+/// Chars are matched greedily, so the score isn't always the best possible one, but it's good
+/// enough for sorting a filtered list.
+pub fn score(pattern: &str, text: &str) -> Option<i64> {
+    let mut pattern = pattern.chars().flat_map(char::to_lowercase).peekable();
+    let mut score = 0;
+    let mut last_match: Option<usize> = None;
+    let mut prev_char: Option<char> = None;
+
+    for (i, c) in text.chars().flat_map(char::to_lowercase).enumerate() {
+        let Some(&p) = pattern.peek() else {
+            break;
+        };
+
+        if c == p {
+            score += MATCH_SCORE;
+            match last_match {
+                Some(j) if j + 1 == i => score += CONSECUTIVE_BONUS,
+                Some(j) => score -= GAP_PENALTY * (i - j - 1) as i64,
+                None => {}
+            }
+            if !prev_char.map_or(false, char::is_alphanumeric) {
+                score += WORD_START_BONUS;
+            }
+            last_match = Some(i);
+            pattern.next();
+        }
+        prev_char = Some(c);
+    }
+
+    match pattern.peek() {
+        Some(_) => None,
+        None => Some(score),
+    }
+}
+
+/// Returns the indices of the chars of `text` matched by `pattern`, picked greedily like in
+/// [score], or `None` if there's no match. Useful for highlighting matches.
+pub fn matched_indices(pattern: &str, text: &str) -> Option<Vec<usize>> {
+    let mut pattern = pattern.chars().flat_map(char::to_lowercase).peekable();
+    let mut indices = Vec::new();
+
+    for (i, c) in text.chars().enumerate() {
+        let Some(&p) = pattern.peek() else {
+            break;
+        };
+
+        if c.to_lowercase().next() == Some(p) {
+            indices.push(i);
+            pattern.next();
+        }
+    }
+
+    match pattern.peek() {
+        Some(_) => None,
+        None => Some(indices),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches() {
+        assert!(score("bhrap", "Bohemian Rhapsody").is_some());
+        assert!(score("", "anything").is_some());
+        assert!(score("QUEEN", "queen - bohemian rhapsody").is_some());
+        assert!(score("rhapb", "Bohemian Rhapsody").is_none());
+        assert!(score("xyz", "Bohemian Rhapsody").is_none());
+    }
+
+    #[test]
+    fn test_ranking() {
+        let substring = score("rhap", "Bohemian Rhapsody").unwrap();
+        let scattered = score("rhap", "Rock Hard And Proud").unwrap();
+        let spread = score("rhap", "rather heavy ambient pop").unwrap();
+        assert!(substring > spread);
+        assert!(scattered > spread);
+    }
+
+    #[test]
+    fn test_matched_indices() {
+        assert_eq!(
+            matched_indices("bhr", "Bohemian Rhapsody"),
+            Some(vec![0, 2, 9])
+        );
+        assert_eq!(matched_indices("", "anything"), Some(vec![]));
+        assert_eq!(matched_indices("rhapb", "Bohemian Rhapsody"), None);
+    }
+}
@@ -1,15 +1,27 @@
 #![doc = include_str!("../README.md")]
 
+#[cfg(feature = "album-art")]
+pub mod album_art;
 pub mod app;
 pub mod command;
 pub mod config;
+#[cfg(feature = "discord")]
+pub mod discord;
 pub mod error;
 pub mod m3u;
+#[cfg(all(feature = "mpris", target_os = "linux"))]
+pub mod mpris;
 pub mod player;
+#[cfg(feature = "scrobble")]
+pub mod scrobble;
 pub mod visualizer;
 
 mod dbglog;
 mod events;
+mod fuzzy;
+mod recent;
 mod rect_ops;
+mod resume;
+mod session;
 mod util;
 mod widgets;
@@ -0,0 +1,79 @@
+//! Conversion of playlists to other formats
+
+use std::{fmt::Write, fs, path::PathBuf};
+
+use super::{Parser, Song};
+use crate::{config::Config, error::Result};
+
+/// Writes songs in the [PLS](https://en.wikipedia.org/wiki/PLS_(file_format)) format
+pub fn to_pls(songs: &[Song]) -> String {
+    let mut pls = String::from("[playlist]\n");
+    for (i, song) in songs.iter().enumerate() {
+        let n = i + 1;
+        // write! to a String can't fail
+        writeln!(pls, "File{}={}", n, song.path).unwrap();
+        writeln!(pls, "Title{}={}", n, song.full_title()).unwrap();
+        // PLS uses -1 for songs of unknown length, like streams
+        match song.duration.as_secs() {
+            0 => writeln!(pls, "Length{}=-1", n).unwrap(),
+            secs => writeln!(pls, "Length{}={}", n, secs).unwrap(),
+        }
+    }
+    writeln!(pls, "NumberOfEntries={}", songs.len()).unwrap();
+    pls.push_str("Version=2\n");
+    pls
+}
+
+/// Writes a copy of the playlist in the PLS format, next to the original one. Returns the path of
+/// the new file.
+pub fn export_pls(playlist_name: &str) -> Result<PathBuf> {
+    let path = Config::playlist_path(playlist_name);
+    let songs = Parser::from_path(&path)?.all_songs()?;
+
+    let pls_path = path.with_extension("pls");
+    fs::write(&pls_path, to_pls(&songs))?;
+    Ok(pls_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_pls_round_trip() {
+        let m3u = "#EXTM3U\n#EXTINF:61,Artist - Title\n/music/a.mp3\n#EXTINF:0,Stream\nhttps://example.com/b\n";
+        let songs = Parser::from_string(m3u).all_songs().unwrap();
+        let pls = to_pls(&songs);
+
+        // Read the songs back from the PLS entries
+        let value = |key: String| {
+            pls.lines()
+                .find_map(|line| line.strip_prefix(&key))
+                .unwrap()
+                .to_string()
+        };
+        let count: usize = value("NumberOfEntries=".into()).parse().unwrap();
+        let parsed: Vec<_> = (1..=count)
+            .map(|n| {
+                let title = value(format!("Title{}=", n));
+                // -1 is an unknown length
+                let length: i64 = value(format!("Length{}=", n)).parse().unwrap();
+                (
+                    value(format!("File{}=", n)),
+                    title,
+                    Duration::from_secs(length.max(0) as u64),
+                )
+            })
+            .collect();
+
+        let expected: Vec<_> = songs
+            .iter()
+            .map(|s| (s.path.clone(), s.full_title(), s.duration))
+            .collect();
+        assert_eq!(parsed, expected);
+        assert!(pls.contains("Length2=-1\n"));
+        assert!(pls.starts_with("[playlist]\n"));
+        assert!(pls.ends_with("Version=2\n"));
+    }
+}
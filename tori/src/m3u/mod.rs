@@ -1,5 +1,6 @@
 use std::io::{self, ErrorKind, Read, Seek, Write};
 
+use std::path::Path;
 use std::time::Duration;
 
 use crate::{config::Config, error::Result};
@@ -10,11 +11,54 @@ pub use stringreader::StringReader;
 pub mod parser;
 pub use parser::Parser;
 
+pub mod export;
 pub mod playlist_management;
 
+/// The contents of a playlist file
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Playlist {
+    /// Directives before the first song, like `#PLAYLIST:`, kept verbatim so they can be written
+    /// back
+    pub header: Vec<String>,
+    pub songs: Vec<Song>,
+}
+
+impl Playlist {
+    /// The title given by a `#PLAYLIST:` directive, if there's one
+    pub fn title(&self) -> Option<&str> {
+        self.header
+            .iter()
+            .find_map(|line| line.strip_prefix("#PLAYLIST:"))
+            .map(str::trim)
+    }
+
+    pub fn serialize(&self) -> String {
+        let mut s = String::from("#EXTM3U\n");
+        for line in &self.header {
+            s.push_str(line);
+            s.push('\n');
+        }
+        for song in &self.songs {
+            s.push_str(&song.serialize());
+        }
+        s
+    }
+}
+
+/// The songs of a playlist, read in the background and ready to be shown
+#[derive(Debug, Default, Clone)]
+pub struct LoadedSongs {
+    pub songs: Vec<Song>,
+    /// Whether some songs came from nested playlists
+    pub has_nested: bool,
+    /// Nested playlists that were left out because they include themselves or are nested too deep
+    pub skipped_nested: Vec<String>,
+}
+
 #[derive(Debug, Default, Clone, PartialEq)]
 pub struct Song {
     pub title: String,
+    pub artist: Option<String>,
     pub duration: Duration,
     pub path: String,
 }
@@ -52,13 +96,15 @@ impl Song {
             .into());
         }
 
-        let metadata: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+        let metadata: serde_json::Value = serde_json::from_slice(&output.stdout)
+            .map_err(|e| format!("Couldn't parse the output of yt-dlp: {}", e))?;
         let title = metadata["title"].as_str().unwrap_or("?").into();
         let duration = Duration::from_secs_f64(metadata["duration"].as_f64().unwrap_or(0.0));
         Ok(Song {
             title,
             duration,
             path: url.into(),
+            ..Default::default()
         })
     }
 
@@ -81,7 +127,7 @@ impl Song {
             Ok(tf) => Some(tf),
             Err(e) => match e.kind() {
                 UnknownFormat | NotAPicture | UnsupportedPicture | UnsupportedTag => None,
-                _ => return Err(e.into()),
+                _ => return Err(format!("Couldn't read the tags of {}: {}", path, e).into()),
             },
         };
 
@@ -89,13 +135,10 @@ impl Song {
             .as_ref()
             .and_then(|t| t.primary_tag().or(t.first_tag()));
 
-        let title = match (
-            tag.and_then(Accessor::artist),
-            tag.and_then(Accessor::title),
-        ) {
-            (Some(artist), Some(title)) => format!("{} - {}", artist, title),
-            (Some(artist), None) => format!("{} - ?", artist),
-            (None, Some(title)) => title.to_string(),
+        let artist = tag.and_then(Accessor::artist).map(|a| a.to_string());
+        let title = match (&artist, tag.and_then(Accessor::title)) {
+            (_, Some(title)) => title.to_string(),
+            (Some(_), None) => "?".into(),
             (None, None) => default_title(),
         };
 
@@ -105,14 +148,51 @@ impl Song {
 
         Ok(Song {
             title,
+            artist,
             duration,
             path: path.into(),
         })
     }
 
+    /// Reads the real duration of a local song file using lofty. Returns `None` if the path isn't
+    /// an existing file or if it can't be read.
+    pub fn probe_duration(path: &str) -> Option<Duration> {
+        use lofty::AudioFile;
+
+        if !std::path::Path::new(path).is_file() {
+            return None;
+        }
+
+        lofty::read_from_path(path)
+            .ok()
+            .map(|t| t.properties().duration())
+    }
+
+    /// Title in the "Artist - Title" format, or just the title if there's no artist
+    pub fn full_title(&self) -> String {
+        match &self.artist {
+            Some(artist) => format!("{} - {}", artist, self.title),
+            None => self.title.clone(),
+        }
+    }
+
+    /// Makes a relative local path absolute by joining it to `dir`, usually the directory of the
+    /// playlist the song is in. URLs are left as they are.
+    pub fn resolve_relative_to(&mut self, dir: &Path) {
+        if self.path.contains("://") || !Path::new(&self.path).is_relative() {
+            return;
+        }
+        self.path = dir.join(&self.path).to_string_lossy().into_owned();
+    }
+
     pub fn serialize(&self) -> String {
         let duration = self.duration.as_secs();
-        format!("#EXTINF:{},{}\n{}\n", duration, self.title, self.path)
+        format!(
+            "#EXTINF:{},{}\n{}\n",
+            duration,
+            self.full_title(),
+            self.path
+        )
     }
 
     pub fn add_to_playlist(&self, playlist_name: &str) -> Result<()> {
@@ -131,7 +211,7 @@ impl Song {
             Ok(()) => false,
             Err(e) if e.kind() == ErrorKind::UnexpectedEof => false,
             Err(e) => {
-                return Err(Box::new(e));
+                return Err(e.into());
             }
         };
 
@@ -152,7 +232,7 @@ impl Song {
             Ok(()) => false,
             Err(e) if e.kind() == ErrorKind::UnexpectedEof => false,
             Err(e) => {
-                return Err(Box::new(e));
+                return Err(e.into());
             }
         };
 
@@ -2,11 +2,12 @@ use std::{
     error::Error,
     fs,
     io::{self, BufRead, BufReader, Read},
+    path::{Path, PathBuf},
     time::Duration,
 };
 
-use super::Song;
 use super::StringReader;
+use super::{Playlist, Song};
 
 /////////////////////////
 //        Error        //
@@ -66,7 +67,12 @@ impl<R: Read> LineReader for BufReader<R> {
 pub struct Parser<L: LineReader> {
     reader: L,
     line_buf: Option<String>,
+    /// Length in bytes of the line in `line_buf`, including the newline
+    line_buf_bytes: usize,
+    /// Bytes consumed so far
     cursor: usize,
+    /// Directives found by [Parser::next_header], or `None` if it wasn't called yet
+    header: Option<Vec<String>>,
 }
 
 impl Parser<BufReader<fs::File>> {
@@ -74,7 +80,9 @@ impl Parser<BufReader<fs::File>> {
         Self {
             reader: BufReader::new(reader),
             line_buf: None,
+            line_buf_bytes: 0,
             cursor: 0,
+            header: None,
         }
     }
 
@@ -89,7 +97,9 @@ impl<'s> Parser<StringReader<'s>> {
         Self {
             reader: StringReader::new(s),
             line_buf: None,
+            line_buf_bytes: 0,
             cursor: 0,
+            header: None,
         }
     }
 }
@@ -99,7 +109,9 @@ impl<R: Read> Parser<BufReader<R>> {
         Self {
             reader: BufReader::new(reader),
             line_buf: None,
+            line_buf_bytes: 0,
             cursor: 0,
+            header: None,
         }
     }
 }
@@ -116,12 +128,19 @@ impl<L: LineReader> Parser<L> {
                 return Ok(None);
             }
 
-            self.cursor += bytes;
+            self.line_buf_bytes = bytes;
 
             let is_nl = |c| c == Some(b'\n') || c == Some(b'\r');
             while is_nl(line.as_bytes().last().copied()) {
                 line.pop();
             }
+            // Some editors start UTF-8 files with a byte order mark. It still counts towards the
+            // cursor, which is an offset in the file.
+            if self.cursor == 0 {
+                if let Some(stripped) = line.strip_prefix('\u{feff}') {
+                    line = stripped.to_string();
+                }
+            }
             self.line_buf = Some(line);
         }
 
@@ -130,20 +149,39 @@ impl<L: LineReader> Parser<L> {
 
     fn consume_line(&mut self) -> Result<Option<String>> {
         self.peek_line()?;
+        if self.line_buf.is_some() {
+            self.cursor += self.line_buf_bytes;
+        }
         Ok(self.line_buf.take())
     }
 
+    /// Consumes the `#EXTM3U` line and the directives after it, up to the first song. Returns
+    /// whether there was an `#EXTM3U` line. The directives can be retrieved with
+    /// [Parser::playlist].
     pub fn next_header(&mut self) -> Result<bool> {
-        match self.peek_line()? {
-            Some(line) if line.starts_with("#EXTM3U") => {
-                self.consume_line()?;
-                Ok(true)
+        let mut extm3u = false;
+        let mut header = Vec::new();
+        while let Some(line) = self.peek_line()? {
+            let line = line.trim();
+            if line.starts_with("#EXTM3U") {
+                extm3u = true;
+            } else if is_header_directive(line) {
+                header.push(line.to_string());
+            } else if !line.is_empty() {
+                break;
             }
-            _otherwise => Ok(false),
+            self.consume_line()?;
         }
+
+        self.header = Some(header);
+        Ok(extm3u)
     }
 
     pub fn next_song(&mut self) -> Result<Option<Song>> {
+        if self.header.is_none() {
+            self.next_header()?;
+        }
+
         let mut song = Song::default();
         while let Some(line) = self.consume_line()? {
             let line = line.trim();
@@ -153,8 +191,10 @@ impl<L: LineReader> Parser<L> {
                 match parse_extline(line)? {
                     Extm3u => {}
                     Extinf(d, t) => {
+                        let (artist, title) = split_artist(&t);
                         song.duration = d;
-                        song.title = t;
+                        song.artist = artist;
+                        song.title = title;
                     }
                 }
             } else {
@@ -175,6 +215,27 @@ impl<L: LineReader> Parser<L> {
         }
         Ok(songs)
     }
+
+    /// Parses the header directives and all songs
+    pub fn playlist(&mut self) -> Result<Playlist> {
+        if self.header.is_none() {
+            self.next_header()?;
+        }
+        let songs = self.all_songs()?;
+        Ok(Playlist {
+            header: self.header.clone().unwrap_or_default(),
+            songs,
+        })
+    }
+}
+
+/// Whether a line before the first song is a directive that should be kept in the header, like
+/// `#PLAYLIST:` or some `#EXT` directive tori doesn't know about
+fn is_header_directive(line: &str) -> bool {
+    line.starts_with("#PLAYLIST:")
+        || (line.starts_with("#EXT")
+            && !line.starts_with("#EXTINF")
+            && !line.starts_with("#EXTM3U"))
 }
 
 fn parse_extline(line: &str) -> Result<Ext> {
@@ -198,6 +259,76 @@ fn parse_extline(line: &str) -> Result<Ext> {
     Err(ParserError::UnknownExtline(line.to_string()))
 }
 
+/// How many levels of playlists within playlists [expand_nested] follows
+pub const MAX_NESTING_DEPTH: usize = 8;
+
+/// Replaces the songs that are local .m3u or .m3u8 files with the songs of those playlists,
+/// recursively. `songs` are the songs of the playlist at `path`, with their paths already resolved
+/// (see [Song::resolve_relative_to]). Playlists that can't be read are left as they are.
+///
+/// Also returns the playlists that weren't expanded because they include themselves, directly or
+/// through another playlist, or are nested more than [MAX_NESTING_DEPTH] levels deep.
+pub fn expand_nested(songs: Vec<Song>, path: &Path) -> (Vec<Song>, Vec<String>) {
+    let mut ancestors = vec![fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())];
+    let mut skipped = Vec::new();
+    let songs = expand_nested_in(songs, &mut ancestors, &mut skipped);
+    (songs, skipped)
+}
+
+fn expand_nested_in(
+    songs: Vec<Song>,
+    ancestors: &mut Vec<PathBuf>,
+    skipped: &mut Vec<String>,
+) -> Vec<Song> {
+    let mut expanded = Vec::with_capacity(songs.len());
+    for song in songs {
+        if !is_playlist_path(&song.path) {
+            expanded.push(song);
+            continue;
+        }
+        let Ok(nested_path) = fs::canonicalize(&song.path) else {
+            expanded.push(song);
+            continue;
+        };
+        if ancestors.contains(&nested_path) || ancestors.len() > MAX_NESTING_DEPTH {
+            skipped.push(song.path);
+            continue;
+        }
+        let Ok(mut nested) = Parser::from_path(&nested_path).and_then(|mut p| p.all_songs()) else {
+            expanded.push(song);
+            continue;
+        };
+
+        if let Some(dir) = nested_path.parent() {
+            for song in &mut nested {
+                song.resolve_relative_to(dir);
+            }
+        }
+        ancestors.push(nested_path);
+        expanded.extend(expand_nested_in(nested, ancestors, skipped));
+        ancestors.pop();
+    }
+    expanded
+}
+
+fn is_playlist_path(path: &str) -> bool {
+    !path.contains("://")
+        && Path::new(path).extension().map_or(false, |ext| {
+            ext.eq_ignore_ascii_case("m3u") || ext.eq_ignore_ascii_case("m3u8")
+        })
+}
+
+/// Splits an EXTINF title in the "Artist - Title" format into its artist and title. If there's no
+/// " - " separator, the whole string is considered the title.
+pub(crate) fn split_artist(s: &str) -> (Option<String>, String) {
+    match s.split_once(" - ") {
+        Some((artist, title)) if !artist.trim().is_empty() => {
+            (Some(artist.trim().to_string()), title.to_string())
+        }
+        _ => (None, s.to_string()),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -236,12 +367,14 @@ mod tests {
             parser.all_songs().ok(),
             Some(vec![
                 Song {
-                    title: "Artist - Title".into(),
+                    title: "Title".into(),
+                    artist: Some("Artist".into()),
                     duration: Duration::from_secs_f64(10.),
                     path: "https://www.youtube.com/watch?v=dQw4w9WgXcQ".into()
                 },
                 Song {
                     title: "Yup".into(),
+                    artist: None,
                     duration: Duration::from_secs_f64(0.),
                     path: "/path/to/local/song".into()
                 }
@@ -261,11 +394,13 @@ mod tests {
             Some(vec![
                 Song {
                     title: "#DOESNOTBEGINWITHEXT".into(),
+                    artist: None,
                     duration: Duration::default(),
                     path: "#DOESNOTBEGINWITHEXT".into(),
                 },
                 Song {
                     title: "something.mp3".into(),
+                    artist: None,
                     duration: Duration::default(),
                     path: "something.mp3".into()
                 },
@@ -273,6 +408,57 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_byte_order_mark() {
+        let content = "\u{feff}#EXTM3U\n#EXTINF:10,Artist - Title\nsong.mp3\n";
+        let songs = Parser::from_reader(content.as_bytes()).all_songs().unwrap();
+        assert_eq!(
+            songs,
+            vec![Song {
+                title: "Title".into(),
+                artist: Some("Artist".into()),
+                duration: Duration::from_secs(10),
+                path: "song.mp3".into(),
+            }]
+        );
+
+        // Without a header, the first line is a song
+        let content = "\u{feff}song.mp3\n";
+        let songs = Parser::from_reader(content.as_bytes()).all_songs().unwrap();
+        assert_eq!(songs[0].path, "song.mp3");
+    }
+
+    #[test]
+    fn test_split_artist() {
+        assert_eq!(
+            split_artist("Artist - Title"),
+            (Some("Artist".into()), "Title".into())
+        );
+        assert_eq!(
+            split_artist("Artist - Title - Live"),
+            (Some("Artist".into()), "Title - Live".into())
+        );
+        assert_eq!(split_artist("Just a title"), (None, "Just a title".into()));
+        assert_eq!(split_artist("Dashed-title"), (None, "Dashed-title".into()));
+        assert_eq!(split_artist(" - Title"), (None, " - Title".into()));
+    }
+
+    #[test]
+    fn test_serialize_roundtrip() {
+        let song = Song {
+            title: "Title".into(),
+            artist: Some("Artist".into()),
+            duration: Duration::from_secs(10),
+            path: "/path/to/song.mp3".into(),
+        };
+        let serialized = song.serialize();
+        assert_eq!(serialized, "#EXTINF:10,Artist - Title\n/path/to/song.mp3\n");
+        assert_eq!(
+            Parser::from_string(&serialized).all_songs().ok(),
+            Some(vec![song])
+        );
+    }
+
     #[test]
     fn test_extline_errors() {
         let mut parser = Parser::from_string(
@@ -291,4 +477,73 @@ mod tests {
             Err(ParserError::UnknownExtline(s)) if s == "#EXTNOTSUPPORTED"
         ));
     }
+
+    #[test]
+    fn test_header_directives() {
+        let content =
+            "#EXTM3U\n#PLAYLIST:Road trip\n#EXTART:Various\n\n#EXTINF:10,Title\nsong.mp3\n";
+        let playlist = Parser::from_string(content).playlist().unwrap();
+        assert_eq!(
+            playlist.header,
+            vec!["#PLAYLIST:Road trip".to_string(), "#EXTART:Various".into()]
+        );
+        assert_eq!(playlist.title(), Some("Road trip"));
+        assert_eq!(playlist.songs.len(), 1);
+        assert_eq!(playlist.songs[0].path, "song.mp3");
+
+        assert_eq!(
+            Parser::from_string(&playlist.serialize()).playlist().ok(),
+            Some(playlist)
+        );
+    }
+
+    #[test]
+    fn test_resolve_relative_paths() {
+        let content = "#EXTM3U\n#EXTINF:1,A\nalbum/a.mp3\n#EXTINF:1,B\n/music/b.mp3\n#EXTINF:1,C\nhttps://example.com/c\n";
+        let mut songs = Parser::from_string(content).all_songs().unwrap();
+        for song in &mut songs {
+            song.resolve_relative_to(Path::new("/home/user/playlists"));
+        }
+
+        let paths: Vec<_> = songs.iter().map(|s| s.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "/home/user/playlists/album/a.mp3",
+                "/music/b.mp3",
+                "https://example.com/c"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_expand_nested() {
+        let dir = std::env::temp_dir().join(format!("tori-nested-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let write = |name: &str, content: &str| {
+            let path = dir.join(name);
+            fs::write(&path, content).unwrap();
+            path
+        };
+        let outer = write("outer.m3u8", "#EXTM3U\na.mp3\ninner.m3u\nd.mp3\n");
+        write("inner.m3u", "b.mp3\nouter.m3u8\nc.mp3\n");
+
+        let mut songs = Parser::from_path(&outer).unwrap().all_songs().unwrap();
+        for song in &mut songs {
+            song.resolve_relative_to(&dir);
+        }
+        let (songs, skipped) = expand_nested(songs, &outer);
+
+        let names: Vec<_> = songs.iter().map(|s| s.title.as_str()).collect();
+        let names: Vec<_> = names
+            .iter()
+            .map(|t| Path::new(t).file_name().unwrap().to_str().unwrap())
+            .collect();
+        assert_eq!(names, vec!["a.mp3", "b.mp3", "c.mp3", "d.mp3"]);
+        // inner.m3u refers back to outer.m3u8, which is being expanded
+        assert_eq!(skipped.len(), 1);
+        assert!(skipped[0].ends_with("outer.m3u8"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }
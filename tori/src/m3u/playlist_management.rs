@@ -1,6 +1,7 @@
 use std::{
     fs,
     io::{self, Write},
+    path::{Path, PathBuf},
     result::Result as StdResult,
     thread,
 };
@@ -77,6 +78,56 @@ fn image_file(file: &std::path::Path) -> bool {
     )
 }
 
+/// Extensions of the files picked up by [import_directory]
+const AUDIO_EXTENSIONS: &[&str] = &[
+    "mp3", "flac", "ogg", "oga", "opus", "m4a", "aac", "wav", "wma", "aiff", "ape", "alac",
+];
+
+fn audio_file(file: &std::path::Path) -> bool {
+    file.extension()
+        .and_then(|s| s.to_str())
+        .map_or(false, |ext| {
+            AUDIO_EXTENSIONS.contains(&ext.to_lowercase().as_str())
+        })
+}
+
+/// Fills an existing, empty playlist with the audio files in `dir`, sorted by path. Other files
+/// are skipped. Subdirectories are only searched if `recursive` is set, and symlinked directories
+/// never are, to avoid infinite loops. Every song is read before the playlist is written, so it's
+/// left untouched if one can't be. Returns how many songs were added.
+pub fn import_directory(dir: &Path, playlist_name: &str, recursive: bool) -> Result<usize> {
+    let mut files = Vec::new();
+    find_audio_files(dir, recursive, &mut files)?;
+
+    let mut playlist = m3u::Playlist::default();
+    for file in &files {
+        let path = file
+            .to_str()
+            .ok_or_else(|| format!("Path is not valid UTF-8: '{}'", file.display()))?;
+        playlist.songs.push(m3u::Song::from_path(path)?);
+    }
+    write_playlist(playlist_name, &playlist)?;
+    Ok(files.len())
+}
+
+fn find_audio_files(dir: &Path, recursive: bool, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    let mut entries = fs::read_dir(dir)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+    entries.sort();
+
+    for path in entries {
+        if path.is_dir() {
+            if recursive && !path.is_symlink() {
+                find_audio_files(&path, recursive, files)?;
+            }
+        } else if audio_file(&path) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
 #[derive(Debug)]
 pub enum CreatePlaylistError {
     PlaylistAlreadyExists,
@@ -90,30 +141,133 @@ impl From<io::Error> for CreatePlaylistError {
     }
 }
 
-/// Creates the corresponding .m3u8 file for a new playlist
-pub fn create_playlist(playlist_name: &str) -> StdResult<(), CreatePlaylistError> {
+fn validate_playlist_name(playlist_name: &str) -> StdResult<(), CreatePlaylistError> {
     if playlist_name.contains('/') {
         return Err(CreatePlaylistError::InvalidChar('/'));
     }
     if playlist_name.contains('\\') {
         return Err(CreatePlaylistError::InvalidChar('\\'));
     }
+    Ok(())
+}
+
+/// Names of the playlists in `dir`, sorted. Fails if `dir` doesn't exist, which main.rs offers to
+/// fix at startup.
+pub fn list_playlists(dir: &Path) -> Result<Vec<String>> {
+    let entries =
+        fs::read_dir(dir).map_err(|e| format!("Failed to read playlists directory: {}", e))?;
+
+    let mut playlists = Vec::new();
+    for entry in entries {
+        let name = entry?
+            .file_name()
+            .into_string()
+            .map_err(|filename| format!("File '{:?}' has invalid UTF-8", filename))?;
+
+        // Other files, like exported .pls playlists, are not shown
+        if let Some(playlist) = name.strip_suffix(".m3u8") {
+            playlists.push(playlist.to_string());
+        }
+    }
 
+    playlists.sort();
+    Ok(playlists)
+}
+
+/// Creates the corresponding .m3u8 file for a new playlist
+pub fn create_playlist(playlist_name: &str) -> StdResult<(), CreatePlaylistError> {
+    validate_playlist_name(playlist_name)?;
     let path = Config::playlist_path(playlist_name);
 
     // TODO: when it's stabilized, use std::fs::File::create_new
     if path.try_exists()? {
         Err(CreatePlaylistError::PlaylistAlreadyExists)
     } else {
-        fs::File::create(path)?;
+        fs::write(path, m3u::Playlist::default().serialize())?;
         Ok(())
     }
 }
 
-pub fn delete_song(playlist_name: &str, index: usize) -> Result<()> {
-    let path = Config::playlist_path(playlist_name);
-    let content = fs::read_to_string(&path)?;
-    let mut parser = m3u::Parser::from_string(&content);
+/// Replaces the whole content of a playlist file, header directives included. Edits to single
+/// songs go through [splice_song] instead, which leaves the rest of the file as it is.
+pub fn write_playlist(playlist_name: &str, playlist: &m3u::Playlist) -> Result<()> {
+    fs::write(Config::playlist_path(playlist_name), playlist.serialize())?;
+    Ok(())
+}
+
+/// An edit that undoes some change to a playlist. The functions that change songs in a playlist
+/// return the edit that undoes them.
+#[derive(Debug, Clone)]
+pub enum Edit {
+    /// Inserts raw m3u content before the `index`-th song
+    InsertSong {
+        playlist: String,
+        index: usize,
+        content: String,
+    },
+    /// Replaces the `index`-th song with raw m3u content
+    ReplaceSong {
+        playlist: String,
+        index: usize,
+        content: String,
+    },
+    DeleteSong {
+        playlist: String,
+        index: usize,
+    },
+    SwapSong {
+        playlist: String,
+        index: usize,
+    },
+    /// Moves the `from`-th song to the `to`-th position
+    MoveSong {
+        playlist: String,
+        from: usize,
+        to: usize,
+    },
+    /// Applies every edit in order
+    Batch(Vec<Edit>),
+}
+
+impl Edit {
+    pub fn apply(self) -> Result<()> {
+        match self {
+            Edit::InsertSong {
+                playlist,
+                index,
+                content,
+            } => splice_song(&playlist, index, &content, false),
+            Edit::ReplaceSong {
+                playlist,
+                index,
+                content,
+            } => splice_song(&playlist, index, &content, true),
+            Edit::DeleteSong { playlist, index } => delete_song(&playlist, index).map(|_| ()),
+            Edit::SwapSong { playlist, index } => swap_song(&playlist, index).map(|_| ()),
+            Edit::MoveSong { playlist, from, to } => {
+                move_song_to_index(&playlist, from, to).map(|_| ())
+            }
+            Edit::Batch(edits) => edits.into_iter().try_for_each(Edit::apply),
+        }
+    }
+
+    /// The playlists changed by this edit
+    pub fn playlists(&self) -> Vec<&str> {
+        match self {
+            Edit::InsertSong { playlist, .. }
+            | Edit::ReplaceSong { playlist, .. }
+            | Edit::DeleteSong { playlist, .. }
+            | Edit::SwapSong { playlist, .. }
+            | Edit::MoveSong { playlist, .. } => vec![playlist.as_str()],
+            Edit::Batch(edits) => edits.iter().flat_map(Edit::playlists).collect(),
+        }
+    }
+}
+
+/// Returns the byte range of the `index`-th song in some playlist content. If there's no such
+/// song, the range is empty and points to the end of the playlist.
+fn song_span(content: &str, index: usize) -> Result<(usize, usize)> {
+    let mut parser = m3u::Parser::from_string(content);
 
     parser.next_header()?;
     for _ in 0..index {
@@ -121,22 +275,173 @@ pub fn delete_song(playlist_name: &str, index: usize) -> Result<()> {
     }
 
     let start_pos = parser.cursor();
-    let _song = parser.next_song()?;
+    parser.next_song()?;
     let end_pos = parser.cursor();
+    Ok((start_pos, end_pos))
+}
+
+/// Writes `new_content` before the `index`-th song of a playlist, or in place of it if `replace`
+fn splice_song(playlist_name: &str, index: usize, new_content: &str, replace: bool) -> Result<()> {
+    let path = Config::playlist_path(playlist_name);
+    let content = fs::read_to_string(&path)?;
+    let (start_pos, end_pos) = song_span(&content, index)?;
+    let end_pos = if replace { end_pos } else { start_pos };
 
     let mut file = fs::OpenOptions::new()
         .write(true)
         .truncate(true)
         .open(&path)?;
-    file.write_all(content[..start_pos].as_bytes())?;
-    file.write_all(content[end_pos..].as_bytes())?;
+    file.write_all(&content.as_bytes()[..start_pos])?;
+    file.write_all(new_content.as_bytes())?;
+    file.write_all(&content.as_bytes()[end_pos..])?;
 
     Ok(())
 }
 
-pub fn rename_song(playlist_name: &str, index: usize, new_name: &str) -> Result<()> {
+/// The raw m3u lines of the `index`-th song of a playlist, or `None` if there's no such song
+pub fn song_content(playlist_name: &str, index: usize) -> Result<Option<String>> {
+    let content = fs::read_to_string(Config::playlist_path(playlist_name))?;
+    let (start_pos, end_pos) = song_span(&content, index)?;
+    Ok((start_pos < end_pos).then(|| with_newline(&content[start_pos..end_pos])))
+}
+
+/// Inserts raw m3u lines (see [song_content]) as the `index`-th song of a playlist, or at the
+/// end if it has fewer songs
+pub fn insert_song_at(playlist_name: &str, index: usize, song: &str) -> Result<Option<Edit>> {
+    let content = fs::read_to_string(Config::playlist_path(playlist_name))?;
+    let songs = m3u::Parser::from_string(&content).all_songs()?.len();
+    let mut song = with_newline(song);
+    if index >= songs && !content.ends_with('\n') {
+        song.insert(0, '\n');
+    }
+    splice_song(playlist_name, index, &song, false)?;
+
+    Ok(Some(Edit::DeleteSong {
+        playlist: playlist_name.to_string(),
+        index: index.min(songs),
+    }))
+}
+
+/// Cuts the raw lines of the `from`-th song out of some playlist content and splices them back in
+/// as the `to`-th song. Returns `None` if either index is out of range, or they're the same.
+fn move_song_within(content: &str, from: usize, to: usize) -> Result<Option<String>> {
+    let count = m3u::Parser::from_string(content).all_songs()?.len();
+    if from == to || from >= count || to >= count {
+        return Ok(None);
+    }
+
+    let (start_pos, end_pos) = song_span(content, from)?;
+    let song = with_newline(&content[start_pos..end_pos]);
+    let mut moved = format!("{}{}", &content[..start_pos], &content[end_pos..]);
+
+    let (mut at, _) = song_span(&moved, to)?;
+    if at == moved.len() && !moved.ends_with('\n') {
+        moved.push('\n');
+        at += 1;
+    }
+    moved.insert_str(at, &song);
+    Ok(Some(moved))
+}
+
+/// The last song of a playlist may not end with a newline, which would merge it with the next one
+fn with_newline(song: &str) -> String {
+    if song.ends_with('\n') {
+        song.to_string()
+    } else {
+        format!("{}\n", song)
+    }
+}
+
+/// Cuts the raw lines of the `index`-th song out of some playlist content. Returns the remaining
+/// content and the lines that were cut, or `None` if `index` is out of range.
+pub(crate) fn delete_song_within(content: &str, index: usize) -> Result<Option<(String, String)>> {
+    let (start_pos, end_pos) = song_span(content, index)?;
+
+    if start_pos == end_pos {
+        return Ok(None);
+    }
+
+    Ok(Some((
+        format!("{}{}", &content[..start_pos], &content[end_pos..]),
+        content[start_pos..end_pos].to_string(),
+    )))
+}
+
+pub fn delete_song(playlist_name: &str, index: usize) -> Result<Option<Edit>> {
     let path = Config::playlist_path(playlist_name);
     let content = fs::read_to_string(&path)?;
+    let Some((remaining, deleted)) = delete_song_within(&content, index)? else {
+        return Ok(None);
+    };
+
+    fs::write(&path, remaining)?;
+
+    Ok(Some(Edit::InsertSong {
+        playlist: playlist_name.to_string(),
+        index,
+        content: deleted,
+    }))
+}
+
+/// Changes the title in the `#EXTINF` line of a song. The path and the duration are left as they
+/// are, so renaming a stream keeps it playable.
+pub fn rename_song(playlist_name: &str, index: usize, new_name: &str) -> Result<Option<Edit>> {
+    let path = Config::playlist_path(playlist_name);
+    let content = fs::read_to_string(&path)?;
+    let (start_pos, end_pos) = song_span(&content, index)?;
+
+    if start_pos == end_pos {
+        return Ok(None);
+    }
+
+    let old_song = &content[start_pos..end_pos];
+    splice_song(
+        playlist_name,
+        index,
+        &retitle_song(old_song, new_name),
+        true,
+    )?;
+
+    Ok(Some(Edit::ReplaceSong {
+        playlist: playlist_name.to_string(),
+        index,
+        content: old_song.to_string(),
+    }))
+}
+
+/// Rewrites the title after the comma of the `#EXTINF` line of a song entry, or adds an
+/// `#EXTINF` line before the path if the entry doesn't have one. Every other line is kept as is.
+fn retitle_song(song: &str, new_title: &str) -> String {
+    let new_title = new_title.replace(['\n', '\r'], " ");
+    let mut out = String::with_capacity(song.len() + new_title.len());
+    let mut has_extinf = false;
+
+    for line in song.split_inclusive('\n') {
+        let text = line.trim_end_matches(['\n', '\r']);
+        let newline = &line[text.len()..];
+        if let Some(info) = text.strip_prefix("#EXTINF:") {
+            let duration = info.split_once(',').map_or(info, |(d, _)| d);
+            out.push_str(&format!("#EXTINF:{},{}{}", duration, new_title, newline));
+            has_extinf = true;
+        } else if !has_extinf && !text.trim().is_empty() && !text.starts_with('#') {
+            out.push_str(&format!("#EXTINF:0,{}\n", new_title));
+            out.push_str(line);
+            has_extinf = true;
+        } else {
+            out.push_str(line);
+        }
+    }
+    out
+}
+
+/// Moves the `index`-th song of a playlist to the end of another one. Moving a song to the
+/// playlist it's already in does nothing.
+pub fn move_song(from_playlist: &str, index: usize, to_playlist: &str) -> Result<Option<Edit>> {
+    if from_playlist == to_playlist {
+        return Ok(None);
+    }
+
+    let content = fs::read_to_string(Config::playlist_path(from_playlist))?;
     let mut parser = m3u::Parser::from_string(&content);
 
     parser.next_header()?;
@@ -144,27 +449,64 @@ pub fn rename_song(playlist_name: &str, index: usize, new_name: &str) -> Result<
         parser.next_song()?;
     }
 
-    let start_pos = parser.cursor();
-    let song = parser.next_song()?;
-    let end_pos = parser.cursor();
+    if let Some(song) = parser.next_song()? {
+        // Add before deleting, so the song isn't lost if something goes wrong
+        song.add_to_playlist(to_playlist)?;
+        let reinsert = delete_song(from_playlist, index)?;
 
-    if let Some(mut song) = song {
-        song.title = new_name.to_string();
+        let to_content = fs::read_to_string(Config::playlist_path(to_playlist))?;
+        let added_index = m3u::Parser::from_string(&to_content).all_songs()?.len() - 1;
 
-        let mut file = fs::OpenOptions::new()
-            .write(true)
-            .truncate(true)
-            .open(&path)?;
-        file.write_all(content[..start_pos].as_bytes())?;
-        file.write_all(song.serialize().as_bytes())?;
-        file.write_all(content[end_pos..].as_bytes())?;
+        let mut edits = vec![Edit::DeleteSong {
+            playlist: to_playlist.to_string(),
+            index: added_index,
+        }];
+        edits.extend(reinsert);
+        return Ok(Some(Edit::Batch(edits)));
     }
 
-    Ok(())
+    Ok(None)
+}
+
+/// Deletes several songs of a playlist at once
+pub fn delete_songs(playlist_name: &str, indices: &[usize]) -> Result<Option<Edit>> {
+    let mut indices = indices.to_vec();
+    indices.sort_unstable();
+    indices.dedup();
+
+    // Delete from the last song up, so the other indices don't shift
+    let mut edits = Vec::new();
+    for &index in indices.iter().rev() {
+        edits.extend(delete_song(playlist_name, index)?);
+    }
+
+    // Reinsert the songs in reverse order
+    edits.reverse();
+    Ok((!edits.is_empty()).then_some(Edit::Batch(edits)))
+}
+
+/// Moves several songs of a playlist to the end of another one, keeping their order
+pub fn move_songs(
+    from_playlist: &str,
+    indices: &[usize],
+    to_playlist: &str,
+) -> Result<Option<Edit>> {
+    let mut indices = indices.to_vec();
+    indices.sort_unstable();
+    indices.dedup();
+
+    // Every song moved before shifts the next ones up by one
+    let mut edits = Vec::new();
+    for (moved, &index) in indices.iter().enumerate() {
+        edits.extend(move_song(from_playlist, index - moved, to_playlist)?);
+    }
+
+    edits.reverse();
+    Ok((!edits.is_empty()).then_some(Edit::Batch(edits)))
 }
 
 /// Swaps `index`-th song with the `index+1`-th (0-indexed)
-pub fn swap_song(playlist_name: &str, index: usize) -> Result<()> {
+pub fn swap_song(playlist_name: &str, index: usize) -> Result<Option<Edit>> {
     let path = Config::playlist_path(playlist_name);
     let content = fs::read_to_string(&path)?;
     let mut parser = m3u::Parser::from_string(&content);
@@ -188,8 +530,57 @@ pub fn swap_song(playlist_name: &str, index: usize) -> Result<()> {
         file.write_all(song2.serialize().as_bytes())?;
         file.write_all(song1.serialize().as_bytes())?;
         file.write_all(content[end_pos..].as_bytes())?;
+
+        return Ok(Some(Edit::SwapSong {
+            playlist: playlist_name.to_string(),
+            index,
+        }));
     }
 
+    Ok(None)
+}
+
+/// Moves the `from`-th song of a playlist to the `to`-th position, shifting the songs in between.
+/// The song's lines are moved as they are, and the file is written once.
+pub fn move_song_to_index(playlist_name: &str, from: usize, to: usize) -> Result<Option<Edit>> {
+    let path = Config::playlist_path(playlist_name);
+    let content = fs::read_to_string(&path)?;
+    let Some(moved) = move_song_within(&content, from, to)? else {
+        return Ok(None);
+    };
+    fs::write(&path, moved)?;
+
+    Ok(Some(Edit::MoveSong {
+        playlist: playlist_name.to_string(),
+        from: to,
+        to: from,
+    }))
+}
+
+/// Renames the .m3u8 file of a playlist. Fails if a playlist named `new_name` already exists.
+pub fn rename_playlist(playlist_name: &str, new_name: &str) -> StdResult<(), CreatePlaylistError> {
+    validate_playlist_name(new_name)?;
+    let new_path = Config::playlist_path(new_name);
+    if new_path.try_exists()? {
+        return Err(CreatePlaylistError::PlaylistAlreadyExists);
+    }
+
+    fs::rename(Config::playlist_path(playlist_name), new_path)?;
+    Ok(())
+}
+
+/// Copies a playlist, with all of its metadata, to a new name
+pub fn duplicate_playlist(
+    playlist_name: &str,
+    new_name: &str,
+) -> StdResult<(), CreatePlaylistError> {
+    validate_playlist_name(new_name)?;
+    let new_path = Config::playlist_path(new_name);
+    if new_path.try_exists()? {
+        return Err(CreatePlaylistError::PlaylistAlreadyExists);
+    }
+
+    fs::copy(Config::playlist_path(playlist_name), new_path)?;
     Ok(())
 }
 
@@ -198,3 +589,106 @@ pub fn delete_playlist(playlist_name: &str) -> Result<()> {
     fs::remove_file(path)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_song_span() {
+        let content = "#EXTM3U\n#EXTINF:1,A\na.mp3\n#EXTINF:2,B\nb.mp3\n";
+        let (start, end) = song_span(content, 1).unwrap();
+        assert_eq!(&content[start..end], "#EXTINF:2,B\nb.mp3\n");
+
+        let (start, end) = song_span(content, 2).unwrap();
+        assert_eq!((start, end), (content.len(), content.len()));
+    }
+
+    #[test]
+    fn test_song_span_skips_header() {
+        let content = "#EXTM3U\n#PLAYLIST:Mix\n#EXTINF:1,A\na.mp3\n";
+        let (start, end) = song_span(content, 0).unwrap();
+        assert_eq!(&content[..start], "#EXTM3U\n#PLAYLIST:Mix\n");
+        assert_eq!(&content[start..end], "#EXTINF:1,A\na.mp3\n");
+    }
+
+    #[test]
+    fn test_with_newline() {
+        let content = "#EXTM3U\n#EXTINF:1,A\na.mp3\n#EXTINF:2,B\nb.mp3";
+        let (start, end) = song_span(content, 1).unwrap();
+        assert_eq!(with_newline(&content[start..end]), "#EXTINF:2,B\nb.mp3\n");
+        assert_eq!(with_newline("a.mp3\r\n"), "a.mp3\r\n");
+    }
+
+    #[test]
+    fn test_move_song_within() {
+        let content = "#EXTM3U\n#PLAYLIST:Mix\n#EXTINF:-1,Radio\nhttps://radio.example\nb.mp3\n#EXTINF:2.5,C\nc.mp3";
+        let moved = move_song_within(content, 0, 2).unwrap().unwrap();
+        assert_eq!(
+            moved,
+            "#EXTM3U\n#PLAYLIST:Mix\nb.mp3\n#EXTINF:2.5,C\nc.mp3\n#EXTINF:-1,Radio\nhttps://radio.example\n"
+        );
+        let back = move_song_within(&moved, 2, 0).unwrap().unwrap();
+        assert_eq!(
+            back,
+            "#EXTM3U\n#PLAYLIST:Mix\n#EXTINF:-1,Radio\nhttps://radio.example\nb.mp3\n#EXTINF:2.5,C\nc.mp3\n"
+        );
+
+        assert_eq!(move_song_within(content, 1, 1).unwrap(), None);
+        assert_eq!(move_song_within(content, 0, 3).unwrap(), None);
+    }
+
+    #[test]
+    fn test_retitle_song() {
+        let song = "#EXTINF:-1,Old title\r\nhttps://radio.example/stream?a=1,2\r\n";
+        assert_eq!(
+            retitle_song(song, "Artist - New"),
+            "#EXTINF:-1,Artist - New\r\nhttps://radio.example/stream?a=1,2\r\n"
+        );
+
+        // Songs without an #EXTINF line get one
+        assert_eq!(retitle_song("a.mp3\n", "A"), "#EXTINF:0,A\na.mp3\n");
+
+        let content = format!("#EXTM3U\n{}#EXTINF:2,B\nb.mp3\n", song);
+        let (start, end) = song_span(&content, 0).unwrap();
+        let renamed = format!(
+            "{}{}{}",
+            &content[..start],
+            retitle_song(&content[start..end], "Renamed"),
+            &content[end..]
+        );
+        let songs = m3u::Parser::from_string(&renamed).all_songs().unwrap();
+        assert_eq!(songs[0].title, "Renamed");
+        assert_eq!(songs[0].path, "https://radio.example/stream?a=1,2");
+        assert_eq!(songs[1].path, "b.mp3");
+    }
+
+    #[test]
+    fn test_audio_file() {
+        assert!(audio_file(Path::new("/music/song.mp3")));
+        assert!(audio_file(Path::new("/music/Song.FLAC")));
+        assert!(!audio_file(Path::new("/music/cover.jpg")));
+        assert!(!audio_file(Path::new("/music/notes")));
+    }
+
+    #[test]
+    fn test_list_playlists() {
+        let dir = std::env::temp_dir().join(format!("tori-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+
+        // A missing directory is an error, and isn't created behind the user's back
+        assert!(list_playlists(&dir).is_err());
+        assert!(!dir.exists());
+        fs::create_dir(&dir).unwrap();
+
+        // An empty directory has no playlists
+        assert_eq!(list_playlists(&dir).unwrap(), Vec::<String>::new());
+
+        fs::write(dir.join("b.m3u8"), "#EXTM3U\n").unwrap();
+        fs::write(dir.join("a.m3u8"), "#EXTM3U\n").unwrap();
+        fs::write(dir.join("a.pls"), "[playlist]\n").unwrap();
+        assert_eq!(list_playlists(&dir).unwrap(), vec!["a", "b"]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -2,7 +2,7 @@ pub use tori::*;
 
 use app::App;
 use argh::FromArgs;
-use config::{Config, OptionalConfig};
+use config::Config;
 pub use error::{Error, Result};
 use std::path::{Path, PathBuf};
 
@@ -11,28 +11,43 @@ use std::path::{Path, PathBuf};
 struct Args {
     #[argh(option, short = 'c')]
     /// the path to an alternative config file. If not present, the config is loaded from
-    /// $CONFIG_DIR/tori.yaml, where $CONFIG_DIR is $HOME/.config on Linux,
-    /// $HOME/Library/Application Support on macOS, and %appdata% on Windows.
+    /// $TORI_CONFIG if it's set, or from $CONFIG_DIR/tori.yaml, where $CONFIG_DIR is
+    /// $HOME/.config on Linux, $HOME/Library/Application Support on macOS, and %appdata% on
+    /// Windows.
     config: Option<String>,
+
+    #[argh(option, short = 'p')]
+    /// the name of a playlist to select on startup
+    playlist: Option<String>,
 }
 
 fn main() -> Result<()> {
     pretty_env_logger::init();
 
     let args: Args = argh::from_env();
-    Config::set_global({
-        let opt_conf = OptionalConfig::from_path(
-            args.config
-                .map(PathBuf::from)
-                .unwrap_or(dirs::config_dir().unwrap_or_default().join("tori.yaml")),
-        )?;
-
-        Config::default().merge(opt_conf)
-    });
+    let config_path = match args.config {
+        Some(path) => PathBuf::from(path),
+        None => Config::default_path()?,
+    };
+    Config::set_global(Config::load(&config_path)?);
 
     make_sure_playlist_dir_exists();
 
+    if let Some(playlist) = &args.playlist {
+        if !Config::playlist_path(playlist).is_file() {
+            eprintln!("The playlist '{playlist}' does not exist!");
+            std::process::exit(1);
+        }
+    }
+
     let mut app = App::new()?;
+    match &args.playlist {
+        Some(playlist) => {
+            app.select_playlist(playlist)?;
+        }
+        None => app.restore_session(),
+    }
+    app.watch_config(config_path);
     app.run()
 }
 
@@ -0,0 +1,245 @@
+//! [MPRIS2](https://specifications.freedesktop.org/mpris-spec/latest/) integration, so desktop
+//! media keys and tools like `playerctl` can control tori.
+//!
+//! The D-Bus connection handles requests in its own thread, forwarding them to the app as
+//! [Event::Command]s. The app is responsible for calling [Mpris::update] every now and then, so
+//! that the published playback status and metadata are kept in sync with the player.
+
+use std::{
+    collections::HashMap,
+    sync::{mpsc::Sender, Mutex},
+};
+
+use zbus::{
+    blocking::{Connection, ConnectionBuilder},
+    dbus_interface,
+    zvariant::{ObjectPath, OwnedValue, Value},
+};
+
+use crate::{command::Command, error::Result, events::Event, player::PlayerState};
+
+const BUS_NAME: &str = "org.mpris.MediaPlayer2.tori";
+const OBJECT_PATH: &str = "/org/mpris/MediaPlayer2";
+
+pub struct Mpris {
+    connection: Connection,
+}
+
+impl Mpris {
+    /// Registers tori on the session bus
+    pub fn new(sender: Sender<Event>) -> Result<Self> {
+        let root = RootInterface {
+            sender: Mutex::new(sender.clone()),
+        };
+        let player = PlayerInterface {
+            sender: Mutex::new(sender),
+            state: PlayerState::default(),
+        };
+
+        let connection = ConnectionBuilder::session()?
+            .name(BUS_NAME)?
+            .serve_at(OBJECT_PATH, root)?
+            .serve_at(OBJECT_PATH, player)?
+            .build()?;
+
+        Ok(Self { connection })
+    }
+
+    /// Publishes the new player state, notifying D-Bus clients of anything that changed
+    pub fn update(&self, state: PlayerState) -> Result<()> {
+        let iface_ref = self
+            .connection
+            .object_server()
+            .interface::<_, PlayerInterface>(OBJECT_PATH)?;
+        let mut iface = iface_ref.get_mut();
+        if iface.state == state {
+            return Ok(());
+        }
+
+        let old = std::mem::replace(&mut iface.state, state);
+        let ctxt = iface_ref.signal_context();
+        zbus::block_on(async {
+            if (old.paused, old.idle) != (iface.state.paused, iface.state.idle) {
+                iface.playback_status_changed(ctxt).await?;
+            }
+            if (&old.media_title, old.duration())
+                != (&iface.state.media_title, iface.state.duration())
+            {
+                iface.metadata_changed(ctxt).await?;
+            }
+            zbus::Result::Ok(())
+        })?;
+        Ok(())
+    }
+}
+
+/// Sends a command to the app, ignoring errors because the app may be shutting down
+fn send_command(sender: &Mutex<Sender<Event>>, cmd: Command) {
+    sender.lock().unwrap().send(Event::Command(cmd)).ok();
+}
+
+///////////////////////////////////////////
+//        org.mpris.MediaPlayer2        //
+///////////////////////////////////////////
+struct RootInterface {
+    sender: Mutex<Sender<Event>>,
+}
+
+#[dbus_interface(name = "org.mpris.MediaPlayer2")]
+impl RootInterface {
+    fn raise(&self) {}
+
+    fn quit(&self) {
+        send_command(&self.sender, Command::Quit);
+    }
+
+    #[dbus_interface(property)]
+    fn can_quit(&self) -> bool {
+        true
+    }
+
+    #[dbus_interface(property)]
+    fn can_raise(&self) -> bool {
+        false
+    }
+
+    #[dbus_interface(property)]
+    fn has_track_list(&self) -> bool {
+        false
+    }
+
+    #[dbus_interface(property)]
+    fn identity(&self) -> &str {
+        "tori"
+    }
+
+    #[dbus_interface(property)]
+    fn supported_uri_schemes(&self) -> Vec<String> {
+        vec!["file".into(), "http".into(), "https".into()]
+    }
+
+    #[dbus_interface(property)]
+    fn supported_mime_types(&self) -> Vec<String> {
+        vec![]
+    }
+}
+
+//////////////////////////////////////////////////
+//        org.mpris.MediaPlayer2.Player        //
+//////////////////////////////////////////////////
+struct PlayerInterface {
+    sender: Mutex<Sender<Event>>,
+    state: PlayerState,
+}
+
+#[dbus_interface(name = "org.mpris.MediaPlayer2.Player")]
+impl PlayerInterface {
+    fn next(&self) {
+        send_command(&self.sender, Command::NextSong);
+    }
+
+    fn previous(&self) {
+        send_command(&self.sender, Command::PrevSong);
+    }
+
+    fn play_pause(&self) {
+        send_command(&self.sender, Command::TogglePause);
+    }
+
+    fn pause(&self) {
+        if !self.state.paused {
+            send_command(&self.sender, Command::TogglePause);
+        }
+    }
+
+    fn play(&self) {
+        if self.state.paused {
+            send_command(&self.sender, Command::TogglePause);
+        }
+    }
+
+    /// tori can't really stop, so this pauses instead
+    fn stop(&self) {
+        self.pause();
+    }
+
+    #[dbus_interface(property)]
+    fn playback_status(&self) -> &str {
+        match (self.state.idle, self.state.paused) {
+            (true, _) => "Stopped",
+            (false, true) => "Paused",
+            (false, false) => "Playing",
+        }
+    }
+
+    #[dbus_interface(property)]
+    fn metadata(&self) -> HashMap<String, OwnedValue> {
+        let mut metadata = HashMap::new();
+        if self.state.idle {
+            return metadata;
+        }
+
+        let trackid = ObjectPath::from_static_str_unchecked("/org/tori/track/current");
+        metadata.insert("mpris:trackid".into(), Value::from(trackid).into());
+        metadata.insert(
+            "mpris:length".into(),
+            Value::from(self.state.duration() * 1_000_000).into(),
+        );
+        metadata.insert(
+            "xesam:title".into(),
+            Value::from(self.state.media_title.clone()).into(),
+        );
+        metadata
+    }
+
+    /// Position in microseconds
+    #[dbus_interface(property)]
+    fn position(&self) -> i64 {
+        self.state.time_pos * 1_000_000
+    }
+
+    #[dbus_interface(property)]
+    fn rate(&self) -> f64 {
+        1.0
+    }
+
+    #[dbus_interface(property)]
+    fn minimum_rate(&self) -> f64 {
+        1.0
+    }
+
+    #[dbus_interface(property)]
+    fn maximum_rate(&self) -> f64 {
+        1.0
+    }
+
+    #[dbus_interface(property)]
+    fn can_go_next(&self) -> bool {
+        true
+    }
+
+    #[dbus_interface(property)]
+    fn can_go_previous(&self) -> bool {
+        true
+    }
+
+    #[dbus_interface(property)]
+    fn can_play(&self) -> bool {
+        true
+    }
+
+    #[dbus_interface(property)]
+    fn can_pause(&self) -> bool {
+        true
+    }
+
+    #[dbus_interface(property)]
+    fn can_seek(&self) -> bool {
+        false
+    }
+
+    #[dbus_interface(property)]
+    fn can_control(&self) -> bool {
+        true
+    }
+}
@@ -1,9 +1,14 @@
 use crate::error::Result;
 
+mod state;
+pub use state::PlayerState;
+
 #[cfg(feature = "mpv")]
 mod mpv;
 #[cfg(feature = "mpv")]
 pub type DefaultPlayer = mpv::MpvPlayer;
+#[cfg(feature = "mpv")]
+pub use mpv::MpvError;
 
 #[cfg(feature = "tori-player")]
 mod tori_player_glue;
@@ -13,28 +18,63 @@ pub type DefaultPlayer = tori_player::Player;
 pub trait Player: Sized {
     fn new() -> Result<Self>;
     fn play(&mut self, path: &str) -> Result<()>;
+    /// Like [Player::play], but starts `seconds` into the song
+    fn play_from(&mut self, path: &str, seconds: i64) -> Result<()>;
     fn queue(&mut self, path: &str) -> Result<()>;
     fn seek(&mut self, seconds: f64) -> Result<()>;
-    fn seek_absolute(&mut self, percent: usize) -> Result<()>;
+    fn seek_absolute(&mut self, percent: f64) -> Result<()>;
+    /// Jumps to `seconds` into the song being played
+    fn seek_to(&mut self, seconds: f64) -> Result<()>;
     fn playlist_next(&mut self) -> Result<()>;
     fn playlist_previous(&mut self) -> Result<()>;
     fn toggle_pause(&mut self) -> Result<()>;
     fn toggle_loop_file(&mut self) -> Result<()>;
     fn looping_file(&self) -> Result<bool>;
+    fn toggle_loop_playlist(&mut self) -> Result<()>;
+    fn looping_playlist(&self) -> Result<bool>;
+    /// Start and end of the A-B loop, in seconds. Each is `None` while it isn't set.
+    fn ab_loop(&self) -> Result<(Option<f64>, Option<f64>)>;
+    /// Sets the A-B loop of the song being played. It's cleared when the song changes.
+    fn set_ab_loop(&mut self, a: Option<f64>, b: Option<f64>) -> Result<()>;
     fn volume(&self) -> Result<i64>;
     fn add_volume(&mut self, x: isize) -> Result<()>;
     fn set_volume(&mut self, x: i64) -> Result<()>;
     fn toggle_mute(&mut self) -> Result<()>;
     fn muted(&self) -> Result<bool>;
+    /// Playback speed, where 1.0 is the normal speed
+    fn speed(&self) -> Result<f64>;
+    fn set_speed(&mut self, speed: f64) -> Result<()>;
+    /// Replaces the equalizer's audio filter, given in mpv's `af` syntax. Other filters, like the
+    /// ones from `mpv_options`, are kept. An empty string removes the equalizer's filter.
+    fn set_audio_filter(&mut self, filter: &str) -> Result<()>;
+    /// Options from the config's `mpv_options` that the player didn't accept when it started
+    fn rejected_options(&self) -> &[String];
+    /// Whether the video of songs that have a video track is shown, which `mpv_options` may have
+    /// turned off
+    fn video(&self) -> Result<bool>;
+    /// Shows or hides the video of songs that have a video track. Songs without one aren't
+    /// affected.
+    fn set_video(&mut self, on: bool) -> Result<()>;
     fn media_title(&self) -> Result<String>;
+    /// Path or URL of the song being played
+    fn path(&self) -> Result<String>;
     fn percent_pos(&self) -> Result<i64>;
     fn time_pos(&self) -> Result<i64>;
+    /// Like [Self::time_pos], but with the fraction of a second
+    fn time_pos_f64(&self) -> Result<f64>;
     fn time_remaining(&self) -> Result<i64>;
     fn paused(&self) -> Result<bool>;
+    fn idle(&self) -> Result<bool>;
     fn shuffle(&mut self) -> Result<()>;
 
     // Playlist-related:
     fn playlist_count(&self) -> Result<usize>;
     fn playlist_track_title(&self, i: usize) -> Result<String>;
+    /// Path or URL of the `i`-th song in the queue
+    fn playlist_track_path(&self, i: usize) -> Result<String>;
     fn playlist_position(&self) -> Result<usize>;
+    fn playlist_play_index(&mut self, i: usize) -> Result<()>;
+    fn playlist_remove(&mut self, i: usize) -> Result<()>;
+    /// Stops playing and empties the queue
+    fn playlist_clear(&mut self) -> Result<()>;
 }
@@ -3,24 +3,45 @@ use crate::error::Result;
 
 mod select;
 use select::Mpv;
+pub use select::MpvError;
+
+/// Label of the equalizer's audio filter, so that it can be swapped without touching the filters
+/// from `mpv_options`
+const EQ_FILTER_LABEL: &str = "@tori-eq";
 
-#[repr(transparent)]
 pub struct MpvPlayer {
     pub(crate) mpv: Mpv,
+    /// `mpv_options` that mpv didn't accept, with the reason
+    rejected_options: Vec<String>,
 }
 
 impl super::Player for MpvPlayer {
     fn new() -> Result<Self> {
+        let mut rejected_options = Vec::new();
         let mpv = Mpv::with_initializer(|mpv| {
             mpv.set_bool("video", false)?;
             mpv.set_i64("volume", 100)?;
             if let Some(ao) = &Config::global().mpv_ao {
                 mpv.set_str("ao", ao.as_str())?;
             }
+            // An invalid value is reported by the app instead of failing to start
+            if let Some(gapless) = Config::global().valid_gapless_audio() {
+                mpv.set_str("gapless-audio", gapless)?;
+            }
+            // Set last so that they can override the ones above. Like gapless_audio, invalid
+            // options are reported by the app.
+            for (name, value) in &Config::global().mpv_options {
+                if let Err(e) = mpv.set_str(name, value) {
+                    rejected_options.push(format!("{}={} ({})", name, value, e));
+                }
+            }
             Ok(())
         })?;
 
-        Ok(Self { mpv })
+        Ok(Self {
+            mpv,
+            rejected_options,
+        })
     }
 
     fn play(&mut self, path: &str) -> Result<()> {
@@ -28,6 +49,12 @@ impl super::Player for MpvPlayer {
         Ok(())
     }
 
+    fn play_from(&mut self, path: &str, seconds: i64) -> Result<()> {
+        self.mpv
+            .play_with_options(path, &format!("start={}", seconds))?;
+        Ok(())
+    }
+
     fn queue(&mut self, path: &str) -> Result<()> {
         self.mpv.queue(path)?;
         Ok(())
@@ -42,11 +69,13 @@ impl super::Player for MpvPlayer {
         Ok(())
     }
 
-    fn seek_absolute(&mut self, percent: usize) -> Result<()> {
-        // this is bugged currently :/
-        // self.mpv.seek_percent_absolute(percent)?;
-        self.mpv
-            .command("seek", &[&format!("{}", percent), "absolute-percent"])?;
+    fn seek_absolute(&mut self, percent: f64) -> Result<()> {
+        self.mpv.set_f64("percent-pos", percent.clamp(0.0, 100.0))?;
+        Ok(())
+    }
+
+    fn seek_to(&mut self, seconds: f64) -> Result<()> {
+        self.mpv.set_f64("time-pos", seconds.max(0.0))?;
         Ok(())
     }
 
@@ -80,6 +109,37 @@ impl super::Player for MpvPlayer {
         Ok(status == "inf")
     }
 
+    fn toggle_loop_playlist(&mut self) -> Result<()> {
+        let status = self.mpv.get_str("loop-playlist");
+        let next_status = match status.as_deref() {
+            Ok("no") => "inf",
+            _ => "no",
+        };
+        self.mpv.set_str("loop-playlist", next_status)?;
+        Ok(())
+    }
+
+    fn looping_playlist(&self) -> Result<bool> {
+        let status = self.mpv.get_str("loop-playlist")?;
+        Ok(status == "inf")
+    }
+
+    fn ab_loop(&self) -> Result<(Option<f64>, Option<f64>)> {
+        // Unset points are "no"
+        let point = |name| -> Result<Option<f64>> { Ok(self.mpv.get_str(name)?.parse().ok()) };
+        Ok((point("ab-loop-a")?, point("ab-loop-b")?))
+    }
+
+    fn set_ab_loop(&mut self, a: Option<f64>, b: Option<f64>) -> Result<()> {
+        // File-local options go back to their previous value when the file changes
+        let value = |point: Option<f64>| point.map_or("no".to_string(), |p| p.to_string());
+        self.mpv
+            .set_str("file-local-options/ab-loop-a", &value(a))?;
+        self.mpv
+            .set_str("file-local-options/ab-loop-b", &value(b))?;
+        Ok(())
+    }
+
     fn volume(&self) -> Result<i64> {
         Ok(self.mpv.get_i64("volume")?)
     }
@@ -103,10 +163,46 @@ impl super::Player for MpvPlayer {
         Ok(self.mpv.get_bool("mute")?)
     }
 
+    fn speed(&self) -> Result<f64> {
+        Ok(self.mpv.get_f64("speed")?)
+    }
+
+    fn set_speed(&mut self, speed: f64) -> Result<()> {
+        self.mpv.set_f64("speed", speed)?;
+        Ok(())
+    }
+
+    fn set_audio_filter(&mut self, filter: &str) -> Result<()> {
+        // Fails when there's no filter with the label yet, which is fine
+        let _ = self.mpv.command("af", &["remove", EQ_FILTER_LABEL]);
+        if !filter.is_empty() {
+            let labelled = quote(&format!("{}:{}", EQ_FILTER_LABEL, filter));
+            self.mpv.command("af", &["add", &labelled])?;
+        }
+        Ok(())
+    }
+
+    fn rejected_options(&self) -> &[String] {
+        &self.rejected_options
+    }
+
+    fn video(&self) -> Result<bool> {
+        Ok(self.mpv.get_str("vid")? != "no")
+    }
+
+    fn set_video(&mut self, on: bool) -> Result<()> {
+        self.mpv.set_str("vid", if on { "auto" } else { "no" })?;
+        Ok(())
+    }
+
     fn media_title(&self) -> Result<String> {
         Ok(self.mpv.get_str("media-title")?)
     }
 
+    fn path(&self) -> Result<String> {
+        Ok(self.mpv.get_str("path")?)
+    }
+
     fn percent_pos(&self) -> Result<i64> {
         Ok(self.mpv.get_i64("percent-pos")?)
     }
@@ -115,6 +211,10 @@ impl super::Player for MpvPlayer {
         Ok(self.mpv.get_i64("time-pos")?)
     }
 
+    fn time_pos_f64(&self) -> Result<f64> {
+        Ok(self.mpv.get_f64("time-pos")?)
+    }
+
     fn time_remaining(&self) -> Result<i64> {
         Ok(self.mpv.get_i64("time-remaining")?)
     }
@@ -123,6 +223,10 @@ impl super::Player for MpvPlayer {
         Ok(self.mpv.get_bool("pause")?)
     }
 
+    fn idle(&self) -> Result<bool> {
+        Ok(self.mpv.get_bool("idle-active")?)
+    }
+
     fn shuffle(&mut self) -> Result<()> {
         Ok(self.mpv.command("playlist-shuffle", &[])?)
     }
@@ -138,7 +242,41 @@ impl super::Player for MpvPlayer {
             .or_else(|_| self.mpv.get_str(&format!("playlist/{}/filename", i)))?)
     }
 
+    fn playlist_track_path(&self, i: usize) -> Result<String> {
+        Ok(self.mpv.get_str(&format!("playlist/{}/filename", i))?)
+    }
+
     fn playlist_position(&self) -> Result<usize> {
         Ok(self.mpv.get_i64("playlist-playing-pos")? as usize)
     }
+
+    fn playlist_play_index(&mut self, i: usize) -> Result<()> {
+        Ok(self.mpv.set_i64("playlist-pos", i as i64)?)
+    }
+
+    fn playlist_remove(&mut self, i: usize) -> Result<()> {
+        Ok(self.mpv.command("playlist-remove", &[&i.to_string()])?)
+    }
+
+    fn playlist_clear(&mut self) -> Result<()> {
+        // playlist-clear keeps the current song, so stop gets rid of it too
+        self.mpv.command("playlist-clear", &[])?;
+        Ok(self.mpv.command("stop", &[])?)
+    }
+}
+
+/// Quotes `arg` for mpv's command syntax, so that spaces and quotes in it are kept
+fn quote(arg: &str) -> String {
+    format!("\"{}\"", arg.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("lavfi=[bass=g=6]"), r#""lavfi=[bass=g=6]""#);
+        assert_eq!(quote(r#"a "b" \c"#), r#""a \"b\" \\c""#);
+    }
 }
@@ -86,6 +86,20 @@ impl Mpv {
         }
     }
 
+    /// Like [Self::play], with some per-file options, like `start=10`
+    pub fn play_with_options(&self, path: &str, options: &str) -> Result<()> {
+        match self {
+            Self::V034(mpv) => {
+                mpv.playlist_load_files(&[(path, mpv034::FileState::Replace, Some(options))])?;
+                Ok(())
+            }
+            Self::V035(mpv) => {
+                mpv.playlist_load_files(&[(path, mpv035::FileState::Replace, Some(options))])?;
+                Ok(())
+            }
+        }
+    }
+
     pub fn queue(&self, path: &str) -> Result<()> {
         match self {
             Self::V034(mpv) => {
@@ -108,9 +122,11 @@ impl Mpv {
     define_data! { get get_bool bool }
     define_data! { get get_str String }
     define_data! { get get_i64 i64 }
+    define_data! { get get_f64 f64 }
 
     define_data! { set set_str &str }
     define_data! { set set_i64 i64 }
+    define_data! { set set_f64 f64 }
 
     define_data! { add add_isize isize }
 }
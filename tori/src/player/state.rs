@@ -0,0 +1,54 @@
+use super::Player;
+
+/// Snapshot of the player's properties, read once per second by the app (see
+/// [App::player_state](crate::app::App::player_state)) so that components don't each query the
+/// player on their own
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PlayerState {
+    pub media_title: String,
+    /// Path or URL of the song being played
+    pub path: String,
+    pub percentage: i64,
+    /// Position in the current song, in seconds
+    pub time_pos: i64,
+    /// Time left in the current song, in seconds
+    pub time_remaining: i64,
+    pub paused: bool,
+    /// Whether the player has nothing loaded
+    pub idle: bool,
+    pub loop_file: bool,
+    pub loop_playlist: bool,
+    /// Start and end of the A-B loop, in seconds
+    pub ab_loop: (Option<f64>, Option<f64>),
+    pub volume: i64,
+    pub muted: bool,
+    /// Playback speed, where 1.0 is the normal speed
+    pub speed: f64,
+}
+
+impl PlayerState {
+    /// Reads every property from the player. Properties that can't be read (e.g. the title when
+    /// nothing is playing) get a default value.
+    pub fn from_player(player: &impl Player) -> Self {
+        Self {
+            media_title: player.media_title().unwrap_or_default(),
+            path: player.path().unwrap_or_default(),
+            percentage: player.percent_pos().unwrap_or_default(),
+            time_pos: player.time_pos().unwrap_or_default(),
+            time_remaining: player.time_remaining().unwrap_or_default(),
+            paused: player.paused().unwrap_or_default(),
+            idle: player.idle().unwrap_or(true),
+            loop_file: player.looping_file().unwrap_or_default(),
+            loop_playlist: player.looping_playlist().unwrap_or_default(),
+            ab_loop: player.ab_loop().unwrap_or_default(),
+            volume: player.volume().unwrap_or_default(),
+            muted: player.muted().unwrap_or_default(),
+            speed: player.speed().unwrap_or(1.0),
+        }
+    }
+
+    /// Duration of the current song, in seconds
+    pub fn duration(&self) -> i64 {
+        self.time_pos + self.time_remaining
+    }
+}
@@ -1,4 +1,6 @@
-use tori_player::{Result, controller::Controller};
+use tori_player::controller::Controller;
+
+use crate::error::Result;
 
 macro_rules! my_todo {
     () => {
@@ -13,7 +15,11 @@ impl super::Player for tori_player::Player {
     }
 
     fn play(&mut self, path: &str) -> Result<()> {
-        self.controller.play(path)
+        Ok(self.controller.play(path)?)
+    }
+
+    fn play_from(&mut self, path: &str, seconds: i64) -> Result<()> {
+        my_todo!()
     }
 
     fn queue(&mut self, path: &str) -> Result<()> {
@@ -24,7 +30,11 @@ impl super::Player for tori_player::Player {
         my_todo!()
     }
 
-    fn seek_absolute(&mut self, percent: usize) -> Result<()> {
+    fn seek_absolute(&mut self, percent: f64) -> Result<()> {
+        my_todo!()
+    }
+
+    fn seek_to(&mut self, seconds: f64) -> Result<()> {
         my_todo!()
     }
 
@@ -48,6 +58,14 @@ impl super::Player for tori_player::Player {
         my_todo!()
     }
 
+    fn toggle_loop_playlist(&mut self) -> Result<()> {
+        my_todo!()
+    }
+
+    fn looping_playlist(&self) -> Result<bool> {
+        my_todo!()
+    }
+
     fn volume(&self) -> Result<i64> {
         my_todo!()
     }
@@ -68,10 +86,46 @@ impl super::Player for tori_player::Player {
         my_todo!()
     }
 
+    fn ab_loop(&self) -> Result<(Option<f64>, Option<f64>)> {
+        my_todo!()
+    }
+
+    fn set_ab_loop(&mut self, _a: Option<f64>, _b: Option<f64>) -> Result<()> {
+        my_todo!()
+    }
+
+    fn speed(&self) -> Result<f64> {
+        my_todo!()
+    }
+
+    fn set_speed(&mut self, _speed: f64) -> Result<()> {
+        my_todo!()
+    }
+
+    fn set_audio_filter(&mut self, _filter: &str) -> Result<()> {
+        my_todo!()
+    }
+
+    fn rejected_options(&self) -> &[String] {
+        &[]
+    }
+
+    fn video(&self) -> Result<bool> {
+        my_todo!()
+    }
+
+    fn set_video(&mut self, _on: bool) -> Result<()> {
+        my_todo!()
+    }
+
     fn media_title(&self) -> Result<String> {
         my_todo!()
     }
 
+    fn path(&self) -> Result<String> {
+        my_todo!()
+    }
+
     fn percent_pos(&self) -> Result<i64> {
         my_todo!()
     }
@@ -80,6 +134,10 @@ impl super::Player for tori_player::Player {
         my_todo!()
     }
 
+    fn time_pos_f64(&self) -> Result<f64> {
+        my_todo!()
+    }
+
     fn time_remaining(&self) -> Result<i64> {
         my_todo!()
     }
@@ -88,6 +146,10 @@ impl super::Player for tori_player::Player {
         my_todo!()
     }
 
+    fn idle(&self) -> Result<bool> {
+        my_todo!()
+    }
+
     fn shuffle(&mut self) -> Result<()> {
         my_todo!()
     }
@@ -100,7 +162,23 @@ impl super::Player for tori_player::Player {
         my_todo!()
     }
 
+    fn playlist_track_path(&self, i: usize) -> Result<String> {
+        my_todo!()
+    }
+
     fn playlist_position(&self) -> Result<usize> {
         my_todo!()
     }
+
+    fn playlist_play_index(&mut self, i: usize) -> Result<()> {
+        my_todo!()
+    }
+
+    fn playlist_remove(&mut self, i: usize) -> Result<()> {
+        my_todo!()
+    }
+
+    fn playlist_clear(&mut self) -> Result<()> {
+        my_todo!()
+    }
 }
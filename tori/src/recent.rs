@@ -0,0 +1,137 @@
+//! Keeps track of the songs played most recently, which the playlists pane shows as a playlist of
+//! its own (see the `recent_songs` config option).
+
+use serde::{Deserialize, Serialize};
+
+use crate::{error::Result, m3u, player::PlayerState, util};
+
+/// Where the recently played songs are kept between runs, in the [util::data_dir]
+const FILE_NAME: &str = "recent.yaml";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecentSong {
+    pub path: String,
+    pub title: String,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecentSongs {
+    /// Most recent first
+    songs: Vec<RecentSong>,
+    /// Path of the song that was playing on the last [Self::update], so that songs are only added
+    /// when they start
+    #[serde(skip)]
+    playing: Option<String>,
+}
+
+impl RecentSongs {
+    /// Loads the saved songs. Returns an empty list if there's none or it can't be read.
+    pub fn load() -> Self {
+        util::load_data(FILE_NAME).unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        util::save_data(FILE_NAME, self)
+    }
+
+    /// Adds the song being played if it just started, keeping at most `max` songs. Returns whether
+    /// the list changed.
+    pub fn update(&mut self, state: &PlayerState, max: usize) -> bool {
+        let playing = (!state.idle && !state.path.is_empty()).then(|| state.path.clone());
+        if playing == self.playing {
+            return false;
+        }
+        self.playing = playing;
+
+        let Some(path) = self.playing.clone() else {
+            return false;
+        };
+        let title = match state.media_title.as_str() {
+            "" => path.clone(),
+            title => title.to_string(),
+        };
+        self.push(RecentSong { path, title }, max);
+        true
+    }
+
+    /// Puts `song` at the top. A song that was played before is moved instead of being added
+    /// twice, and the oldest songs are dropped to keep at most `max`.
+    fn push(&mut self, song: RecentSong, max: usize) {
+        self.songs.retain(|s| s.path != song.path);
+        self.songs.insert(0, song);
+        self.songs.truncate(max);
+    }
+
+    /// The songs as playlist entries, most recent first
+    pub fn to_songs(&self) -> Vec<m3u::Song> {
+        self.songs
+            .iter()
+            .map(|song| {
+                // mpv falls back to the file name when there's no title
+                let (artist, title) = if song.title == song.path {
+                    (None, song.title.clone())
+                } else {
+                    m3u::parser::split_artist(&song.title)
+                };
+                m3u::Song {
+                    title,
+                    artist,
+                    path: song.path.clone(),
+                    ..Default::default()
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn playing(path: &str, title: &str) -> PlayerState {
+        PlayerState {
+            path: path.into(),
+            media_title: title.into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_update() {
+        let mut recent = RecentSongs::default();
+        assert!(recent.update(&playing("a.mp3", "Queen - A"), 2));
+        // Still the same song
+        assert!(!recent.update(&playing("a.mp3", "Queen - A"), 2));
+        assert!(recent.update(&playing("b.mp3", ""), 2));
+        assert!(recent.update(&playing("c.mp3", "C"), 2));
+
+        let paths: Vec<_> = recent.songs.iter().map(|s| s.path.as_str()).collect();
+        assert_eq!(paths, vec!["c.mp3", "b.mp3"]);
+
+        // Playing a song again moves it to the top
+        assert!(recent.update(&playing("b.mp3", ""), 2));
+        let paths: Vec<_> = recent.songs.iter().map(|s| s.path.as_str()).collect();
+        assert_eq!(paths, vec!["b.mp3", "c.mp3"]);
+
+        // Stopping isn't a new song
+        let idle = PlayerState {
+            idle: true,
+            ..Default::default()
+        };
+        assert!(!recent.update(&idle, 2));
+        assert_eq!(recent.songs.len(), 2);
+    }
+
+    #[test]
+    fn test_to_songs() {
+        let mut recent = RecentSongs::default();
+        recent.update(&playing("a.mp3", "Queen - A"), 10);
+        recent.update(&playing("/music/x - y.mp3", ""), 10);
+
+        let songs = recent.to_songs();
+        assert_eq!(songs[0].title, "/music/x - y.mp3");
+        assert_eq!(songs[0].artist, None);
+        assert_eq!(songs[1].title, "A");
+        assert_eq!(songs[1].artist.as_deref(), Some("Queen"));
+    }
+}
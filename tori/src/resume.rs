@@ -0,0 +1,84 @@
+//! Remembers the song being played and its position when tori quits, so that it can be resumed
+//! on the next launch (see the `resume_playback` config option).
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{error::Result, player::PlayerState, util};
+
+/// Where the resume point is kept between runs, in the [util::data_dir]
+const FILE_NAME: &str = "resume.yaml";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResumePoint {
+    pub path: String,
+    /// Position in the song, in seconds
+    pub time_pos: i64,
+}
+
+impl ResumePoint {
+    /// The point to resume from, if a local song was being played. Streams can't be resumed.
+    pub fn from_state(state: &PlayerState) -> Option<Self> {
+        if state.idle || state.path.is_empty() || state.path.contains("://") {
+            return None;
+        }
+
+        Some(Self {
+            path: state.path.clone(),
+            time_pos: state.time_pos,
+        })
+    }
+
+    /// Loads the saved resume point. Returns `None` if there's none, or if its song doesn't exist
+    /// anymore.
+    pub fn load() -> Option<Self> {
+        let point: Self = util::load_data(FILE_NAME)?;
+        Path::new(&point.path).is_file().then_some(point)
+    }
+
+    /// Saves `point` for the next run, or forgets the saved one if `point` is `None`
+    pub fn save(point: Option<&Self>) -> Result<()> {
+        let Some(point) = point else {
+            let path = util::data_dir().map(|dir| dir.join(FILE_NAME));
+            if let Some(path) = path.filter(|path| path.exists()) {
+                std::fs::remove_file(path)?;
+            }
+            return Ok(());
+        };
+        util::save_data(FILE_NAME, point)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_state() {
+        let state = PlayerState {
+            path: "/music/song.mp3".into(),
+            time_pos: 42,
+            ..Default::default()
+        };
+        assert_eq!(
+            ResumePoint::from_state(&state),
+            Some(ResumePoint {
+                path: "/music/song.mp3".into(),
+                time_pos: 42
+            })
+        );
+
+        let stream = PlayerState {
+            path: "https://example.com/stream".into(),
+            ..state.clone()
+        };
+        assert_eq!(ResumePoint::from_state(&stream), None);
+
+        let idle = PlayerState {
+            idle: true,
+            ..state
+        };
+        assert_eq!(ResumePoint::from_state(&idle), None);
+    }
+}
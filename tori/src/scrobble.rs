@@ -0,0 +1,172 @@
+//! [Last.fm](https://www.last.fm/api/scrobbling) scrobbling.
+//!
+//! The app calls [Scrobbler::update] every second. When a new song starts, it's sent to Last.fm as
+//! "now playing", and once it has played for long enough, it's scrobbled. Requests are made in
+//! their own threads, and failures are reported back to the app as [Event::ScrobbleFailed].
+
+use std::{
+    sync::mpsc::Sender,
+    thread,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    config::ScrobbleConfig, events::Event, m3u::parser::split_artist, player::PlayerState,
+};
+
+const API_URL: &str = "https://ws.audioscrobbler.com/2.0/";
+
+/// Last.fm doesn't accept scrobbles of songs shorter than this, in seconds
+const MIN_DURATION: i64 = 30;
+/// A song is scrobbled after playing for half its duration or this many seconds, whichever comes
+/// first
+const MAX_SCROBBLE_POSITION: i64 = 240;
+
+/// The song being played, as far as the scrobbler knows
+#[derive(Debug, Clone, PartialEq)]
+struct Track {
+    /// The title reported by the player, used to detect when the song changes
+    media_title: String,
+    artist: String,
+    title: String,
+    /// When the song started playing, in seconds since the unix epoch
+    started_at: u64,
+    scrobbled: bool,
+}
+
+pub struct Scrobbler {
+    config: ScrobbleConfig,
+    sender: Sender<Event>,
+    current: Option<Track>,
+}
+
+impl Scrobbler {
+    pub fn new(config: ScrobbleConfig, sender: Sender<Event>) -> Self {
+        Self {
+            config,
+            sender,
+            current: None,
+        }
+    }
+
+    /// Checks what the player is doing, sending a "now playing" update when a new song starts
+    /// and scrobbling it once it has played for long enough
+    pub fn update(&mut self, state: &PlayerState) {
+        if state.idle {
+            self.current = None;
+            return;
+        }
+
+        if state.media_title.is_empty() {
+            return;
+        }
+        let media_title = state.media_title.clone();
+        let position = state.time_pos;
+        let duration = state.duration();
+
+        if self.current.as_ref().map(|t| &t.media_title) != Some(&media_title) {
+            self.current = None;
+
+            // Last.fm needs an artist, and the best we can do is guess it from the title
+            let (Some(artist), title) = split_artist(&media_title) else {
+                return;
+            };
+            let track = Track {
+                media_title,
+                artist,
+                title,
+                started_at: unix_time().saturating_sub(position.max(0) as u64),
+                scrobbled: false,
+            };
+            self.send(now_playing_params(&track, duration));
+            self.current = Some(track);
+        }
+
+        let Some(track) = self.current.as_mut() else {
+            return;
+        };
+        let played_enough = position * 2 >= duration || position >= MAX_SCROBBLE_POSITION;
+        if !track.scrobbled && duration > MIN_DURATION && played_enough {
+            track.scrobbled = true;
+            let params = scrobble_params(track, duration);
+            self.send(params);
+        }
+    }
+
+    /// Signs and sends a request to the Last.fm API in another thread
+    fn send(&self, mut params: Vec<(&'static str, String)>) {
+        params.push(("api_key", self.config.api_key.clone()));
+        params.push(("sk", self.config.session_key.clone()));
+        params.push(("api_sig", signature(&params, &self.config.api_secret)));
+        params.push(("format", "json".into()));
+
+        let sender = self.sender.clone();
+        thread::spawn(move || {
+            let form: Vec<_> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
+            if let Err(e) = ureq::post(API_URL).send_form(&form) {
+                sender.send(Event::ScrobbleFailed(e.to_string())).ok();
+            }
+        });
+    }
+}
+
+fn now_playing_params(track: &Track, duration: i64) -> Vec<(&'static str, String)> {
+    vec![
+        ("method", "track.updateNowPlaying".into()),
+        ("artist", track.artist.clone()),
+        ("track", track.title.clone()),
+        ("duration", duration.to_string()),
+    ]
+}
+
+fn scrobble_params(track: &Track, duration: i64) -> Vec<(&'static str, String)> {
+    vec![
+        ("method", "track.scrobble".into()),
+        ("artist", track.artist.clone()),
+        ("track", track.title.clone()),
+        ("duration", duration.to_string()),
+        ("timestamp", track.started_at.to_string()),
+    ]
+}
+
+/// Computes the `api_sig` parameter: the md5 hash of every parameter, sorted by name and
+/// concatenated as `<name><value>`, followed by the API secret.
+/// See <https://www.last.fm/api/authspec#_8-signing-calls>
+fn signature(params: &[(&str, String)], secret: &str) -> String {
+    let mut params: Vec<_> = params.iter().filter(|(k, _)| *k != "format").collect();
+    params.sort_by_key(|(k, _)| *k);
+
+    let mut s = String::new();
+    for (k, v) in params {
+        s.push_str(k);
+        s.push_str(v);
+    }
+    s.push_str(secret);
+    format!("{:x}", md5::compute(s))
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signature() {
+        let params = [
+            ("track", "Title".to_string()),
+            ("artist", "Artist".to_string()),
+            ("format", "json".to_string()),
+        ];
+        // md5("artistArtisttrackTitlesecret")
+        assert_eq!(
+            signature(&params, "secret"),
+            "4ea50e9b0a802bdfcaf9d0abb6470c2f"
+        );
+    }
+}
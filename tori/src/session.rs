@@ -0,0 +1,59 @@
+//! Remembers where the user was in the browse screen when tori quits, so that the next launch
+//! opens the same playlist, song and pane.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{error::Result, util};
+
+/// Where the session is kept between runs, in the [util::data_dir]
+const FILE_NAME: &str = "session.yaml";
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FocusedPane {
+    #[default]
+    Playlists,
+    Songs,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Session {
+    pub playlist: Option<String>,
+    /// Index of the selected song in the playlist
+    pub song: Option<usize>,
+    #[serde(default)]
+    pub pane: FocusedPane,
+}
+
+impl Session {
+    /// Loads the saved session. Returns `None` if there's none or it can't be read.
+    pub fn load() -> Option<Self> {
+        util::load_data(FILE_NAME)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        util::save_data(FILE_NAME, self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize() {
+        let session: Session =
+            serde_yaml::from_str("playlist: rock\nsong: 3\npane: Songs").unwrap();
+        assert_eq!(
+            session,
+            Session {
+                playlist: Some("rock".into()),
+                song: Some(3),
+                pane: FocusedPane::Songs,
+            }
+        );
+
+        // Sessions saved without a pane open on the playlists
+        let session: Session = serde_yaml::from_str("playlist: rock\nsong: null").unwrap();
+        assert_eq!(session.pane, FocusedPane::Playlists);
+    }
+}
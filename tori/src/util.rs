@@ -1,7 +1,17 @@
 //! I don't really know where to put these...
 
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
 use std::time::{Duration, Instant};
 
+use crate::config::Config;
+#[cfg(feature = "clip")]
+use crate::error::Error;
+use crate::error::Result;
+
+use serde::{de::DeserializeOwned, Serialize};
+
 /////////////////////////////
 //        ClickInfo        //
 /////////////////////////////
@@ -17,15 +27,23 @@ pub struct ClickInfo {
 }
 
 impl ClickInfo {
-    /// Updates the ClickInfo with another click
+    /// Updates the ClickInfo with another click. Two clicks on the same line count as a double
+    /// click if they're at most `double_click_ms` apart (see [Config](crate::config::Config)).
     pub fn update(last_click: &mut Option<ClickInfo>, y: u16) -> ClickUpdateSummary {
-        let this_click = ClickInfo {
-            instant: Instant::now(),
-            y,
-        };
+        let threshold = Duration::from_millis(Config::global().double_click_ms);
+        Self::update_at(last_click, y, Instant::now(), threshold)
+    }
+
+    fn update_at(
+        last_click: &mut Option<ClickInfo>,
+        y: u16,
+        now: Instant,
+        threshold: Duration,
+    ) -> ClickUpdateSummary {
+        let this_click = ClickInfo { instant: now, y };
 
         let summary = if let Some(s_last_click) = last_click {
-            let double_click = s_last_click.instant.elapsed() <= Duration::from_millis(200)
+            let double_click = now.duration_since(s_last_click.instant) <= threshold
                 && this_click.y == s_last_click.y;
 
             ClickUpdateSummary { double_click }
@@ -44,11 +62,229 @@ impl ClickInfo {
 //        Clipboard        //
 /////////////////////////////
 #[cfg(feature = "clip")]
-pub fn copy_to_clipboard(text: String) {
+pub fn copy_to_clipboard(text: String) -> Result<()> {
+    use clipboard::{ClipboardContext, ClipboardProvider};
+    let clipboard_err = |e: Box<dyn std::error::Error>| Error::Clipboard(e.to_string());
+    let mut ctx: ClipboardContext = ClipboardContext::new().map_err(clipboard_err)?;
+    ctx.set_contents(text).map_err(clipboard_err)
+}
+
+#[cfg(not(feature = "clip"))]
+pub fn copy_to_clipboard(_text: String) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(feature = "clip")]
+pub fn paste_from_clipboard() -> Option<String> {
     use clipboard::{ClipboardContext, ClipboardProvider};
-    let mut ctx: ClipboardContext = ClipboardContext::new().unwrap();
-    ctx.set_contents(text).unwrap();
+    let mut ctx: ClipboardContext = ClipboardContext::new().ok()?;
+    ctx.get_contents().ok()
 }
 
 #[cfg(not(feature = "clip"))]
-pub fn copy_to_clipboard(_text: String) {}
+pub fn paste_from_clipboard() -> Option<String> {
+    None
+}
+
+/// Pasted text as it goes into a single-line input: each line break becomes a space, so that the
+/// words of different lines don't run together. Line breaks at the end are dropped.
+pub fn single_line(text: &str) -> String {
+    text.trim_end_matches(&['\r', '\n'][..])
+        .replace("\r\n", " ")
+        .replace(&['\r', '\n'][..], " ")
+}
+
+////////////////////////////////
+//        File manager        //
+////////////////////////////////
+#[cfg(target_os = "windows")]
+const FILE_MANAGER: &str = "explorer";
+#[cfg(target_os = "macos")]
+const FILE_MANAGER: &str = "open";
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+const FILE_MANAGER: &str = "xdg-open";
+
+/// Opens a directory in the OS file manager, without waiting for it to close
+pub fn open_in_file_manager(dir: &Path) -> io::Result<()> {
+    Command::new(FILE_MANAGER)
+        .arg(dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    Ok(())
+}
+
+////////////////////////////////////
+//        Path completion        //
+////////////////////////////////////
+/// Paths that start with `prefix`, sorted. Directories end with a separator, so that they can be
+/// completed further. Hidden files are only listed if `prefix` names one.
+pub fn path_completions(prefix: &str) -> Vec<String> {
+    let (dir, name) = match prefix.rfind(std::path::is_separator) {
+        Some(i) => prefix.split_at(i + 1),
+        None => ("", prefix),
+    };
+    let Ok(entries) = std::fs::read_dir(if dir.is_empty() { "." } else { dir }) else {
+        return Vec::new();
+    };
+
+    let mut completions: Vec<_> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let file_name = entry.file_name().into_string().ok()?;
+            if !file_name.starts_with(name) || file_name.starts_with('.') && !name.starts_with('.')
+            {
+                return None;
+            }
+            let separator = if entry.path().is_dir() {
+                std::path::MAIN_SEPARATOR_STR
+            } else {
+                ""
+            };
+            Some(format!("{}{}{}", dir, file_name, separator))
+        })
+        .collect();
+    completions.sort();
+    completions
+}
+
+////////////////////////////////
+//        Time parsing        //
+////////////////////////////////
+/// Parses a position in a song typed as seconds, `mm:ss` or `h:mm:ss`. The seconds can have a
+/// fractional part.
+pub fn parse_time(s: &str) -> Option<f64> {
+    let parts: Vec<_> = s.trim().split(':').collect();
+    if parts.len() > 3 {
+        return None;
+    }
+    let (seconds, rest) = parts.split_last()?;
+    let seconds: f64 = seconds
+        .parse()
+        .ok()
+        .filter(|s: &f64| s.is_finite() && *s >= 0.0)?;
+    if !rest.is_empty() && seconds >= 60.0 {
+        return None;
+    }
+
+    let mut minutes: u64 = 0;
+    for (i, part) in rest.iter().enumerate() {
+        let n: u64 = part.parse().ok()?;
+        // Minutes that come after hours are below 60 too
+        if i > 0 && n >= 60 {
+            return None;
+        }
+        minutes = minutes.checked_mul(60)?.checked_add(n)?;
+    }
+    Some(minutes as f64 * 60.0 + seconds)
+}
+
+////////////////////////////////
+//         Data files         //
+////////////////////////////////
+/// Directory where tori keeps what it remembers between runs, or `None` if the OS doesn't have
+/// one for the current user
+pub fn data_dir() -> Option<PathBuf> {
+    dirs::data_local_dir().map(|dir| dir.join("tori"))
+}
+
+/// Reads `file_name` from the [data_dir] as YAML. Returns `None` if it's not there or can't be
+/// read.
+pub fn load_data<T: DeserializeOwned>(file_name: &str) -> Option<T> {
+    let file = std::fs::File::open(data_dir()?.join(file_name)).ok()?;
+    serde_yaml::from_reader(file).ok()
+}
+
+/// Writes `value` to `file_name` in the [data_dir] as YAML. Without a data directory, nothing is
+/// saved.
+pub fn save_data<T: Serialize>(file_name: &str, value: &T) -> Result<()> {
+    let Some(dir) = data_dir() else {
+        return Ok(());
+    };
+    std::fs::create_dir_all(&dir)?;
+    std::fs::write(dir.join(file_name), serde_yaml::to_string(value)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_time() {
+        assert_eq!(parse_time("90"), Some(90.0));
+        assert_eq!(parse_time(" 12.5 "), Some(12.5));
+        assert_eq!(parse_time("1:30"), Some(90.0));
+        assert_eq!(parse_time("01:05"), Some(65.0));
+        assert_eq!(parse_time("90:00"), Some(5400.0));
+        assert_eq!(parse_time("1:02:03"), Some(3723.0));
+
+        for invalid in [
+            "",
+            "abc",
+            "-5",
+            "1:60",
+            "1:60:00",
+            ":30",
+            "1:",
+            "1:2:3:4",
+            "inf",
+            // Too many hours to count in minutes
+            "400000000000000000:00:00",
+        ] {
+            assert_eq!(parse_time(invalid), None, "{:?}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_single_line() {
+        assert_eq!(single_line("first\nsecond\n"), "first second");
+        assert_eq!(single_line("a\r\nb\rc"), "a b c");
+        assert_eq!(single_line("  spaced  "), "  spaced  ");
+    }
+
+    #[test]
+    fn test_double_click_threshold() {
+        let threshold = Duration::from_millis(200);
+        let now = Instant::now();
+        let click_before = |ms| {
+            Some(ClickInfo {
+                instant: now - Duration::from_millis(ms),
+                y: 3,
+            })
+        };
+
+        let mut last_click = click_before(150);
+        assert!(ClickInfo::update_at(&mut last_click, 3, now, threshold).double_click);
+
+        let mut last_click = click_before(250);
+        assert!(!ClickInfo::update_at(&mut last_click, 3, now, threshold).double_click);
+
+        let mut last_click = click_before(150);
+        assert!(!ClickInfo::update_at(&mut last_click, 4, now, threshold).double_click);
+    }
+
+    #[test]
+    fn test_path_completions() {
+        let dir = std::env::temp_dir().join(format!("tori-complete-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("songs")).unwrap();
+        std::fs::write(dir.join("song.mp3"), "").unwrap();
+        std::fs::write(dir.join(".hidden"), "").unwrap();
+        let dir_str = format!("{}{}", dir.display(), std::path::MAIN_SEPARATOR);
+        let path = |name: &str| format!("{}{}", dir_str, name);
+
+        assert_eq!(
+            path_completions(&path("so")),
+            vec![
+                path("song.mp3"),
+                path(&format!("songs{}", std::path::MAIN_SEPARATOR))
+            ]
+        );
+        assert_eq!(path_completions(&path("")).len(), 2);
+        assert_eq!(path_completions(&path(".")), vec![path(".hidden")]);
+        assert!(path_completions(&path("nothing")).is_empty());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
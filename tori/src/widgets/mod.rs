@@ -3,3 +3,6 @@ pub use scrollbar::Scrollbar;
 
 pub mod notification;
 pub use notification::Notification;
+
+pub mod osd;
+pub use osd::Osd;
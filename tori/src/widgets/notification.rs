@@ -20,6 +20,10 @@ use tui::{
 
 const WIDTH: u16 = 40;
 
+/// How long a notification is shown at most, when `notification_timeout_secs` is too large to be
+/// added to the current time. About a hundred years.
+const LONGEST: Duration = Duration::from_secs(100 * 365 * 24 * 60 * 60);
+
 #[derive(Debug)]
 pub struct Notification<'t> {
     pub text: Cow<'t, str>,
@@ -46,9 +50,10 @@ impl<'t> Notification<'t> {
     {
         let text = text.into();
         let height = count_lines(&text) + 2;
+        let now = Instant::now();
         Self {
             text,
-            show_until: Instant::now() + duration,
+            show_until: now.checked_add(duration).unwrap_or(now + LONGEST),
             height,
             ..Default::default()
         }
@@ -64,6 +69,53 @@ impl<'t> Notification<'t> {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationKind {
+    Error,
+    Info,
+    Ok,
+}
+
+impl NotificationKind {
+    /// How long notifications of this kind are shown, where `timeout_secs` is the
+    /// `notification_timeout_secs` config option. Errors stay a second longer.
+    pub fn duration(self, timeout_secs: u64) -> Duration {
+        match self {
+            NotificationKind::Error => Duration::from_secs(timeout_secs.saturating_add(1)),
+            NotificationKind::Info | NotificationKind::Ok => Duration::from_secs(timeout_secs),
+        }
+    }
+}
+
+/// A notification that was shown, kept for [ShowLog](crate::command::Command::ShowLog)
+#[derive(Debug, Clone)]
+pub struct LogEntry {
+    pub kind: NotificationKind,
+    pub text: String,
+    pub shown_at: Instant,
+}
+
+impl LogEntry {
+    /// One line like `[error] 3m ago: Failed to seek`, with the age as of `now`
+    pub fn format(&self, now: Instant) -> String {
+        let kind = match self.kind {
+            NotificationKind::Error => "error",
+            NotificationKind::Info => "info",
+            NotificationKind::Ok => "ok",
+        };
+        let age = format_age(now.saturating_duration_since(self.shown_at));
+        format!("[{}] {}: {}", kind, age, self.text.replace('\n', " "))
+    }
+}
+
+fn format_age(age: Duration) -> String {
+    match age.as_secs() {
+        s if s < 60 => format!("{}s ago", s),
+        s if s < 60 * 60 => format!("{}m ago", s / 60),
+        s => format!("{}h ago", s / (60 * 60)),
+    }
+}
+
 impl<'t> Component for Notification<'t> {
     type RenderState = ();
 
@@ -256,4 +308,38 @@ mod tests {
         // TODO: this test fails :(
         // assert_eq!(count_lines("a\nb\nc\nd"), 4);
     }
+
+    #[test]
+    fn test_notification_duration() {
+        assert_eq!(NotificationKind::Info.duration(4), Duration::from_secs(4));
+        assert_eq!(NotificationKind::Error.duration(4), Duration::from_secs(5));
+        assert_eq!(
+            NotificationKind::Error.duration(u64::MAX),
+            Duration::from_secs(u64::MAX)
+        );
+    }
+
+    #[test]
+    fn test_notification_huge_timeout() {
+        let duration = NotificationKind::Error.duration(u64::MAX);
+        let notification = Notification::new("Error", duration);
+        assert!(notification.show_until > Instant::now() + Duration::from_secs(3600));
+    }
+
+    #[test]
+    fn test_log_entry_format() {
+        let now = Instant::now();
+        let entry = LogEntry {
+            kind: NotificationKind::Error,
+            text: "Failed to seek:\nnot playing".into(),
+            shown_at: now - Duration::from_secs(200),
+        };
+        assert_eq!(
+            entry.format(now),
+            "[error] 3m ago: Failed to seek: not playing"
+        );
+
+        assert_eq!(format_age(Duration::from_secs(5)), "5s ago");
+        assert_eq!(format_age(Duration::from_secs(7300)), "2h ago");
+    }
 }
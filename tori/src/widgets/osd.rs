@@ -0,0 +1,127 @@
+use crate::{
+    app::{
+        component::{Component, Mode, MyBackend},
+        App,
+    },
+    config::Config,
+    error::Result,
+    events,
+};
+use std::time::{Duration, Instant};
+use tui::{
+    layout::{Alignment, Rect},
+    style::{Color, Style},
+    text::Line,
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+    Frame,
+};
+
+const WIDTH: u16 = 32;
+const HEIGHT: u16 = 4;
+
+/// How long the OSD stays after the last change
+const DURATION: Duration = Duration::from_secs(1);
+
+/// On-screen display: a small box in the middle of the screen that shows a value that just
+/// changed, like the volume, with a bar. It goes away by itself shortly after.
+#[derive(Debug)]
+pub struct Osd {
+    label: String,
+    /// How full the bar is, from 0 to 1
+    ratio: f64,
+    show_until: Instant,
+}
+
+impl Default for Osd {
+    fn default() -> Self {
+        Self {
+            label: String::new(),
+            ratio: 0.0,
+            show_until: Instant::now(),
+        }
+    }
+}
+
+impl Osd {
+    pub fn new(label: impl Into<String>, ratio: f64) -> Self {
+        Self {
+            label: label.into(),
+            ratio,
+            show_until: Instant::now() + DURATION,
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        Instant::now() > self.show_until
+    }
+}
+
+/// A bar `width` cells wide, filled up to `ratio`
+fn bar(ratio: f64, width: usize) -> String {
+    let filled = (ratio.clamp(0.0, 1.0) * width as f64).round() as usize;
+    format!("{}{}", "█".repeat(filled), "░".repeat(width - filled))
+}
+
+impl Component for Osd {
+    type RenderState = ();
+
+    fn mode(&self) -> Mode {
+        Mode::Normal
+    }
+
+    fn render(&mut self, frame: &mut Frame<'_, MyBackend>, size: Rect, (): ()) {
+        if self.is_expired() || size.height < HEIGHT {
+            return;
+        }
+
+        let width = WIDTH.min(size.width);
+        let chunk = Rect {
+            x: size.x + (size.width - width) / 2,
+            y: size.y + (size.height - HEIGHT) / 2,
+            width,
+            height: HEIGHT,
+        };
+
+        let color = match Config::global().osd_color {
+            Some((r, g, b)) => Color::Rgb(r, g, b),
+            None => Color::LightCyan,
+        };
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(color));
+
+        let bar_width = width.saturating_sub(4) as usize;
+        let text = Paragraph::new(vec![
+            Line::from(self.label.as_str()),
+            Line::from(bar(self.ratio, bar_width)),
+        ])
+        .block(block)
+        .style(Style::default().fg(color))
+        .alignment(Alignment::Center);
+
+        frame.render_widget(Clear, chunk);
+        frame.render_widget(text, chunk);
+    }
+
+    /// No-op
+    fn handle_event(&mut self, _app: &mut App, _event: events::Event) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bar() {
+        assert_eq!(bar(0.0, 4), "░░░░");
+        assert_eq!(bar(0.5, 4), "██░░");
+        assert_eq!(bar(0.6, 4), "██░░");
+        assert_eq!(bar(1.0, 4), "████");
+        // mpv's volume goes past 100%
+        assert_eq!(bar(1.3, 4), "████");
+        assert_eq!(bar(-1.0, 4), "░░░░");
+    }
+}
@@ -1,7 +1,14 @@
-use tui::{buffer::Buffer, layout::Rect, style::Style, widgets::Widget};
+use tui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style},
+    widgets::Widget,
+};
+
+use crate::config::Config;
 
 /// Widget that draws a scrollbar at the right side of a chunk
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct Scrollbar {
     /// Line/position of the scrollable component that's currently selected
     pub position: u16,
@@ -10,21 +17,27 @@ pub struct Scrollbar {
     pub total_height: u16,
 
     pub style: Style,
+
+    /// Character the scrollbar is drawn with
+    pub symbol: String,
 }
 
 impl Scrollbar {
-    pub fn new(position: u16, total_height: u16) -> Self {
+    /// A scrollbar drawn with the `scrollbar_symbol` and `scrollbar_color` from the config. Without
+    /// a color, it's drawn with `style`.
+    pub fn themed(position: u16, total_height: u16, style: Style) -> Self {
+        let config = Config::global();
+        let style = match config.scrollbar_color {
+            Some((r, g, b)) => style.fg(Color::Rgb(r, g, b)),
+            None => style,
+        };
         Self {
             position,
             total_height,
-            ..Default::default()
+            style,
+            symbol: config.scrollbar_symbol.clone(),
         }
     }
-
-    pub fn with_style(mut self, style: Style) -> Self {
-        self.style = style;
-        self
-    }
 }
 
 impl Widget for Scrollbar {
@@ -36,7 +49,12 @@ impl Widget for Scrollbar {
             + area.top();
 
         for line in pos..(pos + scrollbar_height).min(area.bottom()) {
-            buf.set_string(area.right().saturating_sub(1), line, "█", self.style);
+            buf.set_string(
+                area.right().saturating_sub(1),
+                line,
+                &self.symbol,
+                self.style,
+            );
         }
     }
 }